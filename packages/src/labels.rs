@@ -2,6 +2,7 @@ pub const ICA_TRANSFER_LABEL: &str = "ica_transfer2";
 pub const LEND_AND_PROVIDE_LIQUIDITY_LABEL: &str = "lend_and_provide_liquidity";
 pub const MARS_WITHDRAW_LABEL: &str = "mars_withdraw";
 pub const SETTLE_OBLIGATION_LABEL: &str = "settle_obligation";
+pub const SETTLE_OBLIGATION_BATCH_LABEL: &str = "settle_obligation_batch";
 pub const REGISTER_OBLIGATION_LABEL: &str = "register_obligation";
 pub const PHASE_SHIFT_LABEL: &str = "phase_shift";
 pub const PROVIDE_LIQUIDIY_LABEL: &str = "provide_liquidity";
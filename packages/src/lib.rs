@@ -2,6 +2,7 @@ pub mod contracts;
 pub mod ibc_eureka_chain_ids;
 pub mod labels;
 pub mod phases;
+pub mod strategist;
 pub mod types;
 pub mod utils;
 pub mod verification;
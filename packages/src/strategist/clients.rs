@@ -0,0 +1,470 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::{Address, Bytes, TxHash},
+    providers::Provider,
+    rpc::types::{BlockTransactionsKind, TransactionRequest},
+    sol_types::SolCall,
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use valence_domain_clients::{
+    clients::{
+        coprocessor::CoprocessorClient, ethereum::EthereumClient,
+        ibc_eureka_route_client::IBCEurekaRouteClient, neutron::NeutronClient,
+        valence_indexer::OneWayVaultIndexerClient,
+    },
+    coprocessor::base_client::CoprocessorBaseClient,
+    cosmos::{base_client::BaseClient, wasm_client::WasmClient},
+    evm::{base_client::EvmBaseClient, request_provider_client::RequestProviderClient},
+    indexer::one_way_vault::OneWayVaultIndexer,
+};
+
+use crate::{
+    strategist::{layout::Domain, schedule::Preemption},
+    utils::{self, grpc_querier::GrpcQuerier},
+};
+
+/// domain clients that the strategy drives its phases through. every
+/// client sits behind a trait so that the phases can be run against the
+/// live domains as well as against an in-memory mock.
+#[derive(Clone)]
+pub struct StrategyClients {
+    pub ethereum: Arc<dyn EthereumDomain>,
+    pub neutron: Arc<dyn NeutronDomain>,
+    /// read access to every cosmos domain used by the route (Neutron included)
+    pub queriers: HashMap<Domain, Arc<dyn CosmosQuerier>>,
+    pub indexer: Arc<dyn WithdrawRequestIndexer>,
+    pub prover: Arc<dyn Prover>,
+    /// skip route api (eureka routes only)
+    pub eureka_router: Option<Arc<dyn EurekaRouter>>,
+}
+
+/// read access to a cosmos domain. every query takes an optional height,
+/// `None` queries the latest state.
+#[async_trait]
+pub trait CosmosQuerier: Send + Sync {
+    async fn latest_height(&self) -> anyhow::Result<u64>;
+
+    async fn query_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        height: Option<u64>,
+    ) -> anyhow::Result<u128>;
+
+    /// smart query of the contract with a json-encoded query message,
+    /// returning the json-encoded response
+    async fn query_contract_raw(
+        &self,
+        contract: &str,
+        query: Vec<u8>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<u8>>;
+
+    /// polls the balance until it reaches the expected amount. the polling
+    /// stops early once pre-empted.
+    async fn poll_until_expected_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        expected: u128,
+        poll_interval: u64,
+        max_attempts: u32,
+        preemption: Preemption,
+    ) -> anyhow::Result<u128> {
+        for attempt in 1..=max_attempts {
+            let balance = self.query_balance(address, denom, None).await?;
+            if balance >= expected {
+                return Ok(balance);
+            }
+            if attempt < max_attempts {
+                preemption.check(&format!("polling {address} for {denom}"))?;
+                tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+            }
+        }
+
+        Err(anyhow!(
+            "{address} {denom} balance did not reach {expected} after {max_attempts} attempts"
+        ))
+    }
+}
+
+/// typed smart queries, available on every cosmos querier (and so on
+/// the Neutron domain as well)
+#[async_trait]
+pub trait ContractQuerier {
+    /// performs a smart query of the given contract
+    async fn query_contract_state<T: DeserializeOwned, Q: Serialize + Send>(
+        &self,
+        contract: &str,
+        query: Q,
+        height: Option<u64>,
+    ) -> anyhow::Result<T>;
+}
+
+#[async_trait]
+impl<C: CosmosQuerier + ?Sized> ContractQuerier for C {
+    async fn query_contract_state<T: DeserializeOwned, Q: Serialize + Send>(
+        &self,
+        contract: &str,
+        query: Q,
+        height: Option<u64>,
+    ) -> anyhow::Result<T> {
+        let query = serde_json::to_vec(&query)?;
+        let response = self.query_contract_raw(contract, query, height).await?;
+
+        Ok(serde_json::from_slice(&response)?)
+    }
+}
+
+/// signing access to Neutron. every tx is awaited until its inclusion.
+#[async_trait]
+pub trait NeutronDomain: CosmosQuerier {
+    /// executes the contract with the json execute message, returning the
+    /// included tx
+    async fn execute_wasm(&self, contract: &str, msg: Value) -> anyhow::Result<NeutronTx>;
+
+    /// bank transfer from the signer account, returning the tx hash
+    async fn transfer(&self, to: &str, amount: u128, denom: &str) -> anyhow::Result<String>;
+}
+
+/// tx included on Neutron, along with the wasm events it emitted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NeutronTx {
+    pub hash: String,
+    /// attributes of every wasm event, as (key, value) pairs
+    pub wasm_events: Vec<Vec<(String, String)>>,
+}
+
+impl NeutronTx {
+    /// value of the `key` attribute of the first wasm event emitted by
+    /// `contract` that carries it
+    pub fn wasm_attribute(&self, contract: &str, key: &str) -> Option<&str> {
+        self.wasm_events
+            .iter()
+            .filter(|attributes| {
+                attributes
+                    .iter()
+                    .any(|(k, v)| k == "_contract_address" && v == contract)
+            })
+            .flatten()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// access to Ethereum
+#[async_trait]
+pub trait EthereumDomain: Send + Sync {
+    async fn block_number(&self) -> anyhow::Result<u64>;
+
+    /// timestamp of the latest block
+    async fn block_timestamp(&self) -> anyhow::Result<u64>;
+
+    /// current gas price, in wei
+    async fn gas_price(&self) -> anyhow::Result<u128>;
+
+    /// `eth_call` of the request at the given block (or the latest one, if `None`)
+    async fn call(&self, request: TransactionRequest, block: Option<u64>) -> anyhow::Result<Bytes>;
+
+    /// signs and broadcasts the transaction, returning its hash. see
+    /// `utils::eth_tx::confirm` for awaiting its confirmation.
+    async fn send(&self, request: TransactionRequest) -> anyhow::Result<TxHash>;
+
+    /// receipt of the transaction, if it was included in a block
+    async fn receipt(&self, tx_hash: TxHash) -> anyhow::Result<Option<EthReceipt>>;
+
+    /// replays the request on top of the given block, returning the revert
+    /// data if it reverts
+    async fn revert_data(
+        &self,
+        request: TransactionRequest,
+        block: u64,
+    ) -> anyhow::Result<Option<Bytes>>;
+}
+
+/// transaction receipt fields read by the strategist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthReceipt {
+    pub block_number: u64,
+    pub success: bool,
+    /// signer of the transaction
+    pub from: Address,
+}
+
+impl dyn EthereumDomain + '_ {
+    /// calls the contract function at the given block (or the latest one,
+    /// if `None`) and decodes its return values
+    pub async fn query<C: SolCall + Send>(
+        &self,
+        contract: Address,
+        call: C,
+        block: Option<u64>,
+    ) -> anyhow::Result<C::Return> {
+        let output = self.call(call_request(contract, &call), block).await?;
+
+        Ok(C::abi_decode_returns(&output, true)?)
+    }
+}
+
+/// builds the transaction request that calls the contract function
+pub fn call_request<C: SolCall>(contract: Address, call: &C) -> TransactionRequest {
+    TransactionRequest::default()
+        .to(contract)
+        .input(Bytes::from(call.abi_encode()).into())
+}
+
+/// zk proof returned by the coprocessor, decoded into the parameters
+/// expected by the authorizations contracts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkProof {
+    pub proof_program: Vec<u8>,
+    pub inputs_program: Vec<u8>,
+    pub proof_domain: Vec<u8>,
+}
+
+#[async_trait]
+pub trait Prover: Send + Sync {
+    /// requests the proof of the coprocessor app for the given input
+    async fn prove(&self, app_id: &str, input: &Value) -> anyhow::Result<ZkProof>;
+}
+
+#[async_trait]
+pub trait WithdrawRequestIndexer: Send + Sync {
+    /// ids of the vault withdraw requests starting from the given id, in order
+    async fn withdraw_request_ids(&self, from_id: u64) -> anyhow::Result<Vec<u64>>;
+}
+
+#[async_trait]
+pub trait EurekaRouter: Send + Sync {
+    /// skip api route of an IBC-Eureka transfer of the given amount
+    async fn route(&self, amount: String) -> anyhow::Result<Value>;
+}
+
+#[async_trait]
+impl CosmosQuerier for GrpcQuerier {
+    async fn latest_height(&self) -> anyhow::Result<u64> {
+        GrpcQuerier::latest_height(self).await
+    }
+
+    async fn query_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        height: Option<u64>,
+    ) -> anyhow::Result<u128> {
+        GrpcQuerier::query_balance(self, address, denom, height).await
+    }
+
+    async fn query_contract_raw(
+        &self,
+        contract: &str,
+        query: Vec<u8>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<u8>> {
+        GrpcQuerier::query_contract_raw(self, contract, query, height).await
+    }
+}
+
+/// live Neutron: queries go through the grpc querier, txs through the
+/// signing client
+pub struct LiveNeutron {
+    pub client: NeutronClient,
+    pub querier: GrpcQuerier,
+}
+
+#[async_trait]
+impl CosmosQuerier for LiveNeutron {
+    async fn latest_height(&self) -> anyhow::Result<u64> {
+        self.querier.latest_height().await
+    }
+
+    async fn query_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        height: Option<u64>,
+    ) -> anyhow::Result<u128> {
+        self.querier.query_balance(address, denom, height).await
+    }
+
+    async fn query_contract_raw(
+        &self,
+        contract: &str,
+        query: Vec<u8>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.querier
+            .query_contract_raw(contract, query, height)
+            .await
+    }
+}
+
+#[async_trait]
+impl NeutronDomain for LiveNeutron {
+    async fn execute_wasm(&self, contract: &str, msg: Value) -> anyhow::Result<NeutronTx> {
+        let tx_resp = self
+            .client
+            .execute_wasm(contract, msg, vec![], None)
+            .await?;
+
+        // poll for inclusion to avoid account sequence mismatch errors
+        let included = self.client.poll_for_tx(&tx_resp.hash).await?;
+
+        let wasm_events = included
+            .events
+            .into_iter()
+            .filter(|event| event.r#type == "wasm")
+            .map(|event| {
+                event
+                    .attributes
+                    .into_iter()
+                    .map(|attribute| (attribute.key, attribute.value))
+                    .collect()
+            })
+            .collect();
+
+        Ok(NeutronTx {
+            hash: tx_resp.hash,
+            wasm_events,
+        })
+    }
+
+    async fn transfer(&self, to: &str, amount: u128, denom: &str) -> anyhow::Result<String> {
+        let tx_resp = self.client.transfer(to, amount, denom, None).await?;
+        self.client.poll_for_tx(&tx_resp.hash).await?;
+
+        Ok(tx_resp.hash)
+    }
+}
+
+#[async_trait]
+impl EthereumDomain for EthereumClient {
+    async fn block_number(&self) -> anyhow::Result<u64> {
+        let eth_rp = self.get_request_provider().await?;
+
+        Ok(eth_rp.get_block_number().await?)
+    }
+
+    async fn block_timestamp(&self) -> anyhow::Result<u64> {
+        let eth_rp = self.get_request_provider().await?;
+        let block = eth_rp
+            .get_block_by_number(BlockNumberOrTag::Latest, BlockTransactionsKind::Hashes)
+            .await?
+            .ok_or_else(|| anyhow!("latest block not found"))?;
+
+        Ok(block.header.timestamp)
+    }
+
+    async fn gas_price(&self) -> anyhow::Result<u128> {
+        let eth_rp = self.get_request_provider().await?;
+
+        Ok(eth_rp.get_gas_price().await?)
+    }
+
+    async fn call(&self, request: TransactionRequest, block: Option<u64>) -> anyhow::Result<Bytes> {
+        let eth_rp = self.get_request_provider().await?;
+        let block = block.map(BlockId::number).unwrap_or_else(BlockId::latest);
+
+        Ok(eth_rp.call(&request).block(block).await?)
+    }
+
+    async fn send(&self, request: TransactionRequest) -> anyhow::Result<TxHash> {
+        let exec_response = self.sign_and_send(request).await?;
+
+        Ok(exec_response.transaction_hash)
+    }
+
+    async fn receipt(&self, tx_hash: TxHash) -> anyhow::Result<Option<EthReceipt>> {
+        let eth_rp = self.get_request_provider().await?;
+
+        let receipt = eth_rp.get_transaction_receipt(tx_hash).await?;
+
+        // receipts of pending transactions carry no block number
+        Ok(receipt.and_then(|receipt| {
+            Some(EthReceipt {
+                block_number: receipt.block_number?,
+                success: receipt.status(),
+                from: receipt.from,
+            })
+        }))
+    }
+
+    async fn revert_data(
+        &self,
+        request: TransactionRequest,
+        block: u64,
+    ) -> anyhow::Result<Option<Bytes>> {
+        let eth_rp = self.get_request_provider().await?;
+
+        match eth_rp.call(&request).block(BlockId::number(block)).await {
+            Ok(_) => Ok(None),
+            Err(e) => match e
+                .as_error_resp()
+                .and_then(|payload| payload.as_revert_data())
+            {
+                Some(data) => Ok(Some(data)),
+                None => Err(anyhow!("failed to replay the transaction: {e}")),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Prover for CoprocessorClient {
+    async fn prove(&self, app_id: &str, input: &Value) -> anyhow::Result<ZkProof> {
+        let response = CoprocessorBaseClient::prove(self, app_id, input).await?;
+
+        // extract the program and domain parameters by decoding the zkp
+        let (proof_program, inputs_program) = utils::decode(response.program)?;
+        let (proof_domain, _) = utils::decode(response.domain)?;
+
+        Ok(ZkProof {
+            proof_program,
+            inputs_program,
+            proof_domain,
+        })
+    }
+}
+
+#[async_trait]
+impl WithdrawRequestIndexer for OneWayVaultIndexerClient {
+    async fn withdraw_request_ids(&self, from_id: u64) -> anyhow::Result<Vec<u64>> {
+        let requests = self
+            .query_vault_withdraw_requests(Some(from_id), true)
+            .await?;
+
+        Ok(requests.into_iter().map(|(id, ..)| id).collect())
+    }
+}
+
+#[async_trait]
+impl EurekaRouter for IBCEurekaRouteClient {
+    async fn route(&self, amount: String) -> anyhow::Result<Value> {
+        self.query_skip_eureka_route(amount).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::TxKind;
+
+    use super::*;
+    use crate::types::sol_types::OneWayVault;
+
+    #[test]
+    fn test_call_request_targets_the_contract_with_the_encoded_call() {
+        let vault = Address::repeat_byte(1);
+        let request = call_request(vault, &OneWayVault::totalSupplyCall {});
+
+        assert_eq!(request.to, Some(TxKind::Call(vault)));
+        assert_eq!(
+            request.input.input(),
+            Some(&Bytes::from(OneWayVault::totalSupplyCall {}.abi_encode()))
+        );
+    }
+}
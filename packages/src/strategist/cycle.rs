@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+use crate::strategist::layout::Phase;
+
+/// how a phase of the cycle went
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PhaseStatus {
+    Succeeded,
+    Failed {
+        /// kind of the failure, see `StrategistError`
+        kind: String,
+        error: String,
+    },
+    Skipped {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PhaseReport {
+    pub phase: Phase,
+    #[serde(flatten)]
+    pub status: PhaseStatus,
+}
+
+/// outcome of every phase that followed the sentry, in the order they
+/// were run (or skipped) in
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CycleSummary {
+    pub phases: Vec<PhaseReport>,
+}
+
+impl CycleSummary {
+    pub fn record(&mut self, phase: Phase, status: PhaseStatus) {
+        self.phases.push(PhaseReport { phase, status });
+    }
+
+    /// status of the phase, if it was reached in the cycle
+    pub fn status(&self, phase: Phase) -> Option<&PhaseStatus> {
+        self.phases
+            .iter()
+            .find(|report| report.phase == phase)
+            .map(|report| &report.status)
+    }
+
+    pub fn succeeded(&self, phase: Phase) -> bool {
+        self.status(phase) == Some(&PhaseStatus::Succeeded)
+    }
+}
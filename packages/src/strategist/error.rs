@@ -0,0 +1,208 @@
+use std::fmt;
+
+use crate::utils::eth_tx::EthTxError;
+
+/// classified failure of a strategist phase. phases raise (or attach, as
+/// context) the variant wherever the cause of a failure is known, and the
+/// worker classifies every phase error with `StrategistError::classify`
+/// to decide whether it is worth retrying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrategistError {
+    /// rpc, grpc or api failure that is expected to clear up on its own
+    TransientNetwork(String),
+    /// an account does not hold the funds that a step relies on
+    InsufficientBalance(String),
+    /// the coprocessor failed to generate a proof
+    ProofFailed(String),
+    /// an Ethereum tx reverted, or a Neutron subroutine failed in the
+    /// processor
+    TxReverted(String),
+    /// an Ethereum tx was broadcast but not confirmed in time. it may still
+    /// land, so it must be reconciled before anything is sent in its place
+    TxPending(String),
+    /// the strategy is in a state that the strategist refuses to act upon
+    InvariantViolated(String),
+    /// the newly calculated rate tripped the rate history checks and was
+    /// not posted
+    RateAnomaly(String),
+    /// the vault is paused
+    VaultPaused,
+    /// the phase stopped between two of its steps for the mandatory rate
+    /// update to go out in time
+    Preempted(String),
+}
+
+impl StrategistError {
+    /// classifies the error that failed a phase. errors that were not
+    /// classified by the phase are most likely failed queries, and are
+    /// treated as transient.
+    pub fn classify(err: &anyhow::Error) -> StrategistError {
+        if let Some(e) = err.downcast_ref::<StrategistError>() {
+            return e.clone();
+        }
+        if let Some(e) = err.downcast_ref::<EthTxError>() {
+            return e.into();
+        }
+
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<StrategistError>() {
+                return e.clone();
+            }
+            if let Some(e) = cause.downcast_ref::<EthTxError>() {
+                return e.into();
+            }
+        }
+
+        StrategistError::TransientNetwork(format!("{err:#}"))
+    }
+
+    /// whether retrying the failed phase may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            StrategistError::TransientNetwork(_) | StrategistError::ProofFailed(_)
+        )
+    }
+
+    /// metric label of the variant
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StrategistError::TransientNetwork(_) => "transient_network",
+            StrategistError::InsufficientBalance(_) => "insufficient_balance",
+            StrategistError::ProofFailed(_) => "proof_failed",
+            StrategistError::TxReverted(_) => "tx_reverted",
+            StrategistError::TxPending(_) => "tx_pending",
+            StrategistError::InvariantViolated(_) => "invariant_violated",
+            StrategistError::RateAnomaly(_) => "rate_anomaly",
+            StrategistError::VaultPaused => "vault_paused",
+            StrategistError::Preempted(_) => "preempted",
+        }
+    }
+}
+
+impl From<&EthTxError> for StrategistError {
+    fn from(e: &EthTxError) -> Self {
+        match e {
+            EthTxError::Reverted { .. } => StrategistError::TxReverted(e.to_string()),
+            // the tx may still land; the next attempt re-reads the chain
+            // state before sending anything again
+            EthTxError::Submission(_) | EthTxError::Unconfirmed { .. } => {
+                StrategistError::TransientNetwork(e.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for StrategistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategistError::TransientNetwork(e) => write!(f, "network failure: {e}"),
+            StrategistError::InsufficientBalance(e) => write!(f, "insufficient balance: {e}"),
+            StrategistError::ProofFailed(e) => write!(f, "proof generation failed: {e}"),
+            StrategistError::TxReverted(e) => write!(f, "tx reverted: {e}"),
+            StrategistError::TxPending(e) => write!(f, "tx pending: {e}"),
+            StrategistError::InvariantViolated(e) => write!(f, "invariant violated: {e}"),
+            StrategistError::RateAnomaly(e) => write!(f, "rate anomaly: {e}"),
+            StrategistError::VaultPaused => write!(f, "vault is paused"),
+            StrategistError::Preempted(e) => write!(f, "pre-empted by the rate update: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StrategistError {}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::TxHash;
+    use anyhow::anyhow;
+
+    use super::*;
+
+    fn reverted() -> EthTxError {
+        EthTxError::Reverted {
+            tx_hash: TxHash::ZERO,
+            block: 1,
+            reason: Some("paused".to_string()),
+        }
+    }
+
+    fn unconfirmed() -> EthTxError {
+        EthTxError::Unconfirmed {
+            tx_hash: TxHash::ZERO,
+            confirmations: 2,
+        }
+    }
+
+    #[test]
+    fn test_unclassified_failures_are_transient() {
+        let class = StrategistError::classify(&anyhow!("connection reset"));
+
+        assert_eq!(
+            class,
+            StrategistError::TransientNetwork("connection reset".to_string())
+        );
+        assert!(class.is_retryable());
+    }
+
+    #[test]
+    fn test_classification_is_found_anywhere_in_the_chain() {
+        let err = anyhow::Error::new(StrategistError::ProofFailed("timeout".to_string()))
+            .context("failed to prove withdraw request 3");
+        assert_eq!(
+            StrategistError::classify(&err),
+            StrategistError::ProofFailed("timeout".to_string())
+        );
+
+        let err = anyhow::Error::new(reverted()).context("failed to route deposits");
+        assert!(matches!(
+            StrategistError::classify(&err),
+            StrategistError::TxReverted(_)
+        ));
+    }
+
+    #[test]
+    fn test_attached_classification_takes_precedence() {
+        let err = anyhow::Error::new(reverted()).context(StrategistError::VaultPaused);
+        assert_eq!(
+            StrategistError::classify(&err),
+            StrategistError::VaultPaused
+        );
+
+        let err = anyhow::Error::new(unconfirmed())
+            .context(StrategistError::TxPending("update_rate".to_string()));
+        assert!(!StrategistError::classify(&err).is_retryable());
+    }
+
+    #[test]
+    fn test_ethereum_tx_failures_are_classified_by_outcome() {
+        assert!(matches!(
+            StrategistError::from(&reverted()),
+            StrategistError::TxReverted(_)
+        ));
+        assert!(StrategistError::from(&unconfirmed()).is_retryable());
+        assert!(
+            StrategistError::from(&EthTxError::Submission(anyhow!("nonce too low"))).is_retryable()
+        );
+    }
+
+    #[test]
+    fn test_only_transient_and_proof_failures_are_retried() {
+        let retryable: Vec<_> = [
+            StrategistError::TransientNetwork(String::new()),
+            StrategistError::InsufficientBalance(String::new()),
+            StrategistError::ProofFailed(String::new()),
+            StrategistError::TxReverted(String::new()),
+            StrategistError::TxPending(String::new()),
+            StrategistError::InvariantViolated(String::new()),
+            StrategistError::RateAnomaly(String::new()),
+            StrategistError::VaultPaused,
+            StrategistError::Preempted(String::new()),
+        ]
+        .iter()
+        .filter(|e| e.is_retryable())
+        .map(StrategistError::kind)
+        .collect();
+
+        assert_eq!(retryable, vec!["transient_network", "proof_failed"]);
+    }
+}
@@ -0,0 +1,419 @@
+use alloy::{primitives::TxHash, rpc::types::TransactionRequest};
+use cosmwasm_std::{Binary, Uint128};
+use log::{error, info, warn};
+
+use crate::{
+    strategist::{
+        error::StrategistError, journal::JournalStep, layout::Domain, plan::PlannedAction,
+        transit::OutboundTransfer, Strategy,
+    },
+    utils::{
+        eth_tx::{self, EthTxError},
+        metrics,
+        valence_core::{self, ExecutionOutcome},
+    },
+};
+
+/// funds that are expected to arrive to an account as a result of a
+/// transfer initiated earlier in the phase
+pub(crate) struct Arrival<'a> {
+    /// label of the transfer that the funds arrive through
+    pub transfer: &'a str,
+    pub domain: Domain,
+    pub address: &'a str,
+    pub denom: &'a str,
+    /// account balance before the transfer was initiated
+    pub baseline: u128,
+    /// amount that left the source account
+    pub sent: u128,
+    /// amount that the transfer is quoted to deliver
+    pub quoted: u128,
+    /// how far below the quote the delivered amount may fall, in bps
+    pub tolerance_bps: u64,
+    /// how long the transfer is counted in transit if it never arrives
+    pub expiry_secs: u64,
+    pub tx_hash: Option<String>,
+    pub poll_interval: u64,
+    pub max_attempts: u32,
+}
+
+impl Arrival<'_> {
+    /// balance that the account is expected to reach once the transfer
+    /// is delivered
+    pub fn expected(&self) -> u128 {
+        self.baseline + min_delivery(self.quoted, self.tolerance_bps)
+    }
+}
+
+/// smallest amount that a transfer quoted to deliver `quoted` may deliver
+/// without being considered short
+pub(crate) fn min_delivery(quoted: u128, tolerance_bps: u64) -> u128 {
+    let tolerance = Uint128::new(quoted).multiply_ratio(tolerance_bps.min(10_000), 10_000u128);
+    quoted - tolerance.u128()
+}
+
+impl Strategy {
+    /// enqueues the messages under the given label and ticks the processor,
+    /// returning the outcome of the execution for the phase to act upon.
+    /// the step is journaled so that an interrupted run can be reconciled.
+    pub(crate) async fn run_neutron_subroutine(
+        &self,
+        phase: &str,
+        label: &str,
+        messages: Vec<Binary>,
+    ) -> anyhow::Result<ExecutionOutcome> {
+        if let Some(dry_run) = &self.dry_run {
+            dry_run.record(phase, PlannedAction::neutron_send_msgs(label, &messages));
+            dry_run.record(
+                phase,
+                PlannedAction::NeutronTick {
+                    processor: self.layout.neutron.processor.to_string(),
+                },
+            );
+            return Ok(ExecutionOutcome::Succeeded);
+        }
+
+        self.preemption
+            .check(&format!("enqueuing the {label} subroutine"))?;
+
+        let step_id = self.journal.start(
+            phase,
+            JournalStep::NeutronSubroutine {
+                label: label.to_string(),
+            },
+        )?;
+
+        let execution_id = valence_core::enqueue_neutron(
+            self.neutron_client.as_ref(),
+            &self.layout.neutron.authorizations,
+            label,
+            messages,
+        )
+        .await?;
+
+        self.tick_execution(phase, step_id, label, execution_id)
+            .await
+    }
+
+    /// ticks the processor through the enqueued execution and settles its
+    /// journal step according to the outcome
+    pub(crate) async fn tick_execution(
+        &self,
+        phase: &str,
+        step_id: u64,
+        label: &str,
+        execution_id: u64,
+    ) -> anyhow::Result<ExecutionOutcome> {
+        let outcome = valence_core::tick_neutron_execution(
+            self.neutron_client.as_ref(),
+            &self.layout.neutron.authorizations,
+            &self.layout.neutron.processor,
+            execution_id,
+            self.preemption,
+        )
+        .await?;
+
+        match &outcome {
+            ExecutionOutcome::Succeeded => self.journal.complete(phase, step_id)?,
+            // a failed subroutine left nothing behind to reconcile
+            ExecutionOutcome::Failed(e) => {
+                warn!(target: phase, "{label} subroutine (execution #{execution_id}) failed: {e}");
+                self.journal.skip(phase, step_id)?;
+            }
+            // the batch stays in the queue and gets flushed by the next sentry
+            ExecutionOutcome::Pending => {
+                warn!(target: phase, "{label} subroutine (execution #{execution_id}) is still pending");
+                self.journal.complete(phase, step_id)?;
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// signs and sends the transaction on Ethereum and awaits its
+    /// confirmation. the step is journaled so that an interrupted run can be
+    /// reconciled. returns the tx hash, or `None` in dry-run mode.
+    /// failures are `EthTxError`s.
+    pub(crate) async fn execute_eth_tx(
+        &self,
+        phase: &str,
+        label: &str,
+        request: TransactionRequest,
+    ) -> anyhow::Result<Option<TxHash>> {
+        if let Some(dry_run) = &self.dry_run {
+            dry_run.record(
+                phase,
+                PlannedAction::EthereumTx {
+                    label: label.to_string(),
+                    request,
+                },
+            );
+            return Ok(None);
+        }
+
+        self.preemption
+            .check(&format!("sending the ethereum {label} tx"))?;
+
+        let step_id = self.journal.start(
+            phase,
+            JournalStep::EthereumTx {
+                label: label.to_string(),
+                tx_hash: None,
+            },
+        )?;
+
+        let tx_hash = eth_tx::broadcast(self.eth_client.as_ref(), &request).await?;
+        // with the hash on record, an interrupted run can look the
+        // transaction up instead of guessing whether it went through
+        self.journal.amend(
+            phase,
+            step_id,
+            JournalStep::EthereumTx {
+                label: label.to_string(),
+                tx_hash: Some(tx_hash.to_string()),
+            },
+        )?;
+
+        match eth_tx::confirm(
+            self.eth_client.as_ref(),
+            request,
+            tx_hash,
+            self.eth_confirmations,
+        )
+        .await
+        {
+            Ok(tx_hash) => {
+                self.journal.complete(phase, step_id)?;
+                Ok(Some(tx_hash))
+            }
+            // a reverted tx left nothing behind to reconcile
+            Err(e @ EthTxError::Reverted { .. }) => {
+                warn!(target: phase, "ethereum {label} tx failed: {e}");
+                self.journal.skip(phase, step_id)?;
+                Err(e.into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// tops up the Neutron account with untrn to cover its ibc fees
+    pub(crate) async fn ensure_fees_coverage(
+        &self,
+        phase: &str,
+        account: &str,
+    ) -> anyhow::Result<()> {
+        if let Some(dry_run) = &self.dry_run {
+            dry_run.record(
+                phase,
+                PlannedAction::NeutronFeeCoverage {
+                    account: account.to_string(),
+                },
+            );
+            return Ok(());
+        }
+
+        valence_core::ensure_neutron_account_fees_coverage(self.neutron_client.as_ref(), account)
+            .await
+    }
+
+    /// blocks until the expected funds arrive, journaling the wait so that
+    /// a restarted strategist resumes it instead of re-sending the funds.
+    /// the delivered amount is tracked against the quote, and a transfer
+    /// that delivered less than the tolerance allows for gets alerted on.
+    /// until it arrives, the transfer is counted in transit.
+    pub(crate) async fn await_arrival(
+        &self,
+        phase: &str,
+        arrival: Arrival<'_>,
+    ) -> anyhow::Result<()> {
+        let expected = arrival.expected();
+
+        // nothing was sent out in dry-run mode, so there is nothing to wait for
+        if let Some(dry_run) = &self.dry_run {
+            dry_run.record(
+                phase,
+                PlannedAction::AwaitArrival {
+                    domain: arrival.domain,
+                    address: arrival.address.to_string(),
+                    denom: arrival.denom.to_string(),
+                    expected,
+                },
+            );
+            return Ok(());
+        }
+
+        let transit_id = self.transit.record(OutboundTransfer {
+            transfer: arrival.transfer.to_string(),
+            domain: arrival.domain,
+            address: arrival.address.to_string(),
+            denom: arrival.denom.to_string(),
+            sent: arrival.sent,
+            expected_out: arrival.quoted,
+            baseline: arrival.baseline,
+            min_delivery: min_delivery(arrival.quoted, arrival.tolerance_bps),
+            tx_hash: arrival.tx_hash.clone(),
+            expiry_secs: arrival.expiry_secs,
+        })?;
+
+        let step_id = self.journal.start(
+            phase,
+            JournalStep::AwaitArrival {
+                domain: arrival.domain,
+                address: arrival.address.to_string(),
+                denom: arrival.denom.to_string(),
+                expected,
+                tx_hash: arrival.tx_hash.clone(),
+            },
+        )?;
+
+        let polled = self
+            .poll_domain_balance(
+                arrival.domain,
+                arrival.address,
+                arrival.denom,
+                expected,
+                arrival.poll_interval,
+                arrival.max_attempts,
+            )
+            .await;
+
+        let balance = match polled {
+            Ok(balance) => balance,
+            // the transfer stays in transit, and its wait in flight for the
+            // journal to reconcile once the rate update is out
+            Err(e) if matches!(StrategistError::classify(&e), StrategistError::Preempted(_)) => {
+                return Err(e)
+            }
+            Err(e) => {
+                // a transfer is delivered all at once, so a balance that
+                // moved without reaching the expected amount means the
+                // transfer landed short rather than that it is in flight
+                let balance = self
+                    .domain_balance(arrival.domain, arrival.address, arrival.denom)
+                    .await?;
+                if balance <= arrival.baseline {
+                    return Err(e);
+                }
+                balance
+            }
+        };
+
+        self.track_delivery(phase, &arrival, balance.saturating_sub(arrival.baseline));
+        self.transit.remove(transit_id)?;
+
+        self.journal.complete(phase, step_id)
+    }
+
+    /// records the delivered share of the quoted amount, alerting on
+    /// deliveries that fell short of the quote beyond the tolerance
+    fn track_delivery(&self, phase: &str, arrival: &Arrival<'_>, delivered: u128) {
+        if arrival.quoted == 0 {
+            return;
+        }
+
+        let ratio = delivered as f64 / arrival.quoted as f64;
+        self.metrics.set_gauge(
+            metrics::TRANSFER_DELIVERY,
+            &[("transfer", arrival.transfer)],
+            ratio,
+        );
+
+        if delivered < min_delivery(arrival.quoted, arrival.tolerance_bps) {
+            error!(
+                target: phase,
+                "{} transfer under-delivered: {delivered} of the {} quoted arrived to {}, beyond the {}bps tolerance",
+                arrival.transfer,
+                arrival.quoted,
+                arrival.address,
+                arrival.tolerance_bps
+            );
+            self.metrics.inc_counter(
+                metrics::TRANSFER_SHORTFALLS,
+                &[("transfer", arrival.transfer)],
+                1.0,
+            );
+        } else {
+            info!(
+                target: phase,
+                "{} transfer delivered {delivered} of the {} quoted",
+                arrival.transfer,
+                arrival.quoted
+            );
+        }
+    }
+
+    pub(crate) async fn domain_balance(
+        &self,
+        domain: Domain,
+        address: &str,
+        denom: &str,
+    ) -> anyhow::Result<u128> {
+        self.querier(domain)?
+            .query_balance(address, denom, None)
+            .await
+    }
+
+    pub(crate) async fn poll_domain_balance(
+        &self,
+        domain: Domain,
+        address: &str,
+        denom: &str,
+        expected: u128,
+        poll_interval: u64,
+        max_attempts: u32,
+    ) -> anyhow::Result<u128> {
+        self.querier(domain)?
+            .poll_until_expected_balance(
+                address,
+                denom,
+                expected,
+                poll_interval,
+                max_attempts,
+                self.preemption,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arrival(baseline: u128, quoted: u128, tolerance_bps: u64) -> Arrival<'static> {
+        Arrival {
+            transfer: "cctp",
+            domain: Domain::Neutron,
+            address: "deposit",
+            denom: "uusdc",
+            baseline,
+            sent: quoted,
+            quoted,
+            tolerance_bps,
+            expiry_secs: 3_600,
+            tx_hash: None,
+            poll_interval: 5,
+            max_attempts: 10,
+        }
+    }
+
+    #[test]
+    fn test_min_delivery_takes_the_tolerance_off_the_quote() {
+        assert_eq!(min_delivery(10_000, 0), 10_000);
+        assert_eq!(min_delivery(10_000, 50), 9_950);
+        // the tolerance is rounded down, in favour of the quote
+        assert_eq!(min_delivery(999, 50), 995);
+        assert_eq!(min_delivery(0, 50), 0);
+    }
+
+    #[test]
+    fn test_min_delivery_tolerance_is_capped_at_the_whole_quote() {
+        assert_eq!(min_delivery(10_000, 10_000), 0);
+        assert_eq!(min_delivery(10_000, u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_arrival_expects_the_min_delivery_on_top_of_the_baseline() {
+        assert_eq!(arrival(500, 10_000, 50).expected(), 10_450);
+        assert_eq!(arrival(0, 10_000, 0).expected(), 10_000);
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::strategist::layout::Domain;
+
+/// a single step of a phase that mutates chain state. every step is
+/// journaled before it is carried out so that a strategist that crashed
+/// halfway through a step can reconcile it on restart instead of
+/// blindly re-sending it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalStep {
+    /// messages pushed to the Neutron processor under the given
+    /// authorization label, followed by a tick
+    NeutronSubroutine { label: String },
+    /// transaction signed and sent on Ethereum. the hash gets journaled
+    /// once the transaction is broadcast.
+    EthereumTx {
+        label: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tx_hash: Option<String>,
+    },
+    /// funds in transit towards an account. carries the hash of the
+    /// tx that initiated the transfer (if any) and the balance that
+    /// marks the arrival
+    AwaitArrival {
+        domain: Domain,
+        address: String,
+        denom: String,
+        expected: u128,
+        tx_hash: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    /// step was journaled and is about to be (or is being) carried out
+    Started,
+    /// step was carried out successfully
+    Completed,
+    /// step was found to be no longer applicable during reconciliation
+    Skipped,
+}
+
+/// one line of the journal file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub id: u64,
+    pub phase: String,
+    pub timestamp: u64,
+    pub status: StepStatus,
+    /// only present on `Started` records. status updates refer back to
+    /// the started record by its id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<JournalStep>,
+}
+
+/// append-only, newline-delimited json journal of the cycle steps.
+/// compacted at the end of every cycle down to the steps still in flight.
+pub struct CycleJournal {
+    /// backing file along with its path. `None` for a disabled journal.
+    file: Option<(PathBuf, Mutex<File>)>,
+    next_id: AtomicU64,
+}
+
+impl CycleJournal {
+    /// opens (or creates) the journal at the given path and resumes the
+    /// step id sequence from the records found in it
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| anyhow!("failed to open journal at {}: {e}", path.display()))?;
+
+        let next_id = read_records(&path)?
+            .iter()
+            .map(|r| r.id + 1)
+            .max()
+            .unwrap_or_default();
+
+        Ok(Self {
+            file: Some((path, Mutex::new(file))),
+            next_id: AtomicU64::new(next_id),
+        })
+    }
+
+    /// journal that records nothing and never reports in-flight steps.
+    /// used where nothing gets broadcast, e.g. in dry-run mode.
+    pub fn disabled() -> Self {
+        Self {
+            file: None,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// journals the start of a step and returns its id
+    pub fn start(&self, phase: &str, step: JournalStep) -> anyhow::Result<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.append(JournalRecord {
+            id,
+            phase: phase.to_string(),
+            timestamp: now()?,
+            status: StepStatus::Started,
+            step: Some(step),
+        })?;
+
+        Ok(id)
+    }
+
+    /// re-journals the step that is being carried out, e.g. to record
+    /// the hash of a transaction once it is broadcast
+    pub fn amend(&self, phase: &str, id: u64, step: JournalStep) -> anyhow::Result<()> {
+        self.append(JournalRecord {
+            id,
+            phase: phase.to_string(),
+            timestamp: now()?,
+            status: StepStatus::Started,
+            step: Some(step),
+        })
+    }
+
+    /// marks the step as successfully carried out
+    pub fn complete(&self, phase: &str, id: u64) -> anyhow::Result<()> {
+        self.finish(phase, id, StepStatus::Completed)
+    }
+
+    /// marks the step as no longer applicable
+    pub fn skip(&self, phase: &str, id: u64) -> anyhow::Result<()> {
+        self.finish(phase, id, StepStatus::Skipped)
+    }
+
+    /// returns all steps that were started but never completed or skipped,
+    /// in the order they were started. amended steps are returned as of
+    /// their latest amendment.
+    pub fn in_flight(&self) -> anyhow::Result<Vec<JournalRecord>> {
+        let Some((path, _)) = &self.file else {
+            return Ok(vec![]);
+        };
+
+        let mut started: BTreeMap<u64, JournalRecord> = BTreeMap::new();
+
+        for record in read_records(path)? {
+            match record.status {
+                StepStatus::Started => {
+                    started.insert(record.id, record);
+                }
+                StepStatus::Completed | StepStatus::Skipped => {
+                    started.remove(&record.id);
+                }
+            }
+        }
+
+        Ok(started.into_values().collect())
+    }
+
+    /// rewrites the journal with only the records of the steps still in
+    /// flight, dropping the history of the ones carried out or skipped.
+    /// the rewrite goes through a temporary file that replaces the journal
+    /// atomically, so a crash mid-compaction leaves either journal intact.
+    pub fn compact(&self) -> anyhow::Result<()> {
+        let Some((path, file)) = &self.file else {
+            return Ok(());
+        };
+
+        // hold the lock throughout so no record gets appended to the
+        // journal that is being replaced
+        let mut file = file
+            .lock()
+            .map_err(|_| anyhow!("journal file lock poisoned"))?;
+
+        let mut contents = String::new();
+        for record in self.in_flight()? {
+            contents.push_str(&serde_json::to_string(&record)?);
+            contents.push('\n');
+        }
+
+        let compacted = path.with_extension("compact");
+        let mut tmp = File::create(&compacted)?;
+        tmp.write_all(contents.as_bytes())?;
+        tmp.sync_data()?;
+        std::fs::rename(&compacted, path)
+            .map_err(|e| anyhow!("failed to compact journal at {}: {e}", path.display()))?;
+
+        *file = OpenOptions::new().append(true).open(path)?;
+
+        Ok(())
+    }
+
+    fn finish(&self, phase: &str, id: u64, status: StepStatus) -> anyhow::Result<()> {
+        self.append(JournalRecord {
+            id,
+            phase: phase.to_string(),
+            timestamp: now()?,
+            status,
+            step: None,
+        })
+    }
+
+    fn append(&self, record: JournalRecord) -> anyhow::Result<()> {
+        let Some((_, file)) = &self.file else {
+            return Ok(());
+        };
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        let mut file = file
+            .lock()
+            .map_err(|_| anyhow!("journal file lock poisoned"))?;
+        file.write_all(line.as_bytes())?;
+        // flush to disk before the step is carried out, otherwise a crash
+        // could lose the record of a step that already hit the chain
+        file.sync_data()?;
+
+        Ok(())
+    }
+}
+
+fn read_records(path: &Path) -> anyhow::Result<Vec<JournalRecord>> {
+    let file = File::open(path)?;
+    let mut records = vec![];
+
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        // a crash mid-write can leave a truncated last line behind,
+        // which is safe to ignore since the step was never carried out
+        if let Ok(record) = serde_json::from_str(&line) {
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
+
+fn now() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("{name}-{}.journal.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn tx(label: &str, tx_hash: Option<&str>) -> JournalStep {
+        JournalStep::EthereumTx {
+            label: label.to_string(),
+            tx_hash: tx_hash.map(str::to_string),
+        }
+    }
+
+    fn in_flight_steps(journal: &CycleJournal) -> Vec<(u64, JournalStep)> {
+        journal
+            .in_flight()
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.id, r.step.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_in_flight_replays_the_open_steps_as_of_their_last_amendment() {
+        let journal = CycleJournal::open(journal_path("replay")).unwrap();
+
+        let completed = journal.start("deposit", tx("deposit", None)).unwrap();
+        let amended = journal.start("update", tx("update_rate", None)).unwrap();
+        let skipped = journal.start("settlement", tx("settle", None)).unwrap();
+        let open = journal.start("settlement", tx("settle", None)).unwrap();
+        journal
+            .amend("update", amended, tx("update_rate", Some("0xabc")))
+            .unwrap();
+        journal.complete("deposit", completed).unwrap();
+        journal.skip("settlement", skipped).unwrap();
+
+        assert_eq!(
+            in_flight_steps(&journal),
+            vec![
+                (amended, tx("update_rate", Some("0xabc"))),
+                (open, tx("settle", None)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_resumes_the_ids_and_ignores_a_truncated_record() {
+        let path = journal_path("resume");
+        let journal = CycleJournal::open(&path).unwrap();
+        journal.start("deposit", tx("deposit", None)).unwrap();
+        let open = journal.start("deposit", tx("deposit", None)).unwrap();
+        drop(journal);
+
+        // a crash mid-write leaves half a record behind
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"id":2,"phase":"dep"#).unwrap();
+        file.write_all(b"\n").unwrap();
+
+        let journal = CycleJournal::open(&path).unwrap();
+        assert_eq!(journal.in_flight().unwrap().len(), 2);
+        assert_eq!(
+            journal.start("deposit", tx("deposit", None)).unwrap(),
+            open + 1
+        );
+    }
+
+    #[test]
+    fn test_compact_keeps_only_the_open_steps() {
+        let path = journal_path("compact");
+        let journal = CycleJournal::open(&path).unwrap();
+
+        for _ in 0..3 {
+            let id = journal.start("deposit", tx("deposit", None)).unwrap();
+            journal.complete("deposit", id).unwrap();
+        }
+        let open = journal.start("update", tx("update_rate", None)).unwrap();
+        journal
+            .amend("update", open, tx("update_rate", Some("0xabc")))
+            .unwrap();
+
+        journal.compact().unwrap();
+
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, 1);
+        assert_eq!(
+            in_flight_steps(&journal),
+            vec![(open, tx("update_rate", Some("0xabc")))]
+        );
+
+        // the journal keeps appending to the compacted file
+        journal.complete("update", open).unwrap();
+        let next = journal.start("deposit", tx("deposit", None)).unwrap();
+        assert!(next > open);
+        assert_eq!(in_flight_steps(&journal), vec![(next, tx("deposit", None))]);
+
+        let reopened = CycleJournal::open(&path).unwrap();
+        assert_eq!(
+            in_flight_steps(&reopened),
+            vec![(next, tx("deposit", None))]
+        );
+    }
+
+    #[test]
+    fn test_disabled_journal_records_nothing() {
+        let journal = CycleJournal::disabled();
+        journal.start("deposit", tx("deposit", None)).unwrap();
+        journal.compact().unwrap();
+
+        assert!(journal.in_flight().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,659 @@
+use std::{fmt, time::Duration};
+
+use alloy::primitives::{Address, U256};
+use anyhow::anyhow;
+use cosmwasm_std::Uint128;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    phases::{DEPOSIT_PHASE, REGISTRATION_PHASE, SETTLEMENT_PHASE, UPDATE_PHASE},
+    strategist::{rate_guard::RateGuard, rebalance::Rebalancer, sentry::SentryPredicate},
+};
+
+/// declarative description of a strategy. every strategist crate builds
+/// one of these from its domain configs and hands it over to the shared
+/// `Strategy` engine, which drives all of the phases based on it.
+///
+/// adding a new supervault or a new vault should only ever require
+/// extending the layout, not the phases.
+#[derive(Debug, Clone)]
+pub struct StrategyLayout {
+    /// ethereum-side vault and deposit configuration
+    pub ethereum: EthereumLayout,
+    /// neutron-side program configuration
+    pub neutron: NeutronLayout,
+    /// route taken by the deposits on their way from Ethereum to Neutron
+    pub route: DepositRoute,
+    /// actions performed with the funds that arrived on Neutron
+    pub entry: PositionEntry,
+    /// withdraw obligation registration throughput
+    pub registration: RegistrationLayout,
+    /// settlement (withdrawal) configuration
+    pub settlement: SettlementLayout,
+    /// redemption rate accounting configuration
+    pub update: UpdateLayout,
+    /// conditions gating the strategist cycle
+    pub sentry: SentryLayout,
+    /// retry policies of the phases
+    pub retry: RetryLayout,
+    /// order of the phases that follow the sentry, and how they depend on
+    /// each other
+    pub cycle: CycleLayout,
+}
+
+#[derive(Debug, Clone)]
+pub struct EthereumLayout {
+    /// ethereum node rpc url
+    pub rpc_url: String,
+    /// authorizations module
+    pub authorizations: Address,
+    /// ERC-4626-based vault
+    pub one_way_vault: Address,
+    /// deposit account where user deposits will settle until being routed out
+    pub deposit_account: Address,
+    /// deposit token ERC20 address
+    pub deposit_token: Address,
+    /// minimum deposit account balance to route the deposits out
+    pub transfer_threshold_amt: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct NeutronLayout {
+    /// grpc connection details
+    pub connection: ChainConnection,
+    /// authorizations module
+    pub authorizations: String,
+    /// processor coupled with the authorizations
+    pub processor: String,
+    /// deposit account where funds arrive from the bridge
+    pub deposit_account: String,
+    /// settlement account where funds will be sent to end users
+    pub settlement_account: String,
+    /// deposit token denom on neutron
+    pub deposit_denom: String,
+    /// clearing queue library
+    pub clearing_queue: String,
+    /// clearing queue coprocessor app id
+    pub clearing_queue_app_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainConnection {
+    pub grpc_url: String,
+    pub grpc_port: String,
+    pub chain_id: String,
+}
+
+/// cosmos domains that the strategy may hold funds on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Domain {
+    Neutron,
+    Gaia,
+    Lombard,
+    Noble,
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Domain::Neutron => "neutron",
+            Domain::Gaia => "gaia",
+            Domain::Lombard => "lombard",
+            Domain::Noble => "noble",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DepositRoute {
+    /// Ethereum -> Cosmos Hub via IBC-Eureka (optionally hopping through Lombard),
+    /// followed by Cosmos Hub ICA -> Neutron ICA IBC transfer
+    Eureka(EurekaRoute),
+    /// Ethereum -> Noble via CCTP, auto-forwarded from Noble to Neutron
+    Cctp(CctpRoute),
+}
+
+#[derive(Debug, Clone)]
+pub struct EurekaRoute {
+    /// cosmos hub grpc connection details
+    pub gaia: ChainConnection,
+    /// cosmos hub native chain denom
+    pub gaia_chain_denom: String,
+    /// cosmos hub ICA address controlled by the neutron Valence ICA
+    pub gaia_ica: String,
+    /// deposit denom as it arrives on cosmos hub
+    pub gaia_deposit_denom: String,
+    /// neutron Valence ICA that gets topped up with untrn to cover
+    /// the ICA IBC transfer fees, if any
+    pub neutron_ica: Option<String>,
+    /// IBC-Eureka coprocessor app id
+    pub coprocessor_app_id: String,
+    /// how far below the quoted amount the funds arriving to the cosmos
+    /// hub ICA and then to Neutron may fall, in bps. the eureka leg is
+    /// quoted by the skip route `amount_out`.
+    pub arrival_tolerance_bps: u64,
+    /// how long a transfer that never arrived keeps being counted in
+    /// transit, in seconds
+    pub transit_expiry_secs: u64,
+    /// lombard hop, for routes that convert the deposit on the way
+    pub lombard: Option<LombardHop>,
+}
+
+impl EurekaRoute {
+    /// eureka fees may move between the skip route quote and the transfer,
+    /// so arrivals may fall up to 1% short of the quoted `amount_out`
+    pub const DEFAULT_ARRIVAL_TOLERANCE_BPS: u64 = 100;
+    /// eureka transfers time out after 12h, after which the funds are
+    /// refunded rather than in transit
+    pub const DEFAULT_TRANSIT_EXPIRY_SECS: u64 = 43_200;
+}
+
+#[derive(Debug, Clone)]
+pub struct LombardHop {
+    /// lombard grpc connection details
+    pub connection: ChainConnection,
+    /// recovery ICA address on lombard
+    pub ica: String,
+    /// deposit denom on lombard
+    pub deposit_denom: String,
+    /// ibc eureka denom that arrives from eth
+    pub eureka_denom: String,
+    /// native denom issued on lombard chain
+    pub native_denom: String,
+    /// IBC entry point contract addr
+    pub entry_contract: String,
+    /// IBC callback contract addr
+    pub callback_contract: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CctpRoute {
+    /// noble grpc connection details
+    pub noble: ChainConnection,
+    /// noble fee denom
+    pub noble_fee_denom: String,
+    /// CCTP transfer library on Ethereum
+    pub cctp_transfer: Address,
+    /// how far below the amount burned on Ethereum the funds arriving to
+    /// the Neutron deposit account may fall, in bps
+    pub arrival_tolerance_bps: u64,
+    /// how long a transfer that never arrived keeps being counted in
+    /// transit, in seconds
+    pub transit_expiry_secs: u64,
+}
+
+impl CctpRoute {
+    /// CCTP mints the burned amount 1:1, so the arrival may only fall short
+    /// by the noble forwarding fees
+    pub const DEFAULT_ARRIVAL_TOLERANCE_BPS: u64 = 10;
+    /// CCTP transfers do not time out, so they are counted in transit for a
+    /// day before they are considered stuck
+    pub const DEFAULT_TRANSIT_EXPIRY_SECS: u64 = 86_400;
+}
+
+/// a set of library functions enqueued atomically under a single label
+/// once the Neutron deposit account holds at least `min_balance`
+#[derive(Debug, Clone)]
+pub struct PositionEntry {
+    pub label: String,
+    pub actions: Vec<EntryAction>,
+    pub min_balance: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryAction {
+    /// splitter library `Split`
+    Split,
+    /// mars lending library `Lend`
+    Lend,
+    /// supervaults lper library `ProvideLiquidity`
+    ProvideLiquidity,
+    /// maxBTC issuer library `Issue`
+    IssueMaxBtc,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegistrationLayout {
+    /// withdraw requests registered per batch. the registrations of a batch
+    /// are posted in order, and then ticked through in a single pass.
+    pub batch_size: usize,
+    /// proofs requested from the coprocessor at once
+    pub max_concurrent_proofs: usize,
+}
+
+impl Default for RegistrationLayout {
+    fn default() -> Self {
+        Self {
+            batch_size: 20,
+            max_concurrent_proofs: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SettlementLayout {
+    /// denoms that withdrawal obligations may be paid out in
+    pub denoms: Vec<SettlementDenom>,
+    /// most obligations settled by a single processor execution. capped by
+    /// the number of functions in the settlement authorization subroutine.
+    pub max_obligations_per_batch: usize,
+}
+
+impl SettlementLayout {
+    /// most obligations settled per processor execution, as far as the
+    /// settlement authorization holds the functions for them
+    pub const DEFAULT_MAX_OBLIGATIONS_PER_BATCH: usize = 10;
+}
+
+#[derive(Debug, Clone)]
+pub struct SettlementDenom {
+    pub denom: String,
+    pub source: SettlementSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementSource {
+    /// denom is expected to already be in the settlement account;
+    /// shortfalls are only reported
+    Held,
+    /// shortfalls are withdrawn from the Mars lending position
+    MarsWithdraw,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateLayout {
+    /// update rate scaling factor
+    pub rate_scaling_factor: Uint128,
+    /// rate update thresholds. if newly calculated rate
+    /// would result in an increase or decrease relative
+    /// to the current rate that would exceed these values,
+    /// vault gets paused and the update is skipped.
+    pub max_rate_increment_bps: u64,
+    pub max_rate_decrement_bps: u64,
+    /// multi-epoch checks against the history of posted rates
+    pub rate_guard: RateGuard,
+    /// whether Mars debts in denoms other than the deposit denom are left
+    /// out of the rate, rather than failing the update. they are alerted
+    /// on either way.
+    pub exclude_foreign_mars_debts: bool,
+    /// proposes updates of the Mars vs supervault split, if set
+    pub rebalance: Option<Rebalancer>,
+    /// positions that count towards the vault assets, all of them
+    /// expressed in the denom that the redemption rate is quoted in
+    pub positions: Vec<Position>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedBalance {
+    pub domain: Domain,
+    pub address: String,
+    pub denom: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Position {
+    /// deposit token balance of the ethereum deposit account
+    EthereumDeposit,
+    /// plain bank balance on one of the cosmos domains
+    Balance(TrackedBalance),
+    /// Mars lending position of the credit account owned by `owner`
+    MarsLend {
+        credit_manager: String,
+        owner: String,
+    },
+    /// Supervault LP shares held by `lp_holder`
+    Supervault {
+        vault: String,
+        depositor: String,
+        lp_holder: String,
+    },
+    /// deposit token held across `sources`, valued as the amount of
+    /// maxBTC that issuing it would mint
+    MaxBtcIssuance {
+        contract: String,
+        sources: Vec<Position>,
+    },
+}
+
+/// gating of the strategist cycle. read from the strategy config, where
+/// any field left out takes its default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SentryLayout {
+    /// predicate that has to hold for the cycle to proceed. re-evaluated
+    /// after every strategy timeout until it does.
+    pub predicate: SentryPredicate,
+    /// how long before the vault would pause itself over a stale rate the
+    /// sentry stops waiting for the predicate and forces an update, in
+    /// seconds
+    pub stale_rate_margin_secs: u64,
+}
+
+impl Default for SentryLayout {
+    fn default() -> Self {
+        Self {
+            predicate: SentryPredicate::Always,
+            stale_rate_margin_secs: 3_600,
+        }
+    }
+}
+
+/// retry policy of a phase. only the failures classified as retryable are
+/// retried (see `StrategistError`), with an exponential backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// runs of the phase per cycle, the first one included
+    pub max_attempts: u32,
+    /// backoff before the first retry, doubled with every subsequent one
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+}
+
+impl RetryPolicy {
+    /// backoff before retrying the given (1-based) attempt, or `None` if
+    /// it was the last one
+    pub fn backoff(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let secs = self
+            .initial_backoff_secs
+            .saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff_secs);
+
+        Some(Duration::from_secs(secs))
+    }
+}
+
+/// retry policies of every phase. read from the strategy config, where
+/// any phase left out keeps its default policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryLayout {
+    pub sentry: RetryPolicy,
+    pub deposit: RetryPolicy,
+    pub registration: RetryPolicy,
+    pub settlement: RetryPolicy,
+    /// the rate update is mandatory, so it gets the most patience
+    pub update: RetryPolicy,
+}
+
+impl Default for RetryLayout {
+    fn default() -> Self {
+        let policy = |max_attempts, initial_backoff_secs, max_backoff_secs| RetryPolicy {
+            max_attempts,
+            initial_backoff_secs,
+            max_backoff_secs,
+        };
+
+        Self {
+            sentry: policy(3, 10, 60),
+            deposit: policy(2, 30, 30),
+            registration: policy(3, 30, 120),
+            settlement: policy(2, 30, 30),
+            update: policy(5, 10, 120),
+        }
+    }
+}
+
+/// phases of the cycle that follow the sentry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Deposit,
+    Registration,
+    Settlement,
+    Update,
+}
+
+impl Phase {
+    /// name of the phase, as used for its log target and metric labels
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Deposit => DEPOSIT_PHASE,
+            Phase::Registration => REGISTRATION_PHASE,
+            Phase::Settlement => SETTLEMENT_PHASE,
+            Phase::Update => UPDATE_PHASE,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// how a failure of the phase affects the rest of the cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseCriticality {
+    /// failure is reported, and the cycle carries on with the phases that
+    /// do not depend on it
+    BestEffort,
+    /// failure fails the cycle, once the rest of the phases have run
+    Mandatory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseSpec {
+    pub phase: Phase,
+    pub criticality: PhaseCriticality,
+    /// phases that have to succeed earlier in the cycle for this one to
+    /// run. the phase is skipped otherwise.
+    #[serde(default)]
+    pub depends_on: Vec<Phase>,
+}
+
+/// order and criticality of the phases following the sentry. read from
+/// the strategy config, where leaving it out keeps the default cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CycleLayout {
+    /// phases in the order they are run in. phases left out never run.
+    pub phases: Vec<PhaseSpec>,
+}
+
+impl CycleLayout {
+    /// checks that the cycle runs the rate update, lists every phase once,
+    /// and only lets phases depend on the ones ordered before them. a
+    /// dependency on a later phase would have the phase skipped every cycle.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (i, spec) in self.phases.iter().enumerate() {
+            let earlier = &self.phases[..i];
+
+            if earlier.iter().any(|s| s.phase == spec.phase) {
+                return Err(anyhow!("cycle layout lists the {} phase twice", spec.phase));
+            }
+
+            for dependency in &spec.depends_on {
+                if !earlier.iter().any(|s| s.phase == *dependency) {
+                    return Err(anyhow!(
+                        "the {} phase depends on the {dependency} phase, which is not ordered before it",
+                        spec.phase
+                    ));
+                }
+            }
+        }
+
+        if !self.phases.iter().any(|s| s.phase == Phase::Update) {
+            return Err(anyhow!("cycle layout does not run the rate update"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for CycleLayout {
+    /// deposit, registration and settlement are best-effort and run
+    /// independently of each other, so that degraded deposit routing never
+    /// holds up the withdrawals or the mandatory rate update
+    fn default() -> Self {
+        let spec = |phase, criticality| PhaseSpec {
+            phase,
+            criticality,
+            depends_on: vec![],
+        };
+
+        Self {
+            phases: vec![
+                spec(Phase::Deposit, PhaseCriticality::BestEffort),
+                spec(Phase::Registration, PhaseCriticality::BestEffort),
+                spec(Phase::Settlement, PhaseCriticality::BestEffort),
+                spec(Phase::Update, PhaseCriticality::Mandatory),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap_until_the_last_attempt() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 30,
+        };
+
+        let backoffs: Vec<_> = (1..=4).map(|attempt| policy.backoff(attempt)).collect();
+        assert_eq!(
+            backoffs,
+            vec![
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(20)),
+                Some(Duration::from_secs(30)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_attempt_is_never_retried() {
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 30,
+        };
+
+        assert_eq!(policy.backoff(1), None);
+    }
+
+    #[test]
+    fn test_phases_left_out_of_the_config_keep_their_default_policy() {
+        let retry: RetryLayout = toml::from_str(
+            r#"
+            [update]
+            max_attempts = 8
+            initial_backoff_secs = 5
+            max_backoff_secs = 60
+            "#,
+        )
+        .unwrap();
+
+        let defaults = RetryLayout::default();
+        assert_eq!(
+            retry.update,
+            RetryPolicy {
+                max_attempts: 8,
+                initial_backoff_secs: 5,
+                max_backoff_secs: 60,
+            }
+        );
+        assert_eq!(retry.sentry, defaults.sentry);
+        assert_eq!(retry.deposit, defaults.deposit);
+        assert_eq!(retry.registration, defaults.registration);
+        assert_eq!(retry.settlement, defaults.settlement);
+    }
+
+    fn spec(phase: Phase, depends_on: Vec<Phase>) -> PhaseSpec {
+        PhaseSpec {
+            phase,
+            criticality: PhaseCriticality::BestEffort,
+            depends_on,
+        }
+    }
+
+    #[test]
+    fn test_default_cycle_layout_is_valid() {
+        CycleLayout::default().validate().unwrap();
+    }
+
+    #[test]
+    fn test_cycle_layout_rejects_a_dependency_on_a_later_phase() {
+        let cycle = CycleLayout {
+            phases: vec![
+                spec(Phase::Settlement, vec![Phase::Registration]),
+                spec(Phase::Registration, vec![]),
+                spec(Phase::Update, vec![]),
+            ],
+        };
+
+        let err = cycle.validate().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("depends on the registration phase, which is not ordered before it"));
+    }
+
+    #[test]
+    fn test_cycle_layout_rejects_a_phase_listed_twice() {
+        let cycle = CycleLayout {
+            phases: vec![
+                spec(Phase::Deposit, vec![]),
+                spec(Phase::Update, vec![]),
+                spec(Phase::Deposit, vec![]),
+            ],
+        };
+
+        assert!(cycle.validate().unwrap_err().to_string().contains("twice"));
+    }
+
+    #[test]
+    fn test_cycle_layout_without_the_update_is_rejected() {
+        let cycle = CycleLayout {
+            phases: vec![spec(Phase::Deposit, vec![])],
+        };
+
+        assert!(cycle
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("does not run the rate update"));
+    }
+
+    #[test]
+    fn test_cycle_layout_is_read_from_toml() {
+        let cycle: CycleLayout = toml::from_str(
+            r#"
+            [[phases]]
+            phase = "registration"
+            criticality = "best_effort"
+
+            [[phases]]
+            phase = "settlement"
+            criticality = "best_effort"
+            depends_on = ["registration"]
+
+            [[phases]]
+            phase = "update"
+            criticality = "mandatory"
+            "#,
+        )
+        .unwrap();
+
+        cycle.validate().unwrap();
+        let phases: Vec<_> = cycle.phases.iter().map(|s| s.phase).collect();
+        assert_eq!(
+            phases,
+            vec![Phase::Registration, Phase::Settlement, Phase::Update]
+        );
+        assert_eq!(cycle.phases[1].depends_on, vec![Phase::Registration]);
+        assert!(cycle.phases[0].depends_on.is_empty());
+        assert_eq!(cycle.phases[2].criticality, PhaseCriticality::Mandatory);
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{BTreeMap, HashMap};
+
+use cosmwasm_std::Coin;
+
+use crate::strategist::layout::{SettlementDenom, SettlementSource};
+
+/// liquidity that the settlement account holds, or can pull in, to pay out
+/// the withdraw obligations
+#[derive(Debug, Clone, Default)]
+pub struct LiquiditySources {
+    /// settlement account balances, per denom
+    pub balances: HashMap<String, u128>,
+    /// amount that can be withdrawn from the Mars lending position, per denom
+    pub mars: HashMap<String, u128>,
+}
+
+/// action that pulls liquidity into the settlement account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiquidityAction {
+    MarsWithdraw { denom: String, amount: u128 },
+}
+
+/// part of the payouts that no source can cover
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortfall {
+    pub denom: String,
+    pub amount: u128,
+}
+
+/// plans the minimal set of actions that pull enough liquidity into the
+/// settlement account to cover the payouts, or returns the shortfalls if
+/// the sources cannot cover them.
+///
+/// Mars withdrawals cover the deficits to the exact amount. denoms that
+/// are already covered never get an action.
+pub fn plan_liquidity(
+    payouts: &[Coin],
+    denoms: &[SettlementDenom],
+    sources: &LiquiditySources,
+) -> Result<Vec<LiquidityAction>, Vec<Shortfall>> {
+    let owed: BTreeMap<&str, u128> = payouts
+        .iter()
+        .map(|payout| (payout.denom.as_str(), payout.amount.u128()))
+        .collect();
+    let mut balances = sources.balances.clone();
+    let deficit = |balances: &HashMap<String, u128>, denom: &str| {
+        owed.get(denom)
+            .copied()
+            .unwrap_or_default()
+            .saturating_sub(balances.get(denom).copied().unwrap_or_default())
+    };
+
+    let mut actions = vec![];
+
+    for settlement_denom in denoms {
+        if settlement_denom.source != SettlementSource::MarsWithdraw {
+            continue;
+        }
+
+        let amount = deficit(&balances, &settlement_denom.denom).min(
+            sources
+                .mars
+                .get(&settlement_denom.denom)
+                .copied()
+                .unwrap_or_default(),
+        );
+        if amount == 0 {
+            continue;
+        }
+
+        *balances.entry(settlement_denom.denom.clone()).or_default() += amount;
+        actions.push(LiquidityAction::MarsWithdraw {
+            denom: settlement_denom.denom.clone(),
+            amount,
+        });
+    }
+
+    let shortfalls: Vec<Shortfall> = owed
+        .keys()
+        .filter_map(|denom| match deficit(&balances, denom) {
+            0 => None,
+            amount => Some(Shortfall {
+                denom: denom.to_string(),
+                amount,
+            }),
+        })
+        .collect();
+
+    if shortfalls.is_empty() {
+        Ok(actions)
+    } else {
+        Err(shortfalls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+
+    use super::*;
+
+    fn denoms() -> Vec<SettlementDenom> {
+        vec![
+            SettlementDenom {
+                denom: "uusdc".to_string(),
+                source: SettlementSource::MarsWithdraw,
+            },
+            SettlementDenom {
+                denom: "lp".to_string(),
+                source: SettlementSource::Held,
+            },
+        ]
+    }
+
+    fn sources(balances: &[(&str, u128)], mars: &[(&str, u128)]) -> LiquiditySources {
+        let to_map = |amounts: &[(&str, u128)]| {
+            amounts
+                .iter()
+                .map(|(denom, amount)| (denom.to_string(), *amount))
+                .collect()
+        };
+
+        LiquiditySources {
+            balances: to_map(balances),
+            mars: to_map(mars),
+        }
+    }
+
+    #[test]
+    fn test_covered_payouts_need_no_withdrawal() {
+        let payouts = [coin(100, "uusdc"), coin(50, "lp")];
+
+        let actions = plan_liquidity(
+            &payouts,
+            &denoms(),
+            &sources(&[("uusdc", 100), ("lp", 80)], &[("uusdc", 1_000)]),
+        )
+        .unwrap();
+
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn test_mars_withdrawal_covers_the_exact_deficit() {
+        let payouts = [coin(300, "uusdc")];
+
+        let actions = plan_liquidity(
+            &payouts,
+            &denoms(),
+            &sources(&[("uusdc", 120)], &[("uusdc", 1_000)]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            actions,
+            vec![LiquidityAction::MarsWithdraw {
+                denom: "uusdc".to_string(),
+                amount: 180,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_uncoverable_payouts_return_the_shortfalls() {
+        let payouts = [coin(300, "uusdc"), coin(50, "lp"), coin(10, "uatom")];
+
+        let shortfalls = plan_liquidity(
+            &payouts,
+            &denoms(),
+            &sources(&[("uusdc", 100), ("lp", 20)], &[("uusdc", 150)]),
+        )
+        .unwrap_err();
+
+        // held denoms and denoms without a source are only reported
+        assert_eq!(
+            shortfalls,
+            vec![
+                Shortfall {
+                    denom: "lp".to_string(),
+                    amount: 30,
+                },
+                Shortfall {
+                    denom: "uatom".to_string(),
+                    amount: 10,
+                },
+                Shortfall {
+                    denom: "uusdc".to_string(),
+                    amount: 50,
+                },
+            ]
+        );
+    }
+}
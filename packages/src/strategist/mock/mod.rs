@@ -0,0 +1,1435 @@
+//! in-memory implementations of the domain clients, for running the
+//! strategist phases deterministically without network access.
+//!
+//! every client handed out by `MockDomains::clients` shares the same
+//! `MockState`, so that e.g. a CCTP transfer sent on Ethereum shows up in
+//! the Neutron balances. queries are always served from the latest state,
+//! regardless of the height they are pinned to.
+//!
+//! the tests next to the mock are end-to-end scenarios that run phases and
+//! whole cycles against it. pure logic is unit tested in its own module.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use alloy::{
+    primitives::{Address, Bytes, TxHash, U256},
+    rpc::types::TransactionRequest,
+    sol_types::{Revert, SolCall, SolError},
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use cosmwasm_std::{Binary, Coin, Decimal, Uint128};
+use serde_json::{json, Value};
+use valence_authorization_utils::{
+    authorization::Subroutine,
+    authorization_message::{Message, MessageDetails, MessageType},
+    builders::AtomicSubroutineBuilder,
+    callback::ExecutionResult,
+    function::AtomicFunction,
+};
+use valence_library_utils::LibraryAccountType;
+
+use crate::{
+    labels::{
+        CCTP_TRANSFER_LABEL, ICA_TRANSFER_LABEL, MARS_WITHDRAW_LABEL, REGISTER_OBLIGATION_LABEL,
+        SETTLE_OBLIGATION_BATCH_LABEL, SETTLE_OBLIGATION_LABEL,
+    },
+    strategist::{
+        clients::{
+            CosmosQuerier, EthReceipt, EthereumDomain, EurekaRouter, NeutronDomain, NeutronTx,
+            Prover, StrategyClients, WithdrawRequestIndexer, ZkProof,
+        },
+        layout::{DepositRoute, Domain, Position, SettlementSource, StrategyLayout},
+        schedule::Preemption,
+    },
+    types::sol_types::{Authorization, OneWayVault, ERC20},
+    utils,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// red bank that the mock credit managers lend into
+const RED_BANK: &str = "red_bank";
+
+/// effect of a processed subroutine on the mock state. receives the json
+/// execute messages of the subroutine (or the zk input, for zk
+/// authorizations).
+pub type SubroutineEffect =
+    Arc<dyn Fn(&mut MockState, &[Value]) -> anyhow::Result<()> + Send + Sync>;
+
+/// state of every mocked domain
+#[derive(Debug, Default, Clone)]
+pub struct MockState {
+    /// height of every cosmos domain (and the Ethereum block number),
+    /// bumped with every tx
+    pub height: u64,
+    /// cosmos bank balances, keyed by (domain, address, denom)
+    pub balances: HashMap<(Domain, String, String), u128>,
+    /// batches waiting in the Neutron processor queue
+    pub processor_queue: VecDeque<MockBatch>,
+    /// execution id of the latest batch pushed to the processor queue
+    pub latest_execution_id: u64,
+    /// labels of the batches that other users push to the processor queue
+    /// right after every authorizations execution, as enqueuing is
+    /// permissionless
+    pub foreign_labels: Vec<String>,
+    /// processor callbacks recorded by the authorizations contract, keyed
+    /// by execution id
+    pub processor_callbacks: HashMap<u64, MockCallback>,
+    /// number of functions in the subroutines of the authorizations, keyed
+    /// by label. like atomic subroutines, `send_msgs` carrying a different
+    /// number of messages are rejected.
+    pub subroutine_functions: HashMap<String, usize>,
+    /// obligations registered into the clearing queue, in order
+    pub clearing_queue: VecDeque<MockObligation>,
+    /// id of the latest obligation registered into the clearing queue
+    pub latest_obligation_id: Option<u64>,
+    /// Mars credit accounts, keyed by account id. the account that the
+    /// lending library created for an owner has the owner as its id.
+    pub mars_accounts: BTreeMap<String, MockCreditAccount>,
+    /// supervaults, keyed by contract address
+    pub supervaults: HashMap<String, MockSupervault>,
+    /// maxBTC minted per deposit token, keyed by contract address
+    pub maxbtc_rates: HashMap<String, Decimal>,
+    /// markets of the Mars red bank that every credit manager lends into,
+    /// keyed by denom. Mars withdrawals debit the market collateral.
+    pub red_bank_markets: HashMap<String, MockMarket>,
+    /// deposit split of the dynamic ratio query provider, keyed by receiver
+    pub split_ratios: HashMap<String, Decimal>,
+    /// ERC20 balances, keyed by (token, holder)
+    pub erc20_balances: HashMap<(Address, Address), u128>,
+    /// latest Ethereum block timestamp
+    pub timestamp: u64,
+    /// Ethereum gas price, in wei
+    pub gas_price: u128,
+    pub vault: MockVault,
+    /// withdraw requests submitted to the vault, in order
+    pub withdraw_requests: Vec<MockWithdrawRequest>,
+    /// withdraw requests that the coprocessor fails to prove
+    pub failing_proofs: HashSet<u64>,
+    /// withdraw requests of which the proven registration fails to be
+    /// posted to the authorizations contract
+    pub failing_posts: HashSet<u64>,
+    /// withdraw requests of which the posted registration fails in the
+    /// processor
+    pub failing_registrations: HashSet<u64>,
+    /// time that the coprocessor takes to prove a withdraw request
+    pub proof_latency: Duration,
+    /// proofs being generated at the moment, and the most of them that
+    /// were ever generated at once
+    pub proofs_in_flight: usize,
+    pub max_proofs_in_flight: usize,
+    /// the OneWayVault indexer fails every query
+    pub indexer_down: bool,
+    /// number of the latest withdraw requests that the indexer has yet to
+    /// pick up
+    pub indexer_lag: usize,
+    /// fee that the skip route takes from IBC-Eureka transfers, in bps
+    pub eureka_fee_bps: u64,
+    /// transfers bridged out of Ethereum never arrive, e.g. because of a
+    /// stalled relayer
+    pub stalled_bridge: bool,
+    /// transfers bridged out of Ethereum that have yet to land on their
+    /// destination. they land once the destination account gets polled.
+    pub in_flight: Vec<MockTransfer>,
+    /// share of the transfers bridged out of Ethereum that gets lost on the
+    /// way on top of the quoted fees, in bps
+    pub bridge_shortfall_bps: u64,
+    /// Ethereum transactions (and queries) calling these selectors revert
+    /// with the given reason
+    pub reverting_calls: HashMap<[u8; 4], String>,
+    /// receipts of the sent Ethereum transactions
+    pub receipts: HashMap<TxHash, MockReceipt>,
+}
+
+/// Ethereum transaction receipt, along with the revert data of reverted
+/// transactions
+#[derive(Debug, Clone)]
+pub struct MockReceipt {
+    pub block: u64,
+    pub input: Bytes,
+    pub revert: Option<Bytes>,
+}
+
+/// transfer bridged out of Ethereum, on its way to a cosmos account
+#[derive(Debug, Clone)]
+pub struct MockTransfer {
+    pub domain: Domain,
+    pub address: String,
+    pub denom: String,
+    pub amount: u128,
+}
+
+/// processor callback of an execution
+#[derive(Debug, Clone)]
+pub struct MockCallback {
+    pub label: String,
+    pub result: ExecutionResult,
+}
+
+/// batch waiting in the processor queue
+#[derive(Debug, Clone)]
+pub struct MockBatch {
+    pub id: u64,
+    pub label: String,
+    pub messages: Vec<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockObligation {
+    pub id: u64,
+    pub recipient: String,
+    pub payout_coins: Vec<Coin>,
+}
+
+/// supervault holding `reserve_0` and `reserve_1` against `total_shares`
+/// of its LP token
+#[derive(Debug, Clone)]
+pub struct MockSupervault {
+    pub lp_denom: String,
+    pub token_0: String,
+    pub token_1: String,
+    pub total_shares: u128,
+    pub reserve_0: u128,
+    pub reserve_1: u128,
+}
+
+/// Mars credit account positions, per denom
+#[derive(Debug, Clone, Default)]
+pub struct MockCreditAccount {
+    pub owner: String,
+    pub lends: HashMap<String, u128>,
+    pub deposits: HashMap<String, u128>,
+    pub debts: HashMap<String, u128>,
+}
+
+/// Mars red bank market rates and totals
+#[derive(Debug, Clone, Default)]
+pub struct MockMarket {
+    pub liquidity_rate: Decimal,
+    pub borrow_rate: Decimal,
+    pub utilization_rate: Decimal,
+    pub collateral_total: u128,
+    pub debt_total: u128,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MockVault {
+    pub redemption_rate: U256,
+    pub total_supply: U256,
+    pub last_rate_update: u64,
+    pub max_rate_update_delay: u64,
+    pub paused: bool,
+}
+
+impl MockVault {
+    /// issues the given shares at a redemption rate of 1.0
+    pub fn issue_at_par(&mut self, total_supply: u64) {
+        self.total_supply = U256::from(total_supply);
+        self.redemption_rate = U256::from(10u128.pow(18));
+    }
+}
+
+/// withdraw request along with the payout that the clearing queue app
+/// proves for it
+#[derive(Debug, Clone)]
+pub struct MockWithdrawRequest {
+    pub id: u64,
+    pub receiver: String,
+    pub payout_coins: Vec<Coin>,
+}
+
+impl MockState {
+    /// pushes the batch to the processor queue, returning its execution id
+    pub fn enqueue(&mut self, label: &str, messages: Vec<Value>) -> u64 {
+        self.latest_execution_id += 1;
+        let id = self.latest_execution_id;
+        self.processor_callbacks.insert(
+            id,
+            MockCallback {
+                label: label.to_string(),
+                result: ExecutionResult::InProcess,
+            },
+        );
+        self.processor_queue.push_back(MockBatch {
+            id,
+            label: label.to_string(),
+            messages,
+        });
+
+        id
+    }
+
+    pub fn balance(&self, domain: Domain, address: &str, denom: &str) -> u128 {
+        self.balances
+            .get(&(domain, address.to_string(), denom.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_balance(&mut self, domain: Domain, address: &str, denom: &str, amount: u128) {
+        self.balances
+            .insert((domain, address.to_string(), denom.to_string()), amount);
+    }
+
+    pub fn credit(&mut self, domain: Domain, address: &str, denom: &str, amount: u128) {
+        *self
+            .balances
+            .entry((domain, address.to_string(), denom.to_string()))
+            .or_default() += amount;
+    }
+
+    /// bridges the amount out of Ethereum to the given account, less the
+    /// configured shortfall. stalled transfers never land.
+    fn bridge(&mut self, domain: Domain, address: &str, denom: &str, amount: u128) {
+        if self.stalled_bridge {
+            return;
+        }
+        let shortfall = Uint128::new(amount).multiply_ratio(self.bridge_shortfall_bps, 10_000u128);
+        self.in_flight.push(MockTransfer {
+            domain,
+            address: address.to_string(),
+            denom: denom.to_string(),
+            amount: amount - shortfall.u128(),
+        });
+    }
+
+    /// lands the transfers in flight to the given account
+    pub fn land_transfers(&mut self, domain: Domain, address: &str, denom: &str) {
+        let (landed, in_flight) = std::mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition(|t| t.domain == domain && t.address == address && t.denom == denom);
+        self.in_flight = in_flight;
+        for transfer in landed {
+            self.credit(domain, address, denom, transfer.amount);
+        }
+    }
+
+    pub fn debit(
+        &mut self,
+        domain: Domain,
+        address: &str,
+        denom: &str,
+        amount: u128,
+    ) -> anyhow::Result<()> {
+        let balance = self.balance(domain, address, denom);
+        let remaining = balance.checked_sub(amount).ok_or_else(|| {
+            anyhow!("{domain} {address} has {balance}{denom}, cannot debit {amount}")
+        })?;
+        self.set_balance(domain, address, denom, remaining);
+
+        Ok(())
+    }
+
+    pub fn erc20_balance(&self, token: Address, holder: Address) -> u128 {
+        self.erc20_balances
+            .get(&(token, holder))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// lend of the credit account that the lending library created for
+    /// the owner
+    pub fn mars_lend(&self, owner: &str, denom: &str) -> u128 {
+        self.mars_accounts
+            .get(owner)
+            .and_then(|account| account.lends.get(denom))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_mars_lend(&mut self, owner: &str, denom: &str, amount: u128) {
+        self.credit_account(owner, owner)
+            .lends
+            .insert(denom.to_string(), amount);
+    }
+
+    /// credit account with the given id, opened for the owner if missing
+    pub fn credit_account(&mut self, id: &str, owner: &str) -> &mut MockCreditAccount {
+        self.mars_accounts
+            .entry(id.to_string())
+            .or_insert_with(|| MockCreditAccount {
+                owner: owner.to_string(),
+                ..Default::default()
+            })
+    }
+}
+
+/// handle to the mocked domains. cloning it yields another handle to the
+/// same state.
+#[derive(Clone)]
+pub struct MockDomains {
+    state: Arc<Mutex<MockState>>,
+    layout: Arc<StrategyLayout>,
+    effects: Arc<Mutex<HashMap<String, SubroutineEffect>>>,
+}
+
+impl MockDomains {
+    /// mocks the domains of the given layout. subroutines of the clearing
+    /// queue, Mars and supervault withdrawals and the ICA transfer are
+    /// processed out of the box; position entry has to be described with
+    /// `on_subroutine`.
+    pub fn new(layout: &StrategyLayout) -> Self {
+        let mock = Self {
+            state: Arc::new(Mutex::new(MockState {
+                height: 1,
+                timestamp: 1_700_000_000,
+                // like the deployed one, the settlement authorization
+                // settles one obligation per execution
+                subroutine_functions: HashMap::from([(SETTLE_OBLIGATION_LABEL.to_string(), 1)]),
+                ..Default::default()
+            })),
+            layout: Arc::new(layout.clone()),
+            effects: Default::default(),
+        };
+
+        mock.on_subroutine(REGISTER_OBLIGATION_LABEL, register_obligation_effect());
+        mock.on_subroutine(SETTLE_OBLIGATION_LABEL, settle_obligation_effect(layout));
+        mock.on_subroutine(
+            SETTLE_OBLIGATION_BATCH_LABEL,
+            settle_obligation_effect(layout),
+        );
+        if let Some(effect) = mars_withdraw_effect(layout) {
+            mock.on_subroutine(MARS_WITHDRAW_LABEL, effect);
+        }
+        for settlement_denom in &layout.settlement.denoms {
+            if settlement_denom.source == SettlementSource::MarsWithdraw {
+                // markets are liquid enough for any lend out of the box
+                mock.state().red_bank_markets.insert(
+                    settlement_denom.denom.to_string(),
+                    MockMarket {
+                        liquidity_rate: Decimal::percent(5),
+                        borrow_rate: Decimal::percent(8),
+                        utilization_rate: Decimal::zero(),
+                        collateral_total: u64::MAX.into(),
+                        debt_total: 0,
+                    },
+                );
+            }
+        }
+        if let Some(effect) = ica_transfer_effect(layout) {
+            mock.on_subroutine(ICA_TRANSFER_LABEL, effect);
+        }
+
+        mock
+    }
+
+    /// registers the effect of processing the subroutine with the given
+    /// label, replacing the existing one
+    pub fn on_subroutine(&self, label: &str, effect: SubroutineEffect) {
+        self.effects
+            .lock()
+            .expect("mock effects lock poisoned")
+            .insert(label.to_string(), effect);
+    }
+
+    /// locks the shared state, e.g. to seed or inspect it
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock state lock poisoned")
+    }
+
+    /// domain clients backed by the mock
+    pub fn clients(&self) -> StrategyClients {
+        let mut queriers: HashMap<Domain, Arc<dyn CosmosQuerier>> = HashMap::new();
+        queriers.insert(Domain::Neutron, Arc::new(self.querier(Domain::Neutron)));
+        match &self.layout.route {
+            DepositRoute::Eureka(route) => {
+                queriers.insert(Domain::Gaia, Arc::new(self.querier(Domain::Gaia)));
+                if route.lombard.is_some() {
+                    queriers.insert(Domain::Lombard, Arc::new(self.querier(Domain::Lombard)));
+                }
+            }
+            DepositRoute::Cctp(_) => {
+                queriers.insert(Domain::Noble, Arc::new(self.querier(Domain::Noble)));
+            }
+        }
+
+        StrategyClients {
+            ethereum: Arc::new(self.clone()),
+            neutron: Arc::new(self.clone()),
+            queriers,
+            indexer: Arc::new(self.clone()),
+            prover: Arc::new(self.clone()),
+            eureka_router: match self.layout.route {
+                DepositRoute::Eureka(_) => Some(Arc::new(self.clone())),
+                DepositRoute::Cctp(_) => None,
+            },
+        }
+    }
+
+    fn querier(&self, domain: Domain) -> MockQuerier {
+        MockQuerier {
+            domain,
+            mock: self.clone(),
+        }
+    }
+
+    /// pops the next batch off the processor queue and applies its effect
+    /// atomically. like the processor, a failing subroutine does not fail
+    /// the tick, but gets its failure recorded in the processor callback.
+    fn tick(&self) -> anyhow::Result<()> {
+        let mut state = self.state();
+        let batch = state
+            .processor_queue
+            .pop_front()
+            .ok_or_else(|| anyhow!("processor queue is empty"))?;
+
+        let effect = self
+            .effects
+            .lock()
+            .expect("mock effects lock poisoned")
+            .get(&batch.label)
+            .cloned()
+            .ok_or_else(|| anyhow!("no mock effect for the {} subroutine", batch.label))?;
+
+        let mut applied = state.clone();
+        let result = match effect(&mut applied, &batch.messages) {
+            Ok(()) => {
+                *state = applied;
+                ExecutionResult::Success
+            }
+            Err(e) => ExecutionResult::Rejected(e.to_string()),
+        };
+        state.processor_callbacks.insert(
+            batch.id,
+            MockCallback {
+                label: batch.label,
+                result,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// executes the authorizations contract, returning the execution id of
+    /// the batch it pushed to the processor queue
+    fn execute_authorizations(&self, msg: &Value) -> anyhow::Result<u64> {
+        let action = field(msg, "permissionless_action")?;
+
+        let (label, messages) = if let Some(send_msgs) = action.get("send_msgs") {
+            let messages = field(send_msgs, "messages")?
+                .as_array()
+                .ok_or_else(|| anyhow!("send_msgs messages are not a list"))?
+                .iter()
+                .map(|message| {
+                    decode_binary(field(field(message, "cosmwasm_execute_msg")?, "msg")?)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let label = string_field(send_msgs, "label")?;
+            if let Some(functions) = self.state().subroutine_functions.get(&label) {
+                if messages.len() != *functions {
+                    return Err(anyhow!(
+                        "{label} subroutine holds {functions} functions, got {} messages",
+                        messages.len()
+                    ));
+                }
+            }
+
+            (label, messages)
+        } else if let Some(zk) = action.get("execute_zk_authorization") {
+            let inputs = decode_binary(field(zk, "inputs")?)?;
+            let withdraw_request_id = field(&inputs, "input")?
+                .get("withdraw_request_id")
+                .and_then(Value::as_u64);
+            if withdraw_request_id.is_some_and(|id| self.state().failing_posts.contains(&id)) {
+                return Err(anyhow!("failed to post the zk authorization of {inputs}"));
+            }
+
+            (
+                string_field(zk, "label")?,
+                vec![field(&inputs, "input")?.clone()],
+            )
+        } else {
+            return Err(anyhow!("unsupported authorizations message: {msg}"));
+        };
+
+        let mut state = self.state();
+        let id = state.enqueue(&label, messages);
+        for label in state.foreign_labels.clone() {
+            state.enqueue(&label, vec![]);
+        }
+
+        Ok(id)
+    }
+
+    fn query_contract(&self, contract: &str, query: &Value) -> anyhow::Result<Value> {
+        let state = self.state();
+        let neutron = &self.layout.neutron;
+
+        let (name, args) = query
+            .as_object()
+            .and_then(|query| query.iter().next())
+            .ok_or_else(|| anyhow!("malformed query: {query}"))?;
+
+        let response = match name.as_str() {
+            "get_queue" if contract == neutron.processor => {
+                let batches: Vec<Value> = state
+                    .processor_queue
+                    .iter()
+                    .map(|batch| json!({ "id": batch.id, "label": batch.label }))
+                    .collect();
+                json!(batches)
+            }
+            "processor_callback" if contract == neutron.authorizations => {
+                let execution_id = field(args, "execution_id")?
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("execution id is not a number"))?;
+                let callback = state
+                    .processor_callbacks
+                    .get(&execution_id)
+                    .ok_or_else(|| anyhow!("no processor callback for #{execution_id}"))?;
+                json!({
+                    "execution_id": execution_id,
+                    "initiator": "owner",
+                    "processor_callback_address": neutron.processor,
+                    "domain": "main",
+                    "label": callback.label,
+                    "messages": [],
+                    "execution_result": callback.result,
+                    "created_at": state.height,
+                    "last_updated_at": state.height,
+                })
+            }
+            "authorizations" if contract == neutron.authorizations => {
+                let start_after = args.get("start_after").and_then(Value::as_str);
+                let limit = args
+                    .get("limit")
+                    .and_then(Value::as_u64)
+                    .map_or(usize::MAX, |limit| limit as usize);
+
+                let mut labels: Vec<(&String, &usize)> =
+                    state.subroutine_functions.iter().collect();
+                labels.sort();
+                let authorizations: Vec<Value> = labels
+                    .into_iter()
+                    .filter(|(label, _)| start_after.is_none_or(|after| label.as_str() > after))
+                    .take(limit)
+                    .map(|(label, functions)| {
+                        json!({
+                            "label": label,
+                            "mode": "permissionless",
+                            "not_before": { "never": {} },
+                            "expiration": { "never": {} },
+                            "max_concurrent_executions": 1,
+                            "subroutine": mock_subroutine(label, *functions),
+                            "priority": "medium",
+                            "state": "enabled",
+                        })
+                    })
+                    .collect();
+                json!(authorizations)
+            }
+            "get_library_config" if contract == neutron.clearing_queue => {
+                json!({ "latest_id": state.latest_obligation_id.map(|id| id.to_string()) })
+            }
+            "pending_obligations" if contract == neutron.clearing_queue => {
+                let obligations: Vec<Value> = state
+                    .clearing_queue
+                    .iter()
+                    .map(|o| json!({ "id": o.id.to_string(), "payout_coins": o.payout_coins }))
+                    .collect();
+                json!({ "obligations": obligations })
+            }
+            "accounts" => {
+                let owner = string_field(args, "owner")?;
+                let start_after = args.get("start_after").and_then(Value::as_str);
+                let limit = args
+                    .get("limit")
+                    .and_then(Value::as_u64)
+                    .map_or(usize::MAX, |limit| limit as usize);
+
+                let accounts: Vec<Value> = state
+                    .mars_accounts
+                    .iter()
+                    .filter(|(id, account)| {
+                        account.owner == owner
+                            && start_after.is_none_or(|after| id.as_str() > after)
+                    })
+                    .take(limit)
+                    .map(|(id, _)| json!({ "id": id, "kind": "default" }))
+                    .collect();
+                json!(accounts)
+            }
+            "positions" => {
+                let account_id = string_field(args, "account_id")?;
+                let account = state
+                    .mars_accounts
+                    .get(&account_id)
+                    .ok_or_else(|| anyhow!("no mock credit account {account_id}"))?;
+                let coins = |amounts: &HashMap<String, u128>| -> Vec<Coin> {
+                    amounts
+                        .iter()
+                        .map(|(denom, amount)| Coin::new(*amount, denom))
+                        .collect()
+                };
+                let debts: Vec<Value> = account
+                    .debts
+                    .iter()
+                    .map(|(denom, amount)| {
+                        json!({ "denom": denom, "shares": amount.to_string(), "amount": amount.to_string() })
+                    })
+                    .collect();
+                json!({
+                    "account_id": account_id,
+                    "account_kind": "default",
+                    "deposits": coins(&account.deposits),
+                    "debts": debts,
+                    "lends": coins(&account.lends),
+                    "vaults": [],
+                    "staked_astro_lps": [],
+                    "perps": [],
+                })
+            }
+            "get_config" => {
+                let vault = supervault(&state, contract)?;
+                json!({
+                    "lp_denom": vault.lp_denom,
+                    "pair_data": {
+                        "token_0": { "denom": vault.token_0 },
+                        "token_1": { "denom": vault.token_1 },
+                    },
+                })
+            }
+            "simulate_withdraw_liquidity" => {
+                let vault = supervault(&state, contract)?;
+                let amount = uint_field(args, "amount")?;
+                let share = |reserve: u128| {
+                    Uint128::new(reserve).multiply_ratio(amount, vault.total_shares.max(1))
+                };
+                json!([share(vault.reserve_0), share(vault.reserve_1)])
+            }
+            "simulate_provide_liquidity" => {
+                let vault = supervault(&state, contract)?;
+                let shares_for = |amount: u128, reserve: u128| match reserve {
+                    0 => Uint128::zero(),
+                    reserve => Uint128::new(amount).multiply_ratio(vault.total_shares, reserve),
+                };
+                let shares = shares_for(uint_field(args, "amount_0")?, vault.reserve_0)
+                    + shares_for(uint_field(args, "amount_1")?, vault.reserve_1);
+                json!(shares)
+            }
+            "config" => json!({ "red_bank": RED_BANK }),
+            "dynamic_ratio" => {
+                let receiver = string_field(args, "params")?;
+                let ratio = state
+                    .split_ratios
+                    .get(&receiver)
+                    .ok_or_else(|| anyhow!("no mock split ratio of {receiver}"))?;
+                let denom_ratios: HashMap<String, Decimal> = field(args, "denoms")?
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(|denom| (denom.to_string(), *ratio))
+                    .collect();
+                json!({ "denom_ratios": denom_ratios })
+            }
+            "market_v2" => {
+                let denom = string_field(args, "denom")?;
+                let market = state
+                    .red_bank_markets
+                    .get(&denom)
+                    .ok_or_else(|| anyhow!("no mock red bank market of {denom}"))?;
+                json!({
+                    "denom": denom,
+                    "liquidity_rate": market.liquidity_rate,
+                    "borrow_rate": market.borrow_rate,
+                    "utilization_rate": market.utilization_rate,
+                    "collateral_total_amount": market.collateral_total.to_string(),
+                    "debt_total_amount": market.debt_total.to_string(),
+                })
+            }
+            "simulate_deposit" => {
+                let rate = state
+                    .maxbtc_rates
+                    .get(contract)
+                    .ok_or_else(|| anyhow!("no mock maxbtc contract {contract}"))?;
+                let minted = Uint128::new(uint_field(args, "amount")?).mul_floor(*rate);
+                json!({ "minted_amount": minted })
+            }
+            _ => return Err(anyhow!("unsupported query of {contract}: {query}")),
+        };
+
+        Ok(response)
+    }
+
+    fn ethereum_call(&self, request: &TransactionRequest) -> anyhow::Result<Bytes> {
+        let (to, input) = decode_request(request)?;
+        let state = self.state();
+        let vault = &state.vault;
+
+        if let Some(reason) = input
+            .get(..4)
+            .and_then(|selector| state.reverting_calls.get(selector))
+        {
+            return Err(anyhow!("{reason}"));
+        }
+
+        let output = if let Some(call) = decode_call::<ERC20::balanceOfCall>(&input) {
+            ERC20::balanceOfCall::abi_encode_returns(&(U256::from(
+                state.erc20_balance(to, call.account),
+            ),))
+        } else if decode_call::<OneWayVault::redemptionRateCall>(&input).is_some() {
+            OneWayVault::redemptionRateCall::abi_encode_returns(&(vault.redemption_rate,))
+        } else if decode_call::<OneWayVault::totalSupplyCall>(&input).is_some() {
+            OneWayVault::totalSupplyCall::abi_encode_returns(&(vault.total_supply,))
+        } else if decode_call::<OneWayVault::lastRateUpdateTimestampCall>(&input).is_some() {
+            OneWayVault::lastRateUpdateTimestampCall::abi_encode_returns(&(vault.last_rate_update,))
+        } else if decode_call::<OneWayVault::currentWithdrawRequestIdCall>(&input).is_some() {
+            let next_id = state
+                .withdraw_requests
+                .iter()
+                .map(|request| request.id + 1)
+                .max()
+                .unwrap_or_default();
+            OneWayVault::currentWithdrawRequestIdCall::abi_encode_returns(&(next_id,))
+        } else if let Some(call) = decode_call::<OneWayVault::withdrawRequestsCall>(&input) {
+            // requests that were never submitted read back zeroed
+            let (owner, receiver) = match state.withdraw_requests.iter().find(|r| r.id == call._0) {
+                Some(request) => (Address::repeat_byte(1), request.receiver.clone()),
+                None => (Address::ZERO, String::new()),
+            };
+            OneWayVault::withdrawRequestsCall::abi_encode_returns(&(
+                call._0,
+                owner,
+                vault.redemption_rate,
+                U256::ZERO,
+                receiver,
+            ))
+        } else if decode_call::<OneWayVault::vaultStateCall>(&input).is_some() {
+            OneWayVault::vaultStateCall::abi_encode_returns(&(vault.paused, false, false))
+        } else if decode_call::<OneWayVault::configCall>(&input).is_some() {
+            OneWayVault::configCall::abi_encode_returns(&(
+                self.layout.ethereum.deposit_account,
+                Address::ZERO,
+                0,
+                0,
+                0,
+                0,
+                0,
+                vault.max_rate_update_delay,
+                U256::MAX,
+                OneWayVault::FeeDistributionConfig {
+                    strategistAccount: Address::ZERO,
+                    platformAccount: Address::ZERO,
+                    strategistRatioBps: 0,
+                },
+            ))
+        } else {
+            return Err(anyhow!("unsupported call to {to}: {input}"));
+        };
+
+        Ok(Bytes::from(output))
+    }
+
+    fn ethereum_send(&self, request: &TransactionRequest) -> anyhow::Result<()> {
+        let (_, input) = decode_request(request)?;
+        let mut state = self.state();
+
+        if let Some(reason) = input
+            .get(..4)
+            .and_then(|selector| state.reverting_calls.get(selector))
+        {
+            return Err(anyhow!("{reason}"));
+        }
+
+        if let Some(call) = decode_call::<OneWayVault::updateCall>(&input) {
+            if state.vault.paused {
+                return Err(anyhow!("vault is paused"));
+            }
+            state.vault.redemption_rate = call.newRate;
+            state.vault.last_rate_update = state.timestamp;
+        } else if decode_call::<OneWayVault::pauseCall>(&input).is_some() {
+            state.vault.paused = true;
+        } else if let Some(call) = decode_call::<Authorization::sendProcessorMessageCall>(&input) {
+            if call.label != CCTP_TRANSFER_LABEL {
+                return Err(anyhow!("unsupported ethereum subroutine {}", call.label));
+            }
+
+            // the whole deposit account balance gets burned and forwarded
+            // from Noble to the Neutron deposit account
+            let amount = self.drain_ethereum_deposit_account(&mut state);
+            state.bridge(
+                Domain::Neutron,
+                &self.layout.neutron.deposit_account,
+                &self.layout.neutron.deposit_denom,
+                amount,
+            );
+        } else if let Some(call) = decode_call::<Authorization::executeZKMessageCall>(&input) {
+            let DepositRoute::Eureka(route) = &self.layout.route else {
+                return Err(anyhow!("zk messages are only used by eureka routes"));
+            };
+
+            // the eureka transfer delivers the skip route amount out that
+            // was proven by the coprocessor
+            let inputs: Value = serde_json::from_slice(&call._inputs)?;
+            let amount_out =
+                utils::skip::get_amount_out(field(field(&inputs, "input")?, "skip_response")?)?;
+
+            self.drain_ethereum_deposit_account(&mut state);
+            state.bridge(
+                Domain::Gaia,
+                &route.gaia_ica,
+                &route.gaia_deposit_denom,
+                amount_out,
+            );
+        } else {
+            return Err(anyhow!("unsupported transaction: {input}"));
+        }
+
+        Ok(())
+    }
+
+    fn drain_ethereum_deposit_account(&self, state: &mut MockState) -> u128 {
+        let ethereum = &self.layout.ethereum;
+        state
+            .erc20_balances
+            .remove(&(ethereum.deposit_token, ethereum.deposit_account))
+            .unwrap_or_default()
+    }
+
+    /// bumps the height of every domain, returning the new height
+    fn next_height(&self) -> u64 {
+        let mut state = self.state();
+        state.height += 1;
+        state.timestamp += 12;
+        state.height
+    }
+}
+
+/// read access to one of the mocked cosmos domains
+struct MockQuerier {
+    domain: Domain,
+    mock: MockDomains,
+}
+
+#[async_trait]
+impl CosmosQuerier for MockQuerier {
+    async fn latest_height(&self) -> anyhow::Result<u64> {
+        Ok(self.mock.state().height)
+    }
+
+    async fn query_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        _height: Option<u64>,
+    ) -> anyhow::Result<u128> {
+        Ok(self.mock.state().balance(self.domain, address, denom))
+    }
+
+    /// transfers in flight to the account land while it is being polled
+    async fn poll_until_expected_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        expected: u128,
+        poll_interval: u64,
+        max_attempts: u32,
+        preemption: Preemption,
+    ) -> anyhow::Result<u128> {
+        self.mock
+            .state()
+            .land_transfers(self.domain, address, denom);
+
+        for attempt in 1..=max_attempts {
+            let balance = self.query_balance(address, denom, None).await?;
+            if balance >= expected {
+                return Ok(balance);
+            }
+            if attempt < max_attempts {
+                preemption.check(&format!("polling {address} for {denom}"))?;
+                tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+            }
+        }
+
+        Err(anyhow!(
+            "{address} {denom} balance did not reach {expected} after {max_attempts} attempts"
+        ))
+    }
+
+    async fn query_contract_raw(
+        &self,
+        contract: &str,
+        query: Vec<u8>,
+        _height: Option<u64>,
+    ) -> anyhow::Result<Vec<u8>> {
+        if self.domain != Domain::Neutron {
+            return Err(anyhow!("no mock contracts on {}", self.domain));
+        }
+
+        let response = self
+            .mock
+            .query_contract(contract, &serde_json::from_slice(&query)?)?;
+
+        Ok(serde_json::to_vec(&response)?)
+    }
+}
+
+#[async_trait]
+impl CosmosQuerier for MockDomains {
+    async fn latest_height(&self) -> anyhow::Result<u64> {
+        self.querier(Domain::Neutron).latest_height().await
+    }
+
+    async fn query_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        height: Option<u64>,
+    ) -> anyhow::Result<u128> {
+        self.querier(Domain::Neutron)
+            .query_balance(address, denom, height)
+            .await
+    }
+
+    async fn poll_until_expected_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        expected: u128,
+        poll_interval: u64,
+        max_attempts: u32,
+        preemption: Preemption,
+    ) -> anyhow::Result<u128> {
+        self.querier(Domain::Neutron)
+            .poll_until_expected_balance(
+                address,
+                denom,
+                expected,
+                poll_interval,
+                max_attempts,
+                preemption,
+            )
+            .await
+    }
+
+    async fn query_contract_raw(
+        &self,
+        contract: &str,
+        query: Vec<u8>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.querier(Domain::Neutron)
+            .query_contract_raw(contract, query, height)
+            .await
+    }
+}
+
+#[async_trait]
+impl NeutronDomain for MockDomains {
+    async fn execute_wasm(&self, contract: &str, msg: Value) -> anyhow::Result<NeutronTx> {
+        let mut wasm_events = vec![];
+        if contract == self.layout.neutron.authorizations {
+            let execution_id = self.execute_authorizations(&msg)?;
+            wasm_events.push(vec![
+                ("_contract_address".to_string(), contract.to_string()),
+                ("execution_id".to_string(), execution_id.to_string()),
+            ]);
+        } else if contract == self.layout.neutron.processor {
+            self.tick()?;
+        } else {
+            return Err(anyhow!("unsupported execution of {contract}: {msg}"));
+        }
+
+        Ok(NeutronTx {
+            hash: format!("neutron-tx-{}", self.next_height()),
+            wasm_events,
+        })
+    }
+
+    async fn transfer(&self, to: &str, amount: u128, denom: &str) -> anyhow::Result<String> {
+        // the signer is assumed to hold enough funds
+        self.state().credit(Domain::Neutron, to, denom, amount);
+
+        Ok(format!("neutron-tx-{}", self.next_height()))
+    }
+}
+
+#[async_trait]
+impl EthereumDomain for MockDomains {
+    async fn block_number(&self) -> anyhow::Result<u64> {
+        Ok(self.state().height)
+    }
+
+    async fn block_timestamp(&self) -> anyhow::Result<u64> {
+        Ok(self.state().timestamp)
+    }
+
+    async fn gas_price(&self) -> anyhow::Result<u128> {
+        Ok(self.state().gas_price)
+    }
+
+    async fn call(
+        &self,
+        request: TransactionRequest,
+        _block: Option<u64>,
+    ) -> anyhow::Result<Bytes> {
+        self.ethereum_call(&request)
+    }
+
+    /// transactions that fail to apply are included as reverted, with
+    /// the failure as their `Error(string)` revert reason
+    async fn send(&self, request: TransactionRequest) -> anyhow::Result<TxHash> {
+        let revert = self.ethereum_send(&request).err().map(|e| {
+            Bytes::from(
+                Revert {
+                    reason: e.to_string(),
+                }
+                .abi_encode(),
+            )
+        });
+
+        let block = self.next_height();
+        let tx_hash = TxHash::from(U256::from(block).to_be_bytes::<32>());
+        let (_, input) = decode_request(&request)?;
+        self.state().receipts.insert(
+            tx_hash,
+            MockReceipt {
+                block,
+                input,
+                revert,
+            },
+        );
+
+        Ok(tx_hash)
+    }
+
+    async fn receipt(&self, tx_hash: TxHash) -> anyhow::Result<Option<EthReceipt>> {
+        Ok(self
+            .state()
+            .receipts
+            .get(&tx_hash)
+            .map(|receipt| EthReceipt {
+                block_number: receipt.block,
+                success: receipt.revert.is_none(),
+                from: Address::ZERO,
+            }))
+    }
+
+    /// reverts are not re-executed, but looked up among the receipts
+    async fn revert_data(
+        &self,
+        request: TransactionRequest,
+        _block: u64,
+    ) -> anyhow::Result<Option<Bytes>> {
+        let (_, input) = decode_request(&request)?;
+
+        Ok(self
+            .state()
+            .receipts
+            .values()
+            .filter(|receipt| receipt.input == input)
+            .max_by_key(|receipt| receipt.block)
+            .and_then(|receipt| receipt.revert.clone()))
+    }
+}
+
+#[async_trait]
+impl Prover for MockDomains {
+    /// the fake proof carries the input it was requested for, so that the
+    /// mocked authorizations can act on it
+    async fn prove(&self, app_id: &str, input: &Value) -> anyhow::Result<ZkProof> {
+        let latency = {
+            let mut state = self.state();
+            state.proofs_in_flight += 1;
+            state.max_proofs_in_flight = state.max_proofs_in_flight.max(state.proofs_in_flight);
+            state.proof_latency
+        };
+        tokio::time::sleep(latency).await;
+
+        let mut state = self.state();
+        state.proofs_in_flight -= 1;
+        let withdraw_request_id = input.get("withdraw_request_id").and_then(Value::as_u64);
+        if withdraw_request_id.is_some_and(|id| state.failing_proofs.contains(&id)) {
+            return Err(anyhow!("failed to prove {input}"));
+        }
+
+        Ok(ZkProof {
+            proof_program: app_id.as_bytes().to_vec(),
+            inputs_program: serde_json::to_vec(&json!({ "app_id": app_id, "input": input }))?,
+            proof_domain: vec![],
+        })
+    }
+}
+
+#[async_trait]
+impl WithdrawRequestIndexer for MockDomains {
+    async fn withdraw_request_ids(&self, from_id: u64) -> anyhow::Result<Vec<u64>> {
+        let state = self.state();
+        if state.indexer_down {
+            return Err(anyhow!("indexer is unavailable"));
+        }
+
+        let indexed = state
+            .withdraw_requests
+            .len()
+            .saturating_sub(state.indexer_lag);
+        Ok(state.withdraw_requests[..indexed]
+            .iter()
+            .map(|request| request.id)
+            .filter(|id| *id >= from_id)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EurekaRouter for MockDomains {
+    async fn route(&self, amount: String) -> anyhow::Result<Value> {
+        let amount_in: u128 = amount.parse()?;
+        let fee = Uint128::new(amount_in).multiply_ratio(self.state().eureka_fee_bps, 10_000u128);
+
+        Ok(json!({
+            "amount_in": amount,
+            "amount_out": (amount_in - fee.u128()).to_string(),
+        }))
+    }
+}
+
+/// registers the withdraw request that the zk input refers to into the
+/// clearing queue
+fn register_obligation_effect() -> SubroutineEffect {
+    Arc::new(|state: &mut MockState, messages: &[Value]| {
+        let input = messages
+            .first()
+            .ok_or_else(|| anyhow!("missing obligation registration input"))?;
+        let id = field(input, "withdraw_request_id")?
+            .as_u64()
+            .ok_or_else(|| anyhow!("withdraw request id is not a number"))?;
+
+        if state
+            .latest_obligation_id
+            .is_some_and(|latest| id <= latest)
+        {
+            return Err(anyhow!("obligation #{id} is already registered"));
+        }
+        // the clearing queue registers the obligations strictly in order
+        let next_id = state.latest_obligation_id.map_or(0, |latest| latest + 1);
+        if id != next_id {
+            return Err(anyhow!(
+                "obligation #{id} is out of order, expected #{next_id}"
+            ));
+        }
+        if state.failing_registrations.contains(&id) {
+            return Err(anyhow!("registration of obligation #{id} failed"));
+        }
+
+        let request = state
+            .withdraw_requests
+            .iter()
+            .find(|request| request.id == id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no withdraw request #{id}"))?;
+
+        state.clearing_queue.push_back(MockObligation {
+            id,
+            recipient: request.receiver,
+            payout_coins: request.payout_coins,
+        });
+        state.latest_obligation_id = Some(id);
+
+        Ok(())
+    })
+}
+
+/// pays out the next obligation in the clearing queue from the settlement
+/// account, once per settlement message
+fn settle_obligation_effect(layout: &StrategyLayout) -> SubroutineEffect {
+    let settlement_account = layout.neutron.settlement_account.clone();
+
+    Arc::new(move |state: &mut MockState, messages: &[Value]| {
+        for _ in messages {
+            let obligation = state
+                .clearing_queue
+                .pop_front()
+                .ok_or_else(|| anyhow!("no obligations to settle"))?;
+
+            for coin in &obligation.payout_coins {
+                state.debit(
+                    Domain::Neutron,
+                    &settlement_account,
+                    &coin.denom,
+                    coin.amount.u128(),
+                )?;
+                state.credit(
+                    Domain::Neutron,
+                    &obligation.recipient,
+                    &coin.denom,
+                    coin.amount.u128(),
+                );
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// withdraws from the Mars lending position into the settlement account.
+/// `None` if the layout does not settle from Mars.
+fn mars_withdraw_effect(layout: &StrategyLayout) -> Option<SubroutineEffect> {
+    let denom = layout
+        .settlement
+        .denoms
+        .iter()
+        .find(|d| d.source == SettlementSource::MarsWithdraw)?
+        .denom
+        .clone();
+    let owner = layout
+        .update
+        .positions
+        .iter()
+        .find_map(|position| match position {
+            Position::MarsLend { owner, .. } => Some(owner.clone()),
+            _ => None,
+        })?;
+    let settlement_account = layout.neutron.settlement_account.clone();
+
+    Some(Arc::new(
+        move |state: &mut MockState, messages: &[Value]| {
+            for message in messages {
+                let withdraw = field(field(message, "process_function")?, "withdraw")?;
+                let lent = state.mars_lend(&owner, &denom);
+                let amount = match withdraw.get("amount").filter(|a| !a.is_null()) {
+                    Some(amount) => amount_value(amount)?,
+                    None => lent,
+                };
+
+                let remaining = lent
+                    .checked_sub(amount)
+                    .ok_or_else(|| anyhow!("cannot withdraw {amount}{denom} out of {lent} lent"))?;
+                let market = state
+                    .red_bank_markets
+                    .get_mut(&denom)
+                    .ok_or_else(|| anyhow!("no mock red bank market of {denom}"))?;
+                let liquidity = market.collateral_total.saturating_sub(market.debt_total);
+                if amount > liquidity {
+                    return Err(anyhow!(
+                        "cannot withdraw {amount}{denom} out of {liquidity} market liquidity"
+                    ));
+                }
+                market.collateral_total -= amount;
+                state.set_mars_lend(&owner, &denom, remaining);
+                state.credit(Domain::Neutron, &settlement_account, &denom, amount);
+            }
+
+            Ok(())
+        },
+    ))
+}
+
+/// transfers the configured amount from the cosmos hub ICA to the Neutron
+/// deposit account. `None` on routes that do not go through the hub.
+fn ica_transfer_effect(layout: &StrategyLayout) -> Option<SubroutineEffect> {
+    let DepositRoute::Eureka(route) = &layout.route else {
+        return None;
+    };
+    let (gaia_ica, gaia_denom) = (route.gaia_ica.clone(), route.gaia_deposit_denom.clone());
+    let (deposit_account, deposit_denom) = (
+        layout.neutron.deposit_account.clone(),
+        layout.neutron.deposit_denom.clone(),
+    );
+
+    Some(Arc::new(
+        move |state: &mut MockState, messages: &[Value]| {
+            let mut amount = None;
+            for message in messages {
+                if let Some(update) = message.get("update_config") {
+                    amount = Some(amount_value(field(
+                        field(update, "new_config")?,
+                        "amount",
+                    )?)?);
+                } else if message.get("process_function").is_some() {
+                    let amount = amount.ok_or_else(|| anyhow!("ica transfer amount is not set"))?;
+                    state.debit(Domain::Gaia, &gaia_ica, &gaia_denom, amount)?;
+                    state.credit(Domain::Neutron, &deposit_account, &deposit_denom, amount);
+                }
+            }
+
+            Ok(())
+        },
+    ))
+}
+
+fn supervault<'a>(state: &'a MockState, contract: &str) -> anyhow::Result<&'a MockSupervault> {
+    state
+        .supervaults
+        .get(contract)
+        .ok_or_else(|| anyhow!("no mock supervault {contract}"))
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> anyhow::Result<&'a Value> {
+    value
+        .get(key)
+        .ok_or_else(|| anyhow!("missing {key} in {value}"))
+}
+
+fn string_field(value: &Value, key: &str) -> anyhow::Result<String> {
+    field(value, key)?
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("{key} is not a string in {value}"))
+}
+
+fn uint_field(value: &Value, key: &str) -> anyhow::Result<u128> {
+    amount_value(field(value, key)?)
+}
+
+/// parses a `Uint128`-encoded amount
+fn amount_value(value: &Value) -> anyhow::Result<u128> {
+    Ok(serde_json::from_value::<Uint128>(value.clone())?.u128())
+}
+
+/// atomic subroutine of `functions` identical functions
+fn mock_subroutine(label: &str, functions: usize) -> Subroutine {
+    let function = AtomicFunction {
+        domain: valence_authorization_utils::domain::Domain::Main,
+        message_details: MessageDetails {
+            message_type: MessageType::CosmwasmExecuteMsg,
+            message: Message {
+                name: label.to_string(),
+                params_restrictions: None,
+            },
+        },
+        contract_address: LibraryAccountType::Addr(label.to_string()),
+    };
+
+    let builder = (0..functions).fold(AtomicSubroutineBuilder::new(), |builder, _| {
+        builder.with_function(function.clone())
+    });
+    Subroutine::Atomic(builder.build())
+}
+
+/// decodes a base64 `Binary` field holding a json message
+fn decode_binary(value: &Value) -> anyhow::Result<Value> {
+    let binary: Binary = serde_json::from_value(value.clone())?;
+
+    Ok(serde_json::from_slice(&binary)?)
+}
+
+fn decode_request(request: &TransactionRequest) -> anyhow::Result<(Address, Bytes)> {
+    let to = request
+        .to
+        .and_then(|kind| kind.to().copied())
+        .ok_or_else(|| anyhow!("transaction has no recipient"))?;
+    let input = request
+        .input
+        .input()
+        .cloned()
+        .ok_or_else(|| anyhow!("transaction has no input"))?;
+
+    Ok((to, input))
+}
+
+fn decode_call<C: SolCall>(input: &[u8]) -> Option<C> {
+    if !input.starts_with(&C::SELECTOR) {
+        return None;
+    }
+
+    C::abi_decode(input, true).ok()
+}
@@ -0,0 +1,1695 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+use alloy::{
+    primitives::{Address, Bytes, TxHash, U256},
+    sol_types::SolCall,
+};
+use anyhow::anyhow;
+use cosmwasm_std::{coin, Decimal, Uint128};
+use serde_json::Value;
+use valence_authorization_utils::callback::ExecutionResult;
+
+use super::{
+    MockDomains, MockMarket, MockObligation, MockReceipt, MockState, MockSupervault,
+    MockWithdrawRequest,
+};
+use crate::{
+    labels::{
+        CCTP_TRANSFER_LABEL, ICA_TRANSFER_LABEL, MARS_WITHDRAW_LABEL,
+        SETTLE_OBLIGATION_BATCH_LABEL, SETTLE_OBLIGATION_LABEL,
+    },
+    phases::DEPOSIT_PHASE,
+    strategist::{
+        cycle::PhaseStatus,
+        error::StrategistError,
+        journal::{CycleJournal, JournalRecord, JournalStep, StepStatus},
+        layout::{
+            CctpRoute, ChainConnection, CycleLayout, DepositRoute, Domain, EntryAction,
+            EthereumLayout, EurekaRoute, NeutronLayout, Phase, Position, PositionEntry,
+            RegistrationLayout, RetryLayout, SentryLayout, SettlementDenom, SettlementLayout,
+            SettlementSource, StrategyLayout, TrackedBalance, UpdateLayout,
+        },
+        phases::sentry::SentryOutcome,
+        rate_guard::{RateAnomalyAction, RateGuard, RateHistory, RateSample},
+        rebalance::{RebalanceBounds, Rebalancer},
+        sentry::{
+            GasPriceCeiling, KillSwitch, MarsMarketThreshold, MinPendingDeposits,
+            MinWithdrawRequests, SentryPredicate,
+        },
+        transit::IN_TRANSIT_POSITION,
+        Strategy,
+    },
+    types::sol_types::{Authorization, OneWayVault},
+    utils::{eth_tx::EthTxError, metrics, valuation::TvlBreakdown},
+};
+
+const DENOM: &str = "uusdc";
+const ENTRY_LABEL: &str = "lend";
+const MARS_OWNER: &str = "mars_input";
+const GAIA_ICA: &str = "gaia_ica";
+const GAIA_DENOM: &str = "ibc/usdc";
+/// redemption rate of 1.0, expressed in decimal atomics
+const RATE_ONE: u128 = 1_000_000_000_000_000_000;
+
+fn connection(chain_id: &str) -> ChainConnection {
+    ChainConnection {
+        grpc_url: "http://localhost".to_string(),
+        grpc_port: "9090".to_string(),
+        chain_id: chain_id.to_string(),
+    }
+}
+
+fn neutron_balance(address: &str) -> Position {
+    Position::Balance(TrackedBalance {
+        domain: Domain::Neutron,
+        address: address.to_string(),
+        denom: DENOM.to_string(),
+    })
+}
+
+/// cctp route that lends everything on Mars and settles out of it
+fn cctp_layout() -> StrategyLayout {
+    StrategyLayout {
+        ethereum: EthereumLayout {
+            rpc_url: "http://localhost:8545".to_string(),
+            authorizations: Address::repeat_byte(1),
+            one_way_vault: Address::repeat_byte(2),
+            deposit_account: Address::repeat_byte(3),
+            deposit_token: Address::repeat_byte(4),
+            transfer_threshold_amt: U256::from(100),
+        },
+        neutron: NeutronLayout {
+            connection: connection("neutron-1"),
+            authorizations: "authorizations".to_string(),
+            processor: "processor".to_string(),
+            deposit_account: "deposit".to_string(),
+            settlement_account: "settlement".to_string(),
+            deposit_denom: DENOM.to_string(),
+            clearing_queue: "clearing_queue".to_string(),
+            clearing_queue_app_id: "clearing_queue_app".to_string(),
+        },
+        route: DepositRoute::Cctp(CctpRoute {
+            noble: connection("noble-1"),
+            noble_fee_denom: DENOM.to_string(),
+            cctp_transfer: Address::repeat_byte(5),
+            arrival_tolerance_bps: 10,
+            transit_expiry_secs: 3_600,
+        }),
+        entry: PositionEntry {
+            label: ENTRY_LABEL.to_string(),
+            actions: vec![EntryAction::Lend],
+            min_balance: 1,
+        },
+        registration: RegistrationLayout::default(),
+        settlement: SettlementLayout {
+            denoms: vec![SettlementDenom {
+                denom: DENOM.to_string(),
+                source: SettlementSource::MarsWithdraw,
+            }],
+            max_obligations_per_batch: 3,
+        },
+        update: UpdateLayout {
+            rate_scaling_factor: Uint128::one(),
+            max_rate_increment_bps: 100,
+            max_rate_decrement_bps: 100,
+            rate_guard: RateGuard::default(),
+            exclude_foreign_mars_debts: false,
+            rebalance: None,
+            positions: vec![
+                Position::EthereumDeposit,
+                neutron_balance("deposit"),
+                neutron_balance("settlement"),
+                Position::MarsLend {
+                    credit_manager: "credit_manager".to_string(),
+                    owner: MARS_OWNER.to_string(),
+                },
+            ],
+        },
+        sentry: SentryLayout::default(),
+        retry: RetryLayout::default(),
+        cycle: CycleLayout::default(),
+    }
+}
+
+/// eureka route without the lombard hop
+fn eureka_layout() -> StrategyLayout {
+    let mut layout = cctp_layout();
+    layout.route = DepositRoute::Eureka(EurekaRoute {
+        gaia: connection("cosmoshub-4"),
+        gaia_chain_denom: "uatom".to_string(),
+        gaia_ica: GAIA_ICA.to_string(),
+        gaia_deposit_denom: GAIA_DENOM.to_string(),
+        neutron_ica: None,
+        coprocessor_app_id: "eureka_app".to_string(),
+        arrival_tolerance_bps: 100,
+        transit_expiry_secs: 3_600,
+        lombard: None,
+    });
+    layout
+        .update
+        .positions
+        .push(Position::Balance(TrackedBalance {
+            domain: Domain::Gaia,
+            address: GAIA_ICA.to_string(),
+            denom: GAIA_DENOM.to_string(),
+        }));
+    layout
+}
+
+/// mocks the layout domains, lending the whole deposit account balance
+/// on Mars upon position entry
+fn mock_domains(layout: &StrategyLayout) -> MockDomains {
+    let mock = MockDomains::new(layout);
+    mock.on_subroutine(
+        ENTRY_LABEL,
+        Arc::new(|state: &mut MockState, _: &[Value]| {
+            let deposited = state.balance(Domain::Neutron, "deposit", DENOM);
+            state.debit(Domain::Neutron, "deposit", DENOM, deposited)?;
+            let lent = state.mars_lend(MARS_OWNER, DENOM);
+            state.set_mars_lend(MARS_OWNER, DENOM, lent + deposited);
+            Ok(())
+        }),
+    );
+    mock
+}
+
+fn mock_strategy(name: &str, layout: StrategyLayout, mock: &MockDomains) -> Strategy {
+    let history_path: PathBuf =
+        std::env::temp_dir().join(format!("{name}-{}.rates.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&history_path);
+
+    Strategy::from_clients(
+        name.to_string(),
+        0,
+        layout,
+        mock.clients(),
+        RateHistory::open(&history_path).unwrap(),
+        None,
+    )
+}
+
+#[tokio::test]
+async fn cctp_cycle_deposits_registers_settles_and_updates() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            1_000,
+        );
+        state.set_mars_lend(MARS_OWNER, DENOM, 500);
+        // shares of the withdraw request are burned upon submission
+        state.vault.issue_at_par(1_300);
+        state.withdraw_requests.push(MockWithdrawRequest {
+            id: 0,
+            receiver: "user".to_string(),
+            payout_coins: vec![coin(200, DENOM)],
+        });
+    }
+    let mut strategy = mock_strategy("cctp_cycle", layout.clone(), &mock);
+
+    strategy.deposit().await.unwrap();
+    {
+        let state = mock.state();
+        assert_eq!(
+            state.erc20_balance(
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account
+            ),
+            0
+        );
+        assert_eq!(state.balance(Domain::Neutron, "deposit", DENOM), 0);
+        assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 1_500);
+    }
+
+    strategy.register_withdraw_obligations().await.unwrap();
+    {
+        let state = mock.state();
+        assert_eq!(state.latest_obligation_id, Some(0));
+        assert_eq!(state.clearing_queue.len(), 1);
+    }
+
+    // already registered obligations are not registered again
+    strategy.register_withdraw_obligations().await.unwrap();
+    assert_eq!(mock.state().clearing_queue.len(), 1);
+
+    strategy.settlement().await.unwrap();
+    {
+        let state = mock.state();
+        assert!(state.clearing_queue.is_empty());
+        assert!(state.processor_queue.is_empty());
+        assert_eq!(state.balance(Domain::Neutron, "user", DENOM), 200);
+        assert_eq!(state.balance(Domain::Neutron, "settlement", DENOM), 0);
+        assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 1_300);
+    }
+
+    let tvl = strategy.update().await.unwrap();
+    assert_eq!(tvl.total().unwrap(), 1_300);
+    assert!(tvl.snapshot.is_some());
+    {
+        let state = mock.state();
+        assert_eq!(state.vault.redemption_rate, U256::from(RATE_ONE));
+        assert_eq!(state.vault.last_rate_update, state.timestamp);
+        assert!(!state.vault.paused);
+    }
+    assert_eq!(strategy.rate_history.samples().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn eureka_deposit_arrives_through_the_hub() {
+    let layout = eureka_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            10_000,
+        );
+        state.eureka_fee_bps = 10;
+    }
+    let mut strategy = mock_strategy("eureka_deposit", layout, &mock);
+
+    strategy.deposit().await.unwrap();
+
+    let state = mock.state();
+    assert_eq!(state.balance(Domain::Gaia, GAIA_ICA, GAIA_DENOM), 0);
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 9_990);
+}
+
+#[tokio::test]
+async fn eureka_delivery_within_tolerance_is_tracked() {
+    let layout = eureka_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            10_000,
+        );
+        state.eureka_fee_bps = 10;
+        // the transfer delivers 50bps less than the skip route quoted
+        state.bridge_shortfall_bps = 50;
+    }
+    let mut strategy = mock_strategy("eureka_delivery", layout, &mock);
+
+    strategy.deposit().await.unwrap();
+
+    assert_eq!(mock.state().mars_lend(MARS_OWNER, DENOM), 9_941);
+    let rendered = strategy.metrics.render();
+    assert!(rendered.contains(&format!(
+        "{}{{transfer=\"eureka_transfer_zkp\"}} 0.995",
+        metrics::TRANSFER_DELIVERY
+    )));
+    assert!(!rendered.contains(metrics::TRANSFER_SHORTFALLS));
+}
+
+#[tokio::test(start_paused = true)]
+async fn under_delivered_cctp_transfer_is_alerted_and_entered() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            1_000,
+        );
+        // 100bps go missing, while the route only tolerates 10bps
+        state.bridge_shortfall_bps = 100;
+    }
+    let mut strategy = mock_strategy("cctp_shortfall", layout, &mock);
+
+    strategy.deposit().await.unwrap();
+
+    // whatever did arrive still gets entered
+    assert_eq!(mock.state().mars_lend(MARS_OWNER, DENOM), 990);
+    let rendered = strategy.metrics.render();
+    assert!(rendered.contains(&format!(
+        "{}{{transfer=\"{CCTP_TRANSFER_LABEL}\"}} 1",
+        metrics::TRANSFER_SHORTFALLS
+    )));
+}
+
+#[tokio::test]
+async fn deposit_below_threshold_is_left_on_ethereum() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    mock.state().erc20_balances.insert(
+        (
+            layout.ethereum.deposit_token,
+            layout.ethereum.deposit_account,
+        ),
+        99,
+    );
+    let mut strategy = mock_strategy("below_threshold", layout.clone(), &mock);
+
+    strategy.deposit().await.unwrap();
+
+    let state = mock.state();
+    assert_eq!(
+        state.erc20_balance(
+            layout.ethereum.deposit_token,
+            layout.ethereum.deposit_account
+        ),
+        99
+    );
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 0);
+}
+
+#[tokio::test]
+async fn update_out_of_bounds_pauses_the_vault() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_050);
+        state.vault.issue_at_par(1_000);
+    }
+    let mut strategy = mock_strategy("out_of_bounds", layout, &mock);
+
+    // a 5% increase exceeds the 1% max rate increment
+    assert!(strategy.update().await.is_err());
+
+    let state = mock.state();
+    assert!(state.vault.paused);
+    assert_eq!(state.vault.redemption_rate, U256::from(RATE_ONE));
+    assert!(strategy.rate_history.samples().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn skipped_rate_anomaly_fails_the_update() {
+    let mut layout = cctp_layout();
+    layout.update.rate_guard.action = RateAnomalyAction::Skip;
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+        state.vault.issue_at_par(1_000);
+    }
+    let mut strategy = mock_strategy("rate_anomaly", layout, &mock);
+    // a rate of 1.0 drifts 11% away from the rates posted so far
+    for _ in 0..5 {
+        strategy.rate_history.record(Decimal::percent(90)).unwrap();
+    }
+
+    let err = strategy.update().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::RateAnomaly(_)
+    ));
+    assert_eq!(mock.state().vault.last_rate_update, 0);
+    assert_eq!(strategy.rate_history.samples().unwrap().len(), 5);
+    assert!(strategy
+        .metrics
+        .render()
+        .contains(&format!("{} 1", metrics::RATE_UPDATES_SKIPPED)));
+}
+
+#[tokio::test]
+async fn update_without_shares_fails() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    mock.state().set_mars_lend(MARS_OWNER, DENOM, 1_000);
+    let mut strategy = mock_strategy("without_shares", layout, &mock);
+
+    assert!(strategy.update().await.is_err());
+    assert_eq!(mock.state().vault.last_rate_update, 0);
+}
+
+#[tokio::test]
+async fn sentry_proceeds_once_the_predicate_holds() {
+    let mut layout = cctp_layout();
+    layout.sentry.predicate = SentryPredicate::All(vec![
+        SentryPredicate::GasPrice(GasPriceCeiling {
+            max_gas_price_gwei: 20,
+        }),
+        SentryPredicate::Any(vec![
+            SentryPredicate::PendingDeposits(MinPendingDeposits {
+                min_amount: U256::from(1_000),
+            }),
+            SentryPredicate::WithdrawRequests(MinWithdrawRequests { min_count: 1 }),
+        ]),
+        SentryPredicate::MarsMarket(MarsMarketThreshold {
+            red_bank: "red_bank".to_string(),
+            denom: DENOM.to_string(),
+            min_utilization: None,
+            max_utilization: Some(Decimal::percent(90)),
+            min_lend_rate: Some(Decimal::percent(2)),
+            max_lend_rate: None,
+        }),
+    ]);
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.gas_price = 10_000_000_000;
+        state.red_bank_markets.insert(
+            DENOM.to_string(),
+            MockMarket {
+                liquidity_rate: Decimal::percent(5),
+                borrow_rate: Decimal::percent(8),
+                utilization_rate: Decimal::percent(70),
+                ..Default::default()
+            },
+        );
+        // no pending deposits, but a single withdraw request to register
+        state.withdraw_requests.push(MockWithdrawRequest {
+            id: 0,
+            receiver: "user".to_string(),
+            payout_coins: vec![coin(200, DENOM)],
+        });
+    }
+    let mut strategy = mock_strategy("sentry_predicate", layout, &mock);
+
+    assert_eq!(strategy.sentry().await.unwrap(), SentryOutcome::Cycle);
+}
+
+#[tokio::test]
+async fn sentry_predicate_is_read_from_the_config() {
+    let sentry: SentryLayout = toml::from_str(
+        r#"predicate = { all = [{ kill_switch = "vault_paused" }, { gas_price = { max_gas_price_gwei = 50 } }] }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        sentry.stale_rate_margin_secs,
+        SentryLayout::default().stale_rate_margin_secs
+    );
+
+    let mut layout = cctp_layout();
+    layout.sentry = SentryLayout {
+        stale_rate_margin_secs: 600,
+        ..sentry
+    };
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.gas_price = 60_000_000_000;
+        state.timestamp = 3_100;
+        state.vault.last_rate_update = 100;
+        state.vault.max_rate_update_delay = 3_600;
+    }
+    let mut strategy = mock_strategy("sentry_config", layout, &mock);
+
+    // gas above the configured ceiling only lets the forced update through
+    assert_eq!(strategy.sentry().await.unwrap(), SentryOutcome::UpdateOnly);
+
+    mock.state().gas_price = 40_000_000_000;
+    assert_eq!(strategy.sentry().await.unwrap(), SentryOutcome::Cycle);
+
+    mock.state().vault.paused = true;
+    assert_eq!(strategy.sentry().await.unwrap(), SentryOutcome::UpdateOnly);
+}
+
+#[tokio::test]
+async fn sentry_forces_an_update_before_the_rate_goes_stale() {
+    let kill_switch =
+        std::env::temp_dir().join(format!("sentry-kill-switch-{}", std::process::id()));
+    std::fs::write(&kill_switch, "").unwrap();
+
+    let mut layout = cctp_layout();
+    layout.sentry = SentryLayout {
+        predicate: SentryPredicate::KillSwitch(KillSwitch::File(kill_switch.clone())),
+        stale_rate_margin_secs: 600,
+    };
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.timestamp = 3_100;
+        state.vault.last_rate_update = 100;
+        state.vault.max_rate_update_delay = 3_600;
+    }
+    let mut strategy = mock_strategy("sentry_stale_rate", layout, &mock);
+
+    let outcome = strategy.sentry().await.unwrap();
+    std::fs::remove_file(&kill_switch).unwrap();
+
+    assert_eq!(outcome, SentryOutcome::UpdateOnly);
+}
+
+#[tokio::test(start_paused = true)]
+async fn stalled_deposit_is_preempted_by_the_mandatory_update() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            1_000,
+        );
+        state.stalled_bridge = true;
+        state.set_mars_lend(MARS_OWNER, DENOM, 500);
+        // the stalled deposits are still backed by their shares
+        state.vault.issue_at_par(1_500);
+        // update is due in 100sec, while the deposit polls for much longer
+        state.timestamp = 1_000;
+        state.vault.max_rate_update_delay = 3_600;
+    }
+    let mut strategy = mock_strategy("stalled_deposit", layout.clone(), &mock);
+    strategy.layout.sentry.stale_rate_margin_secs = 2_500;
+
+    let summary = strategy.run_phases().await.unwrap();
+
+    // the deposit stopped polling at the deadline, without failing the cycle
+    assert!(matches!(
+        summary.status(Phase::Deposit),
+        Some(PhaseStatus::Skipped { .. })
+    ));
+    assert!(summary.succeeded(Phase::Update));
+    let rendered = strategy.metrics.render();
+    assert!(rendered.contains(&format!("{} 1", metrics::UPDATE_PREEMPTIONS)));
+    assert!(!rendered.contains(&format!(
+        "{}{{phase=\"{DEPOSIT_PHASE}\"",
+        metrics::PHASE_ERRORS
+    )));
+    let state = mock.state();
+    assert_eq!(state.balance(Domain::Neutron, "deposit", DENOM), 0);
+    assert_eq!(state.vault.last_rate_update, state.timestamp);
+    assert_eq!(state.vault.redemption_rate, U256::from(RATE_ONE));
+}
+
+/// mock vault holding a withdraw request for each of the given ids
+fn withdraw_requests_mock(layout: &StrategyLayout, ids: &[u64]) -> MockDomains {
+    let mock = mock_domains(layout);
+    mock.state().withdraw_requests = ids
+        .iter()
+        .map(|id| MockWithdrawRequest {
+            id: *id,
+            receiver: format!("user_{id}"),
+            payout_coins: vec![coin(100, DENOM)],
+        })
+        .collect();
+    mock
+}
+
+#[tokio::test]
+async fn indexer_outage_falls_back_to_the_vault() {
+    let layout = cctp_layout();
+    let mock = withdraw_requests_mock(&layout, &[0, 1]);
+    mock.state().indexer_down = true;
+    let mut strategy = mock_strategy("indexer_outage", layout, &mock);
+
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    let state = mock.state();
+    assert_eq!(state.latest_obligation_id, Some(1));
+    assert_eq!(state.clearing_queue.len(), 2);
+}
+
+#[tokio::test]
+async fn lagging_indexer_is_overridden_by_the_vault() {
+    let layout = cctp_layout();
+    let mock = withdraw_requests_mock(&layout, &[0, 1, 2]);
+    mock.state().indexer_lag = 2;
+    let mut strategy = mock_strategy("lagging_indexer", layout, &mock);
+
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    assert_eq!(mock.state().latest_obligation_id, Some(2));
+}
+
+#[tokio::test]
+async fn withdraw_requests_past_an_id_gap_are_not_registered() {
+    let layout = cctp_layout();
+    // request #1 is missing from the vault
+    let mock = withdraw_requests_mock(&layout, &[0, 2]);
+    let mut strategy = mock_strategy("withdraw_request_gap", layout, &mock);
+
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    let state = mock.state();
+    assert_eq!(state.latest_obligation_id, Some(0));
+    assert_eq!(state.clearing_queue.len(), 1);
+}
+
+#[tokio::test]
+async fn registration_fails_when_neither_source_is_available() {
+    let layout = cctp_layout();
+    let mock = withdraw_requests_mock(&layout, &[0]);
+    {
+        let mut state = mock.state();
+        state.indexer_down = true;
+        state.reverting_calls.insert(
+            OneWayVault::currentWithdrawRequestIdCall::SELECTOR,
+            "rpc unavailable".to_string(),
+        );
+    }
+    let mut strategy = mock_strategy("no_withdraw_request_source", layout, &mock);
+
+    let err = strategy.register_withdraw_obligations().await.unwrap_err();
+
+    assert!(StrategistError::classify(&err).is_retryable());
+    assert_eq!(mock.state().latest_obligation_id, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn withdraw_requests_are_proven_concurrently_and_registered_in_order() {
+    let mut layout = cctp_layout();
+    layout.registration = RegistrationLayout {
+        batch_size: 4,
+        max_concurrent_proofs: 2,
+    };
+    let mock = withdraw_requests_mock(&layout, &[0, 1, 2, 3, 4, 5]);
+    mock.state().proof_latency = Duration::from_secs(60);
+    let mut strategy = mock_strategy("concurrent_proofs", layout, &mock);
+
+    let started = tokio::time::Instant::now();
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    // a batch of four, proven in two rounds, followed by a batch of two
+    assert_eq!(started.elapsed(), Duration::from_secs(180));
+    let state = mock.state();
+    assert_eq!(state.max_proofs_in_flight, 2);
+    assert!(state.processor_queue.is_empty());
+    let registered: Vec<u64> = state.clearing_queue.iter().map(|o| o.id).collect();
+    assert_eq!(registered, vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_proof_leaves_the_later_withdraw_requests_unregistered() {
+    let layout = cctp_layout();
+    let mock = withdraw_requests_mock(&layout, &[0, 1, 2, 3]);
+    {
+        let mut state = mock.state();
+        state.failing_proofs.insert(1);
+        state.proof_latency = Duration::from_secs(60);
+    }
+    let mut strategy = mock_strategy("failed_proof", layout, &mock);
+
+    let err = strategy.register_withdraw_obligations().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::ProofFailed(_)
+    ));
+    {
+        let state = mock.state();
+        assert_eq!(state.latest_obligation_id, Some(0));
+        assert!(state.processor_queue.is_empty());
+    }
+
+    // the next run picks up from the failed proof
+    mock.state().failing_proofs.clear();
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    let registered: Vec<u64> = mock.state().clearing_queue.iter().map(|o| o.id).collect();
+    assert_eq!(registered, vec![0, 1, 2, 3]);
+}
+
+#[tokio::test]
+async fn failed_post_still_ticks_the_posted_registrations_through() {
+    let layout = cctp_layout();
+    let mock = withdraw_requests_mock(&layout, &[0, 1, 2, 3]);
+    mock.state().failing_posts.insert(2);
+    let mut strategy = mock_strategy("failed_post", layout, &mock);
+
+    assert!(strategy.register_withdraw_obligations().await.is_err());
+    {
+        let state = mock.state();
+        assert!(state.processor_queue.is_empty());
+        let registered: Vec<u64> = state.clearing_queue.iter().map(|o| o.id).collect();
+        assert_eq!(registered, vec![0, 1]);
+    }
+
+    // the next run picks up from the failed post
+    mock.state().failing_posts.clear();
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    let registered: Vec<u64> = mock.state().clearing_queue.iter().map(|o| o.id).collect();
+    assert_eq!(registered, vec![0, 1, 2, 3]);
+}
+
+#[tokio::test]
+async fn failed_registration_leaves_the_later_ones_to_the_journal() {
+    let layout = cctp_layout();
+    let mock = withdraw_requests_mock(&layout, &[0, 1, 2, 3]);
+    mock.state().failing_registrations.insert(1);
+    let mut strategy = mock_strategy("failed_registration", layout, &mock);
+    let journal_path = std::env::temp_dir().join(format!(
+        "failed-registration-{}.journal.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&journal_path);
+    strategy.journal = CycleJournal::open(&journal_path).unwrap();
+
+    let err = strategy.register_withdraw_obligations().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::TxReverted(_)
+    ));
+    {
+        // the registrations posted after the failed one are not ticked
+        let state = mock.state();
+        assert_eq!(state.latest_obligation_id, Some(0));
+        assert_eq!(state.processor_queue.len(), 2);
+    }
+    assert_eq!(strategy.journal.in_flight().unwrap().len(), 2);
+
+    // the next run flushes them out of the queue before resuming from the
+    // failed registration
+    mock.state().failing_registrations.clear();
+    strategy.register_withdraw_obligations().await.unwrap();
+
+    assert!(strategy.journal.in_flight().unwrap().is_empty());
+    let state = mock.state();
+    assert!(state.processor_queue.is_empty());
+    let registered: Vec<u64> = state.clearing_queue.iter().map(|o| o.id).collect();
+    assert_eq!(registered, vec![0, 1, 2, 3]);
+    let _ = std::fs::remove_file(&journal_path);
+}
+
+/// cctp deposit of 1000 that gets stuck on the way to Neutron, next to
+/// 500 lent out on Mars
+fn stalled_deposit_mock(layout: &StrategyLayout, total_supply: u64) -> MockDomains {
+    let mock = mock_domains(layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            1_000,
+        );
+        state.stalled_bridge = true;
+        state.set_mars_lend(MARS_OWNER, DENOM, 500);
+        state.vault.issue_at_par(total_supply);
+    }
+    mock
+}
+
+fn in_transit_value(tvl: &TvlBreakdown) -> u128 {
+    tvl.positions
+        .iter()
+        .find(|position| position.name == IN_TRANSIT_POSITION)
+        .map(|position| position.value)
+        .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn stalled_transfer_is_counted_in_transit_until_it_arrives() {
+    let layout = cctp_layout();
+    let mock = stalled_deposit_mock(&layout, 1_500);
+    let mut strategy = mock_strategy("in_transit", layout, &mock);
+
+    // the poll gives up, but the transfer stays in transit
+    assert!(strategy.deposit().await.is_err());
+    let tvl = strategy.update().await.unwrap();
+    assert_eq!(in_transit_value(&tvl), 1_000);
+    {
+        let state = mock.state();
+        assert!(!state.vault.paused);
+        assert_eq!(state.vault.redemption_rate, U256::from(RATE_ONE));
+    }
+
+    // the relayer catches up
+    {
+        let mut state = mock.state();
+        state.stalled_bridge = false;
+        state.credit(Domain::Neutron, "deposit", DENOM, 1_000);
+    }
+    strategy.deposit().await.unwrap();
+    assert!(strategy.transit.outstanding().unwrap().is_empty());
+    assert_eq!(mock.state().mars_lend(MARS_OWNER, DENOM), 1_500);
+
+    let tvl = strategy.update().await.unwrap();
+    assert_eq!(in_transit_value(&tvl), 0);
+    assert_eq!(mock.state().vault.redemption_rate, U256::from(RATE_ONE));
+}
+
+#[tokio::test(start_paused = true)]
+async fn expired_transfer_is_no_longer_counted_in_transit() {
+    let mut layout = cctp_layout();
+    if let DepositRoute::Cctp(route) = &mut layout.route {
+        route.transit_expiry_secs = 0;
+    }
+    // the lost deposits no longer back any shares
+    let mock = stalled_deposit_mock(&layout, 500);
+    let mut strategy = mock_strategy("expired_transit", layout, &mock);
+
+    assert!(strategy.deposit().await.is_err());
+    let tvl = strategy.update().await.unwrap();
+
+    assert_eq!(in_transit_value(&tvl), 0);
+    assert!(strategy.transit.outstanding().unwrap().is_empty());
+    assert!(strategy.metrics.render().contains(&format!(
+        "{}{{transfer=\"{CCTP_TRANSFER_LABEL}\"}} 1",
+        metrics::TRANSFERS_EXPIRED
+    )));
+    assert_eq!(mock.state().vault.redemption_rate, U256::from(RATE_ONE));
+}
+
+#[tokio::test]
+async fn interrupted_steps_are_reconciled_against_the_chain() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.credit(Domain::Neutron, "deposit", DENOM, 1_000);
+        for (byte, revert) in [(1, None), (2, Some(Bytes::from_static(b"reverted")))] {
+            state.receipts.insert(
+                TxHash::repeat_byte(byte),
+                MockReceipt {
+                    block: 1,
+                    input: Bytes::new(),
+                    revert,
+                },
+            );
+        }
+    }
+    let mut strategy = mock_strategy("reconcile", layout, &mock);
+    let journal_path =
+        std::env::temp_dir().join(format!("reconcile-{}.journal.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&journal_path);
+    strategy.journal = CycleJournal::open(&journal_path).unwrap();
+
+    let eth_tx = |hash: Option<u8>| JournalStep::EthereumTx {
+        label: CCTP_TRANSFER_LABEL.to_string(),
+        tx_hash: hash.map(|byte| TxHash::repeat_byte(byte).to_string()),
+    };
+    let arrival = |expected: u128| JournalStep::AwaitArrival {
+        domain: Domain::Neutron,
+        address: "deposit".to_string(),
+        denom: DENOM.to_string(),
+        expected,
+        tx_hash: None,
+    };
+    let steps = [
+        (eth_tx(Some(1)), StepStatus::Completed),
+        // reverted
+        (eth_tx(Some(2)), StepStatus::Skipped),
+        // dropped
+        (eth_tx(Some(3)), StepStatus::Skipped),
+        // never broadcast
+        (eth_tx(None), StepStatus::Skipped),
+        (arrival(1_000), StepStatus::Completed),
+        // still in transit
+        (arrival(2_000), StepStatus::Skipped),
+    ];
+    let expected: HashMap<u64, StepStatus> = steps
+        .into_iter()
+        .map(|(step, status)| (strategy.journal.start("deposit", step).unwrap(), status))
+        .collect();
+
+    // funds in transit are not waited on at startup
+    tokio::time::timeout(Duration::from_secs(1), strategy.reconcile_journal())
+        .await
+        .expect("reconciliation waited on the funds in transit")
+        .unwrap();
+
+    assert!(strategy.journal.in_flight().unwrap().is_empty());
+    let statuses: HashMap<u64, StepStatus> = std::fs::read_to_string(&journal_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<JournalRecord>(line).unwrap())
+        .map(|record| (record.id, record.status))
+        .collect();
+    assert_eq!(statuses, expected);
+}
+
+#[tokio::test]
+async fn reverted_deposit_routing_leaves_the_deposits_on_ethereum() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.erc20_balances.insert(
+            (
+                layout.ethereum.deposit_token,
+                layout.ethereum.deposit_account,
+            ),
+            1_000,
+        );
+        state.reverting_calls.insert(
+            Authorization::sendProcessorMessageCall::SELECTOR,
+            "ProcessorPaused".to_string(),
+        );
+    }
+    let mut strategy = mock_strategy("reverted_routing", layout.clone(), &mock);
+
+    strategy.deposit().await.unwrap();
+
+    let state = mock.state();
+    assert_eq!(
+        state.erc20_balance(
+            layout.ethereum.deposit_token,
+            layout.ethereum.deposit_account
+        ),
+        1_000
+    );
+    assert_eq!(state.balance(Domain::Neutron, "deposit", DENOM), 0);
+}
+
+#[tokio::test]
+async fn reverted_rate_update_surfaces_the_revert_reason() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+        state.vault.issue_at_par(1_000);
+        state.vault.paused = true;
+    }
+    let mut strategy = mock_strategy("reverted_update", layout, &mock);
+
+    let err = strategy.update().await.unwrap_err();
+
+    match err.downcast_ref::<EthTxError>() {
+        Some(EthTxError::Reverted { reason, .. }) => {
+            assert!(reason.as_deref().unwrap().contains("vault is paused"))
+        }
+        other => panic!("expected a reverted tx, got {other:?}"),
+    }
+    assert!(strategy.rate_history.samples().unwrap().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn unconfirmed_rate_update_is_reconciled_instead_of_reposted() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_005);
+        state.vault.issue_at_par(1_000);
+    }
+    let mut strategy = mock_strategy("unconfirmed_update", layout, &mock);
+    let journal_path = std::env::temp_dir().join(format!(
+        "unconfirmed-update-{}.journal.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&journal_path);
+    strategy.journal = CycleJournal::open(&journal_path).unwrap();
+    // the rate update lands, but never gets enough confirmations
+    strategy.eth_confirmations = u64::MAX;
+
+    let err = strategy.update().await.unwrap_err();
+
+    let class = StrategistError::classify(&err);
+    assert!(matches!(class, StrategistError::TxPending(_)));
+    assert!(!class.is_retryable());
+    assert_eq!(strategy.journal.in_flight().unwrap().len(), 1);
+    assert!(strategy.rate_history.samples().unwrap().is_empty());
+
+    // the next update records the rate that landed before posting its own
+    strategy.eth_confirmations = 1;
+    strategy.update().await.unwrap();
+
+    assert!(strategy.journal.in_flight().unwrap().is_empty());
+    let rates: Vec<Decimal> = strategy
+        .rate_history
+        .samples()
+        .unwrap()
+        .into_iter()
+        .map(|sample| sample.rate)
+        .collect();
+    assert_eq!(rates, vec![Decimal::permille(1_005); 2]);
+}
+
+#[tokio::test]
+async fn failed_mars_withdraw_ends_the_settlement_phase() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    mock.on_subroutine(
+        MARS_WITHDRAW_LABEL,
+        Arc::new(|_: &mut MockState, _: &[Value]| Err(anyhow!("market is paused"))),
+    );
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 500);
+        state.clearing_queue.push_back(MockObligation {
+            id: 0,
+            recipient: "user".to_string(),
+            payout_coins: vec![coin(200, DENOM)],
+        });
+        state.latest_obligation_id = Some(0);
+    }
+    let mut strategy = mock_strategy("failed_withdraw", layout, &mock);
+
+    let err = strategy.settlement().await.unwrap_err();
+    assert!(err.to_string().contains("market is paused"));
+
+    let state = mock.state();
+    assert!(state.processor_queue.is_empty());
+    assert_eq!(state.clearing_queue.len(), 1);
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 500);
+    assert_eq!(state.balance(Domain::Neutron, "user", DENOM), 0);
+}
+
+#[tokio::test]
+async fn uncoverable_clearing_queue_is_refused_without_withdrawing() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        // the lending position cannot cover the obligation
+        state.set_mars_lend(MARS_OWNER, DENOM, 100);
+        state.clearing_queue.push_back(MockObligation {
+            id: 0,
+            recipient: "user".to_string(),
+            payout_coins: vec![coin(200, DENOM)],
+        });
+        state.latest_obligation_id = Some(0);
+    }
+    let mut strategy = mock_strategy("uncoverable_queue", layout, &mock);
+
+    let err = strategy.settlement().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::InsufficientBalance(_)
+    ));
+    assert!(err.to_string().contains("short of 100uusdc"));
+    let state = mock.state();
+    assert!(state.processor_queue.is_empty());
+    assert_eq!(state.latest_execution_id, 0);
+    assert_eq!(state.clearing_queue.len(), 1);
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 100);
+}
+
+#[tokio::test]
+async fn mars_withdrawal_is_capped_at_the_market_liquidity() {
+    let layout = cctp_layout();
+    let mock = pending_obligations_mock(&layout, &[0, 1], 1);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 500);
+        // the market has all but 100 of its deposits borrowed out
+        let market = state.red_bank_markets.get_mut(DENOM).unwrap();
+        market.collateral_total = 1_000;
+        market.debt_total = 900;
+    }
+    let mut strategy = mock_strategy("illiquid_market", layout, &mock);
+
+    let err = strategy.settlement().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::InsufficientBalance(_)
+    ));
+    assert!(err.to_string().contains("short of 100uusdc"));
+    assert!(strategy.metrics.render().contains(&format!(
+        "{}{{denom=\"{DENOM}\"}} 1",
+        metrics::MARS_LIQUIDITY_SHORTFALLS
+    )));
+    let state = mock.state();
+    assert_eq!(state.latest_execution_id, 0);
+    assert_eq!(state.clearing_queue.len(), 2);
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 500);
+}
+
+#[tokio::test]
+async fn update_nets_the_mars_positions_of_all_credit_accounts() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+        // more accounts than fit in a single page of the accounts query
+        for i in 0..11 {
+            let account = state.credit_account(&format!("account_{i:02}"), MARS_OWNER);
+            account.deposits.insert(DENOM.to_string(), 100);
+        }
+        let account = state.credit_account("account_00", MARS_OWNER);
+        account.debts.insert(DENOM.to_string(), 100);
+        account.lends.insert("untracked".to_string(), 5_000);
+        state.vault.issue_at_par(2_000);
+    }
+    let mut strategy = mock_strategy("mars_accounts", layout, &mock);
+
+    let tvl = strategy.update().await.unwrap();
+
+    // 1_000 lent, 1_100 deposited and 100 owed
+    let mars = tvl
+        .positions
+        .iter()
+        .find(|position| position.name.starts_with("mars"))
+        .unwrap();
+    assert_eq!(mars.value, 2_000);
+    assert_eq!(mock.state().vault.redemption_rate, U256::from(RATE_ONE));
+}
+
+#[tokio::test]
+async fn update_values_a_missing_mars_position_at_zero() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        // freshly deployed, nothing has been lent yet
+        state.set_balance(Domain::Neutron, "settlement", DENOM, 1_000);
+        state.vault.issue_at_par(1_000);
+    }
+    let mut strategy = mock_strategy("empty_mars", layout, &mock);
+
+    let tvl = strategy.update().await.unwrap();
+
+    assert_eq!(tvl.total().unwrap(), 1_000);
+    assert!(mock.state().mars_accounts.is_empty());
+}
+
+/// mock with 1000 lent on Mars next to a debt of 10uatom, which cannot be
+/// valued in the deposit denom
+fn foreign_debt_mock(layout: &StrategyLayout) -> MockDomains {
+    let mock = mock_domains(layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+        state
+            .credit_account(MARS_OWNER, MARS_OWNER)
+            .debts
+            .insert("uatom".to_string(), 10);
+        state.vault.issue_at_par(1_000);
+    }
+    mock
+}
+
+#[tokio::test]
+async fn update_refuses_to_value_mars_debts_in_other_denoms() {
+    let layout = cctp_layout();
+    let mock = foreign_debt_mock(&layout);
+    let mut strategy = mock_strategy("foreign_debt", layout, &mock);
+
+    let err = strategy.update().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::InvariantViolated(_)
+    ));
+    assert!(err.to_string().contains("cannot be valued in uusdc"));
+    assert!(strategy.metrics.render().contains(&format!(
+        "{}{{owner=\"{MARS_OWNER}\",denom=\"uatom\"}} 10",
+        metrics::MARS_FOREIGN_DEBTS
+    )));
+    assert_eq!(mock.state().vault.last_rate_update, 0);
+}
+
+#[tokio::test]
+async fn excluded_mars_debts_in_other_denoms_are_alerted_on() {
+    let mut layout = cctp_layout();
+    layout.update.exclude_foreign_mars_debts = true;
+    let mock = foreign_debt_mock(&layout);
+    let mut strategy = mock_strategy("excluded_foreign_debt", layout, &mock);
+
+    strategy.update().await.unwrap();
+
+    assert!(strategy.metrics.render().contains(&format!(
+        "{}{{owner=\"{MARS_OWNER}\",denom=\"uatom\"}} 10",
+        metrics::MARS_FOREIGN_DEBTS
+    )));
+    let state = mock.state();
+    assert_eq!(state.vault.last_rate_update, state.timestamp);
+    // the lend is valued without the debt
+    assert_eq!(state.vault.redemption_rate, U256::from(RATE_ONE));
+}
+
+#[tokio::test]
+async fn update_exports_the_mars_lend_apy() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+        state.vault.issue_at_par(1_000);
+    }
+    let mut strategy = mock_strategy("mars_lend_apy", layout, &mock);
+
+    strategy.update().await.unwrap();
+
+    // the 5% lend rate of the mock market, compounded continuously
+    assert!(strategy.metrics.render().contains(&format!(
+        "{}{{denom=\"{DENOM}\"}} {}",
+        metrics::MARS_LEND_APY,
+        0.05f64.exp_m1()
+    )));
+}
+
+#[tokio::test]
+async fn covered_clearing_queue_withdraws_nothing() {
+    let layout = cctp_layout();
+    let mock = pending_obligations_mock(&layout, &[0, 1], 1);
+    {
+        let mut state = mock.state();
+        state.set_balance(Domain::Neutron, "settlement", DENOM, 200);
+        state.set_mars_lend(MARS_OWNER, DENOM, 500);
+    }
+    let mut strategy = mock_strategy("covered_queue", layout, &mock);
+
+    strategy.settlement().await.unwrap();
+
+    let state = mock.state();
+    assert!(state.clearing_queue.is_empty());
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 500);
+    // one execution per settled obligation, and none for Mars
+    assert_eq!(state.latest_execution_id, 2);
+}
+
+/// mock clearing queue holding an obligation of 100 for each of the given
+/// ids, with a settlement authorization of `settle_functions` functions
+fn pending_obligations_mock(
+    layout: &StrategyLayout,
+    ids: &[u64],
+    settle_functions: usize,
+) -> MockDomains {
+    let mock = mock_domains(layout);
+    {
+        let mut state = mock.state();
+        state.clearing_queue = ids
+            .iter()
+            .map(|id| MockObligation {
+                id: *id,
+                recipient: format!("user_{id}"),
+                payout_coins: vec![coin(100, DENOM)],
+            })
+            .collect();
+        state.latest_obligation_id = ids.last().copied();
+        state
+            .subroutine_functions
+            .insert(SETTLE_OBLIGATION_BATCH_LABEL.to_string(), settle_functions);
+    }
+    mock
+}
+
+#[tokio::test]
+async fn obligations_are_settled_in_batches_of_the_authorized_size() {
+    let layout = cctp_layout();
+    let mock = pending_obligations_mock(&layout, &[0, 1, 2, 3, 4], 2);
+    mock.state().set_mars_lend(MARS_OWNER, DENOM, 1_000);
+    let mut strategy = mock_strategy("batched_settlement", layout, &mock);
+
+    strategy.settlement().await.unwrap();
+
+    {
+        let state = mock.state();
+        assert!(state.clearing_queue.is_empty());
+        assert_eq!(state.balance(Domain::Neutron, "user_4", DENOM), 100);
+        assert_eq!(state.balance(Domain::Neutron, "settlement", DENOM), 0);
+    }
+    // the layout allows for batches of 3, but the authorization only for 2.
+    // two full batches and the remaining obligation on its own
+    let rendered = strategy.metrics.render();
+    assert!(rendered.contains(&format!("{} 3", metrics::SETTLEMENT_BATCHES)));
+    assert!(rendered.contains(&format!("{} 5", metrics::OBLIGATIONS_SETTLED)));
+    assert!(rendered.contains(&format!("{} 2", metrics::SETTLEMENT_BATCH_SIZE)));
+    assert!(!strategy.batch_fallback_warned.load(Ordering::Relaxed));
+}
+
+#[tokio::test]
+async fn missing_batch_authorization_settles_one_obligation_per_execution() {
+    let layout = cctp_layout();
+    let mock = pending_obligations_mock(&layout, &[0, 1, 2], 2);
+    {
+        // deployed ahead of batched settlement
+        let mut state = mock.state();
+        state
+            .subroutine_functions
+            .remove(SETTLE_OBLIGATION_BATCH_LABEL);
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+    }
+    let mut strategy = mock_strategy("missing_batch_authorization", layout, &mock);
+
+    strategy.settlement().await.unwrap();
+
+    assert!(mock.state().clearing_queue.is_empty());
+    assert!(strategy.batch_fallback_warned.load(Ordering::Relaxed));
+    let rendered = strategy.metrics.render();
+    assert!(rendered.contains(&format!("{} 1", metrics::SETTLEMENT_BATCH_SIZE)));
+    assert!(rendered.contains(&format!("{} 3", metrics::SETTLEMENT_BATCHES)));
+}
+
+#[tokio::test]
+async fn partial_settlement_batches_are_settled_one_obligation_at_a_time() {
+    let layout = cctp_layout();
+    let mock = pending_obligations_mock(&layout, &[0, 1, 2, 3, 4], 3);
+    mock.state().set_mars_lend(MARS_OWNER, DENOM, 1_000);
+    let mut strategy = mock_strategy("partial_settlement_batch", layout, &mock);
+
+    strategy.settlement().await.unwrap();
+
+    let state = mock.state();
+    assert!(state.clearing_queue.is_empty());
+    let mut executions: Vec<(&u64, &str)> = state
+        .processor_callbacks
+        .iter()
+        .map(|(id, callback)| (id, callback.label.as_str()))
+        .filter(|(_, label)| label.starts_with(SETTLE_OBLIGATION_LABEL))
+        .collect();
+    executions.sort();
+    let labels: Vec<&str> = executions.into_iter().map(|(_, label)| label).collect();
+    // the batch authorization only takes exactly 3 messages, so the last two
+    // obligations are settled through the single authorization
+    assert_eq!(
+        labels,
+        vec![
+            SETTLE_OBLIGATION_BATCH_LABEL,
+            SETTLE_OBLIGATION_LABEL,
+            SETTLE_OBLIGATION_LABEL
+        ]
+    );
+    assert!(state
+        .processor_callbacks
+        .values()
+        .all(|callback| callback.result == ExecutionResult::Success));
+}
+
+#[tokio::test]
+async fn settlement_refuses_to_withdraw_from_several_mars_credit_accounts() {
+    let layout = cctp_layout();
+    let mock = pending_obligations_mock(&layout, &[0, 1], 1);
+    {
+        let mut state = mock.state();
+        state.set_mars_lend(MARS_OWNER, DENOM, 100);
+        // lent through another credit account, out of reach of the library
+        state
+            .credit_account("account_01", MARS_OWNER)
+            .lends
+            .insert(DENOM.to_string(), 1_000);
+    }
+    let mut strategy = mock_strategy("several_mars_accounts", layout, &mock);
+
+    let err = strategy.settlement().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::InvariantViolated(_)
+    ));
+    let state = mock.state();
+    assert_eq!(state.clearing_queue.len(), 2);
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 100);
+    assert_eq!(state.latest_execution_id, 0);
+}
+
+#[tokio::test]
+async fn settlement_stops_at_the_first_obligation_it_cannot_cover() {
+    let mut layout = cctp_layout();
+    layout.settlement.denoms[0].source = SettlementSource::Held;
+    let mock = pending_obligations_mock(&layout, &[0, 1, 2, 3], 3);
+    mock.state()
+        .set_balance(Domain::Neutron, "settlement", DENOM, 250);
+    let mut strategy = mock_strategy("uncovered_settlement", layout, &mock);
+
+    let err = strategy.settlement().await.unwrap_err();
+
+    assert!(matches!(
+        StrategistError::classify(&err),
+        StrategistError::InsufficientBalance(_)
+    ));
+    let state = mock.state();
+    let pending: Vec<u64> = state.clearing_queue.iter().map(|o| o.id).collect();
+    assert_eq!(pending, vec![2, 3]);
+    assert_eq!(state.balance(Domain::Neutron, "user_1", DENOM), 100);
+    assert_eq!(state.balance(Domain::Neutron, "settlement", DENOM), 50);
+    assert!(state.processor_queue.is_empty());
+}
+
+#[tokio::test]
+async fn executions_are_followed_by_their_own_id_through_a_shared_queue() {
+    const FOREIGN_LABEL: &str = "foreign";
+
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    mock.on_subroutine(
+        FOREIGN_LABEL,
+        Arc::new(|_: &mut MockState, _: &[Value]| Ok(())),
+    );
+    {
+        let mut state = mock.state();
+        state.credit(Domain::Neutron, "deposit", DENOM, 1_000);
+        // someone else's batch is queued ahead of the entry, and another
+        // one gets queued right behind it
+        state.enqueue(FOREIGN_LABEL, vec![]);
+        state.foreign_labels = vec![FOREIGN_LABEL.to_string()];
+    }
+    let mut strategy = mock_strategy("shared_queue", layout, &mock);
+
+    strategy.deposit().await.unwrap();
+
+    let state = mock.state();
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 1_000);
+    assert_eq!(
+        state.processor_callbacks[&2].result,
+        ExecutionResult::Success
+    );
+    // the batch behind the entry is left for its owner to tick
+    let queued: Vec<u64> = state.processor_queue.iter().map(|b| b.id).collect();
+    assert_eq!(queued, vec![3]);
+}
+
+#[tokio::test]
+async fn failed_position_entry_leaves_the_funds_on_neutron() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    mock.on_subroutine(
+        ENTRY_LABEL,
+        Arc::new(|_: &mut MockState, _: &[Value]| Err(anyhow!("market is paused"))),
+    );
+    mock.state()
+        .credit(Domain::Neutron, "deposit", DENOM, 1_000);
+    let mut strategy = mock_strategy("failed_entry", layout, &mock);
+
+    strategy.deposit().await.unwrap();
+
+    let state = mock.state();
+    assert!(state.processor_queue.is_empty());
+    assert_eq!(state.balance(Domain::Neutron, "deposit", DENOM), 1_000);
+    assert_eq!(state.mars_lend(MARS_OWNER, DENOM), 0);
+    assert_eq!(
+        state.processor_callbacks[&state.latest_execution_id].result,
+        ExecutionResult::Rejected("market is paused".to_string())
+    );
+}
+
+#[tokio::test]
+async fn phase_failures_are_classified() {
+    let layout = cctp_layout();
+    let mock = mock_domains(&layout);
+    mock.state().set_mars_lend(MARS_OWNER, DENOM, 1_000);
+    let mut strategy = mock_strategy("classified", layout, &mock);
+
+    let err = strategy.update().await.unwrap_err();
+    let class = StrategistError::classify(&err);
+    assert!(matches!(class, StrategistError::InvariantViolated(_)));
+    assert!(!class.is_retryable());
+
+    {
+        let mut state = mock.state();
+        state.vault.issue_at_par(1_000);
+        state.vault.paused = true;
+    }
+    let err = strategy.update().await.unwrap_err();
+    assert_eq!(
+        StrategistError::classify(&err),
+        StrategistError::VaultPaused
+    );
+    // the revert itself is still there for the phase to inspect
+    assert!(err.downcast_ref::<EthTxError>().is_some());
+}
+
+/// eureka strategy whose ICA transfer out of the hub keeps failing, with a
+/// withdraw request waiting to be registered. the rate is at 1.0 as long as
+/// `total_supply` matches the TVL.
+fn degraded_deposit_mock(layout: &StrategyLayout, total_supply: u64) -> MockDomains {
+    let mock = mock_domains(layout);
+    mock.on_subroutine(
+        ICA_TRANSFER_LABEL,
+        Arc::new(|_: &mut MockState, _: &[Value]| Err(anyhow!("ica channel is closed"))),
+    );
+    {
+        let mut state = mock.state();
+        state.credit(Domain::Gaia, GAIA_ICA, GAIA_DENOM, 1_000);
+        state.set_mars_lend(MARS_OWNER, DENOM, 1_000);
+        state.vault.issue_at_par(total_supply);
+        state.withdraw_requests.push(MockWithdrawRequest {
+            id: 0,
+            receiver: "user".to_string(),
+            payout_coins: vec![coin(200, DENOM)],
+        });
+    }
+    mock
+}
+
+#[tokio::test]
+async fn failed_deposit_does_not_block_withdrawals_or_the_update() {
+    let layout = eureka_layout();
+    let mock = degraded_deposit_mock(&layout, 1_800);
+    let mut strategy = mock_strategy("degraded_deposit", layout, &mock);
+
+    let summary = strategy.run_phases().await.unwrap();
+
+    assert!(matches!(
+        summary.status(Phase::Deposit),
+        Some(PhaseStatus::Failed { kind, .. }) if kind == "tx_reverted"
+    ));
+    for phase in [Phase::Registration, Phase::Settlement, Phase::Update] {
+        assert!(summary.succeeded(phase), "{phase} did not succeed");
+    }
+
+    let state = mock.state();
+    assert_eq!(state.balance(Domain::Gaia, GAIA_ICA, GAIA_DENOM), 1_000);
+    assert_eq!(state.balance(Domain::Neutron, "user", DENOM), 200);
+    assert_eq!(state.vault.last_rate_update, state.timestamp);
+}
+
+#[tokio::test]
+async fn phase_is_skipped_when_its_dependency_fails() {
+    let mut layout = eureka_layout();
+    layout
+        .cycle
+        .phases
+        .iter_mut()
+        .find(|spec| spec.phase == Phase::Registration)
+        .unwrap()
+        .depends_on = vec![Phase::Deposit];
+    let mock = degraded_deposit_mock(&layout, 2_000);
+    let mut strategy = mock_strategy("skipped_registration", layout, &mock);
+
+    let summary = strategy.run_phases().await.unwrap();
+
+    assert!(matches!(
+        summary.status(Phase::Registration),
+        Some(PhaseStatus::Skipped { reason }) if reason.contains("deposit")
+    ));
+    assert!(summary.succeeded(Phase::Settlement));
+    assert!(summary.succeeded(Phase::Update));
+
+    let state = mock.state();
+    assert!(state.clearing_queue.is_empty());
+    assert_eq!(state.latest_obligation_id, None);
+    assert_eq!(state.vault.last_rate_update, state.timestamp);
+}
+
+fn rebalancer() -> Rebalancer {
+    Rebalancer {
+        dynamic_ratio_query_provider: "ratio_provider".to_string(),
+        mars_receiver: "mars_deposit".to_string(),
+        supervault_receiver: "supervault_deposit".to_string(),
+        credit_manager: "credit_manager".to_string(),
+        supervault: "supervault".to_string(),
+        bounds: RebalanceBounds {
+            max_step: Decimal::percent(10),
+            window_secs: 400 * 86_400,
+            ..Default::default()
+        },
+    }
+}
+
+/// mock of a supervault whose LP shares are worth 1.1 of the deposit
+/// denom, with 80% of the deposits split to Mars, and the LP share values
+/// sampled so far
+fn rebalance_mock(name: &str, share_values: &[RateSample]) -> (MockDomains, Strategy) {
+    let mut layout = cctp_layout();
+    layout.update.rebalance = Some(rebalancer());
+    let mock = mock_domains(&layout);
+    {
+        let mut state = mock.state();
+        state.supervaults.insert(
+            "supervault".to_string(),
+            MockSupervault {
+                lp_denom: "lp".to_string(),
+                token_0: DENOM.to_string(),
+                token_1: "ubtc".to_string(),
+                total_shares: 1_000,
+                reserve_0: 1_100,
+                reserve_1: 1_000,
+            },
+        );
+        state
+            .split_ratios
+            .insert("mars_deposit".to_string(), Decimal::percent(80));
+    }
+
+    let mut strategy = mock_strategy(name, layout, &mock);
+    let share_values_path: PathBuf =
+        std::env::temp_dir().join(format!("{name}-{}.share_values.jsonl", std::process::id()));
+    let history: String = share_values
+        .iter()
+        .map(|sample| serde_json::to_string(sample).unwrap() + "\n")
+        .collect();
+    std::fs::write(&share_values_path, history).unwrap();
+    strategy.share_values = Some(RateHistory::open(&share_values_path).unwrap());
+    let proposals_dir: PathBuf =
+        std::env::temp_dir().join(format!("{name}-{}.proposals", std::process::id()));
+    let _ = std::fs::remove_dir_all(&proposals_dir);
+    strategy.proposals_dir = Some(proposals_dir);
+
+    (mock, strategy)
+}
+
+#[tokio::test]
+async fn rebalance_proposes_moving_the_split_towards_the_better_yield() {
+    // the LP shares were worth 1.0 a year ago, i.e. the supervault yields
+    // 10% against the 5% lend rate of Mars
+    let year_ago = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        - 365 * 86_400;
+    let (_mock, strategy) = rebalance_mock(
+        "rebalance_proposal",
+        &[RateSample {
+            timestamp: year_ago,
+            rate: Decimal::one(),
+        }],
+    );
+
+    let proposal = strategy.propose_rebalance().await.unwrap().unwrap();
+
+    assert!((proposal.supervault_apy - 0.1).abs() < 0.001);
+    assert_eq!(proposal.current_mars_ratio, Decimal::percent(80));
+    assert_eq!(proposal.proposed_mars_ratio, Decimal::percent(70));
+    assert_eq!(proposal.msgs[0].contract, "ratio_provider");
+    assert_eq!(
+        proposal.msgs[0].msg["update_split_cfg"]["split_cfg"]["split_cfg"][DENOM]
+            ["supervault_deposit"],
+        "0.3"
+    );
+    assert_eq!(
+        proposal.msgs[1].contract,
+        strategy.layout.neutron.clearing_queue
+    );
+    assert_eq!(
+        proposal.msgs[1].msg["update_config"]["new_config"]["mars_settlement_ratio"],
+        "0.7"
+    );
+    // the artifact is left for the owner to execute
+    let written = std::fs::read_dir(strategy.proposals_dir.as_ref().unwrap()).unwrap();
+    assert_eq!(written.count(), 1);
+    assert!(strategy
+        .metrics
+        .render()
+        .contains(&format!("{} 1", metrics::REBALANCE_PROPOSALS)));
+}
+
+#[tokio::test]
+async fn rebalance_waits_for_enough_share_value_history() {
+    let (_mock, strategy) = rebalance_mock("rebalance_history", &[]);
+
+    assert!(strategy.propose_rebalance().await.unwrap().is_none());
+
+    // the share value is sampled for the following cycles
+    let samples = strategy.share_values.as_ref().unwrap().samples().unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].rate, Decimal::from_ratio(11u128, 10u128));
+    assert!(!strategy
+        .metrics
+        .render()
+        .contains(metrics::REBALANCE_PROPOSALS));
+}
@@ -0,0 +1,21 @@
+pub mod clients;
+pub mod cycle;
+pub mod error;
+mod execution;
+pub mod journal;
+pub mod layout;
+pub mod liquidity;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod phases;
+pub mod plan;
+pub mod rate_guard;
+pub mod rebalance;
+mod recovery;
+pub mod schedule;
+pub mod sentry;
+pub mod strategy;
+pub mod transit;
+mod worker;
+
+pub use strategy::Strategy;
@@ -0,0 +1,669 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::{
+    primitives::{Bytes, TxHash, U256},
+    sol_types::{SolCall, SolValue},
+};
+use anyhow::anyhow;
+use cosmwasm_std::{to_json_binary, Binary, StdResult};
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use valence_library_utils::OptionUpdate;
+
+use crate::{
+    labels::{CCTP_TRANSFER_LABEL, ICA_TRANSFER_LABEL},
+    phases::DEPOSIT_PHASE,
+    strategist::{
+        clients::call_request,
+        execution::Arrival,
+        layout::{CctpRoute, DepositRoute, Domain, EntryAction, EurekaRoute, LombardHop},
+        plan::PlannedAction,
+        Strategy,
+    },
+    types::sol_types::{
+        self, AtomicFunction, AtomicSubroutine, Authorization, CCTPTransfer, DurationType,
+        Priority, ProcessorMessage, ProcessorMessageType, RetryLogic, RetryTimes, RetryTimesType,
+        SendMsgs, Subroutine, SubroutineType, ERC20,
+    },
+    utils::{self, eth_tx::EthTxError, valence_core::ExecutionOutcome},
+};
+
+/// the timeout being used in skip ui
+const TWELVE_HOURS_IN_SECS: u64 = 43200;
+
+/// journal label of the ethereum tx that posts the eureka transfer zkp
+const EUREKA_TRANSFER_TX: &str = "eureka_transfer_zkp";
+
+impl Strategy {
+    /// carries out the steps needed to bring the new deposits from Ethereum to
+    /// Neutron before entering the configured positions.
+    /// consists of three stages:
+    /// 1. Ethereum -> Cosmos Hub (IBC-Eureka) or Ethereum -> Neutron (CCTP) routing
+    /// 2. Cosmos Hub -> Neutron routing (IBC-Eureka routes only)
+    /// 3. position entry
+    pub async fn deposit(&mut self) -> anyhow::Result<()> {
+        info!(target: DEPOSIT_PHASE, "starting deposit phase");
+
+        // Stage 1: deposit token routing out of Ethereum
+        {
+            // query the ethereum deposit account balance
+            let eth_deposit_acc_bal = self
+                .eth_client
+                .query(
+                    self.layout.ethereum.deposit_token,
+                    ERC20::balanceOfCall {
+                        account: self.layout.ethereum.deposit_account,
+                    },
+                    None,
+                )
+                .await?
+                ._0;
+            info!(target: DEPOSIT_PHASE, "eth deposit acc balance = {eth_deposit_acc_bal}");
+
+            // validate that the deposit account balance exceeds the routing
+            // threshold amount
+            if eth_deposit_acc_bal < self.layout.ethereum.transfer_threshold_amt {
+                // if balance does not exceed the transfer threshold, we skip the bridging steps
+                // and proceed to the next stage
+                info!(target: DEPOSIT_PHASE, "transfer threshold not met! Proceeding to the next stage.");
+            } else {
+                info!(target: DEPOSIT_PHASE, "transfer threshold met!");
+
+                let routing = match &self.layout.route {
+                    DepositRoute::Eureka(route) => {
+                        self.eth_to_gaia_routing(eth_deposit_acc_bal, route).await
+                    }
+                    DepositRoute::Cctp(route) => {
+                        self.eth_to_neutron_routing(eth_deposit_acc_bal, route)
+                            .await
+                    }
+                };
+
+                // a reverted routing tx did not move any funds, so there is
+                // nothing to wait for. the deposits stay in the deposit account
+                // until the next cycle, while the funds that already made it
+                // out of Ethereum carry on.
+                if let Err(e) = routing {
+                    match e.downcast_ref::<EthTxError>() {
+                        Some(EthTxError::Reverted { .. }) => {
+                            warn!(target: DEPOSIT_PHASE, "deposit routing out of Ethereum reverted: {e}");
+                        }
+                        _ => return Err(e),
+                    }
+                }
+            }
+        }
+
+        // transfers that arrived since they were sent are no longer in
+        // transit. this has to be settled before the funds get moved on
+        // from their destination.
+        self.settle_in_transit(DEPOSIT_PHASE, None).await?;
+
+        // Stage 2: deposit token routing from Cosmos Hub to Neutron
+        if let DepositRoute::Eureka(route) = &self.layout.route {
+            let gaia_ica_bal = self
+                .domain_balance(Domain::Gaia, &route.gaia_ica, &route.gaia_deposit_denom)
+                .await?;
+            info!(target: DEPOSIT_PHASE, "Cosmos Hub ICA balance = {gaia_ica_bal}");
+
+            // depending on the gaia ICA deposit token balance, we either perform the ICA IBC routing
+            // of the balances to Neutron, or proceed to the next stage
+            if gaia_ica_bal == 0 {
+                info!(target: DEPOSIT_PHASE, "nothing to bridge; proceeding to position entry");
+            } else {
+                info!(target: DEPOSIT_PHASE, "pulling funds to Neutron...");
+                self.gaia_to_neutron_routing(gaia_ica_bal, route).await?;
+            }
+        }
+
+        // Stage 3: position entry on Neutron
+        {
+            self.settle_in_transit(DEPOSIT_PHASE, None).await?;
+
+            let neutron_deposit_bal = self
+                .domain_balance(
+                    Domain::Neutron,
+                    &self.layout.neutron.deposit_account,
+                    &self.layout.neutron.deposit_denom,
+                )
+                .await?;
+            info!(target: DEPOSIT_PHASE, "Neutron deposit account balance = {neutron_deposit_bal}");
+
+            // depending on the neutron deposit account balance, we either conclude the deposit phase
+            // or enter the configured positions
+            if neutron_deposit_bal < self.layout.entry.min_balance {
+                info!(target: DEPOSIT_PHASE, "Neutron deposit account balance is insufficient for entry! concluding the deposit phase...");
+            } else {
+                info!(target: DEPOSIT_PHASE, "entering positions with {neutron_deposit_bal}...");
+                self.enter_positions(neutron_deposit_bal).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// enqueues all of the configured entry actions under the entry label
+    /// as a single atomic subroutine and ticks the processor
+    async fn enter_positions(&self, neutron_deposit_bal: u128) -> anyhow::Result<()> {
+        let mut messages: Vec<Binary> = vec![];
+
+        for action in &self.layout.entry.actions {
+            let message = match action {
+                // use Splitter to route funds from the Neutron program deposit
+                // account to the position input accounts
+                EntryAction::Split => {
+                    process_function_msg(valence_splitter_library::msg::FunctionMsgs::Split {})?
+                }
+                // use Mars Lending library to lend funds from Mars deposit account
+                // into Mars protocol
+                EntryAction::Lend => {
+                    process_function_msg(valence_mars_lending::msg::FunctionMsgs::Lend {})?
+                }
+                // use Supervaults lper library to deposit funds from Supervaults deposit account
+                // into the configured supervault
+                EntryAction::ProvideLiquidity => process_function_msg(
+                    valence_supervaults_lper::msg::FunctionMsgs::ProvideLiquidity {
+                        expected_vault_ratio_range: None,
+                    },
+                )?,
+                // mint maxBTC by sending the deposit token from the Neutron deposit
+                // account to the maxBTC contract
+                EntryAction::IssueMaxBtc => {
+                    process_function_msg(valence_maxbtc_issuer::msg::FunctionMsgs::Issue {})?
+                }
+            };
+            messages.push(message);
+        }
+
+        // the entry subroutine is atomic, so a failed entry leaves the funds
+        // in the Neutron deposit account for the next cycle to pick up
+        let outcome = self
+            .run_neutron_subroutine(DEPOSIT_PHASE, &self.layout.entry.label, messages)
+            .await?;
+        if outcome != ExecutionOutcome::Succeeded {
+            warn!(target: DEPOSIT_PHASE, "position entry did not go through ({outcome:?}); leaving the funds in the Neutron deposit account");
+            return Ok(());
+        }
+
+        // the entry moves the whole deposit account balance into the positions
+        self.transit.moved_out(
+            Domain::Neutron,
+            &self.layout.neutron.deposit_account,
+            &self.layout.neutron.deposit_denom,
+            neutron_deposit_bal,
+        )
+    }
+
+    /// carries out the steps needed to route the deposits from Ethereum program deposit
+    /// account to the configured Cosmos Hub ICA managed by Neutron Valence-ICA.
+    async fn eth_to_gaia_routing(
+        &self,
+        eth_deposit_acc_bal: U256,
+        route: &EurekaRoute,
+    ) -> anyhow::Result<()> {
+        // fetch the IBC-Eureka route from eureka client
+        let skip_api_response = match self
+            .ibc_eureka_client()?
+            .route(eth_deposit_acc_bal.to_string())
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                warn!(target: DEPOSIT_PHASE, "skip route error: {e}");
+                return Ok(());
+            }
+        };
+
+        let post_fee_amount_out_u128 = utils::skip::get_amount_out(&skip_api_response)?;
+        info!(target: DEPOSIT_PHASE, "post_fee_amount_out_u128 = {post_fee_amount_out_u128:?}");
+
+        // format the response in format expected by the coprocessor and post it
+        // there for proof. the lombard conversion has to yield the full quoted
+        // amount; the arrival tolerance only applies to the balance check.
+        let coprocessor_input = match &route.lombard {
+            Some(lombard) => json!({
+                "skip_response": skip_api_response,
+                "memo": lombard_pfm_memo(
+                    lombard,
+                    &route.gaia_ica,
+                    post_fee_amount_out_u128,
+                )?,
+            }),
+            None => json!({"skip_response": skip_api_response}),
+        };
+
+        info!(target: DEPOSIT_PHASE, "co-processor input: {coprocessor_input}");
+        info!(target: DEPOSIT_PHASE, "co-processor ID: {}", route.coprocessor_app_id);
+
+        let tx_hash = match &self.dry_run {
+            Some(dry_run) => {
+                dry_run.record(
+                    DEPOSIT_PHASE,
+                    PlannedAction::ZkAuthorization {
+                        chain: "ethereum".to_string(),
+                        label: EUREKA_TRANSFER_TX.to_string(),
+                        coprocessor_app_id: route.coprocessor_app_id.to_string(),
+                        input: coprocessor_input,
+                    },
+                );
+                None
+            }
+            None => {
+                self.post_eureka_transfer_zkp(route, &coprocessor_input)
+                    .await?
+            }
+        };
+
+        // transfer can be considered complete when the current ica balance increases
+        // by the post_fee ibc eureka transfer amount out, less the tolerance
+        let pre_routing_gaia_ica_bal = self
+            .domain_balance(Domain::Gaia, &route.gaia_ica, &route.gaia_deposit_denom)
+            .await?;
+
+        let arrival = Arrival {
+            transfer: EUREKA_TRANSFER_TX,
+            domain: Domain::Gaia,
+            address: &route.gaia_ica,
+            denom: &route.gaia_deposit_denom,
+            baseline: pre_routing_gaia_ica_bal,
+            sent: u128::try_from(eth_deposit_acc_bal)?,
+            quoted: post_fee_amount_out_u128,
+            tolerance_bps: route.arrival_tolerance_bps,
+            expiry_secs: route.transit_expiry_secs,
+            tx_hash: tx_hash.map(|h| h.to_string()),
+            poll_interval: 15, // every 15 sec
+            max_attempts: 100, // for 100 times
+        };
+        info!(
+            target: DEPOSIT_PHASE,
+            "gaia ica expected bal = {}; polling...",
+            arrival.expected()
+        );
+
+        // block execution until the funds arrive to the Cosmos Hub ICA owned
+        // by the Valence Interchain Account on Neutron.
+        // poll for 15sec * 100 = 1500sec = 25min which should suffice for
+        // IBC Eureka routing time of 15min
+        self.await_arrival(DEPOSIT_PHASE, arrival).await
+    }
+
+    /// obtains the eureka transfer zkp from the coprocessor and executes it
+    /// via the ethereum authorizations contract
+    async fn post_eureka_transfer_zkp(
+        &self,
+        route: &EurekaRoute,
+        coprocessor_input: &Value,
+    ) -> anyhow::Result<Option<TxHash>> {
+        let skip_response_zkp = self
+            .coprocessor_client
+            .prove(&route.coprocessor_app_id, coprocessor_input)
+            .await?;
+
+        info!(target: DEPOSIT_PHASE, "co_processor zkp post response: {skip_response_zkp:?}");
+
+        // build the eureka transfer zk message from decoded params
+        let auth_eureka_transfer_zk_msg = Authorization::executeZKMessageCall {
+            _inputs: Bytes::from(skip_response_zkp.inputs_program),
+            _proof: Bytes::from(skip_response_zkp.proof_program),
+            payload: Bytes::from(skip_response_zkp.proof_domain),
+        };
+
+        // sign and execute the tx & await its tx receipt before proceeding
+        info!(target: DEPOSIT_PHASE, "posting skip-api zkp ethereum authorizations");
+        self.execute_eth_tx(
+            DEPOSIT_PHASE,
+            EUREKA_TRANSFER_TX,
+            call_request(
+                self.layout.ethereum.authorizations,
+                &auth_eureka_transfer_zk_msg,
+            ),
+        )
+        .await
+    }
+
+    /// carries out the steps needed to route the deposits from cosmos hub ICA to the
+    /// Neutron deposit account.
+    /// two messages are enqueued:
+    /// 1. update the ica ibc transfer library transfer amount
+    /// 2. trigger the ica ibc transfer
+    async fn gaia_to_neutron_routing(
+        &self,
+        gaia_ica_bal: u128,
+        route: &EurekaRoute,
+    ) -> anyhow::Result<()> {
+        let ica_ibc_transfer_update_msg: valence_library_utils::msg::ExecuteMsg<
+            valence_ica_ibc_transfer::msg::FunctionMsgs,
+            valence_ica_ibc_transfer::msg::LibraryConfigUpdate,
+        > = valence_library_utils::msg::ExecuteMsg::UpdateConfig {
+            new_config: valence_ica_ibc_transfer::msg::LibraryConfigUpdate {
+                input_addr: None,
+                amount: Some(gaia_ica_bal.into()),
+                denom: None,
+                receiver: None,
+                memo: None,
+                remote_chain_info: None,
+                denom_to_pfm_map: None,
+                eureka_config: OptionUpdate::Set(None),
+            },
+        };
+        let ica_ibc_transfer_exec_msg =
+            valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+                valence_ica_ibc_transfer::msg::FunctionMsgs::Transfer {},
+            );
+
+        if let Some(neutron_ica) = &route.neutron_ica {
+            self.ensure_fees_coverage(DEPOSIT_PHASE, neutron_ica)
+                .await?;
+        }
+
+        // transfer can be considered complete when the current deposit account
+        // balance increases by the ica balance being transferred
+        let pre_routing_neutron_deposit_acc_bal = self
+            .domain_balance(
+                Domain::Neutron,
+                &self.layout.neutron.deposit_account,
+                &self.layout.neutron.deposit_denom,
+            )
+            .await?;
+
+        let arrival = Arrival {
+            transfer: ICA_TRANSFER_LABEL,
+            domain: Domain::Neutron,
+            address: &self.layout.neutron.deposit_account,
+            denom: &self.layout.neutron.deposit_denom,
+            baseline: pre_routing_neutron_deposit_acc_bal,
+            sent: gaia_ica_bal,
+            quoted: gaia_ica_bal,
+            tolerance_bps: route.arrival_tolerance_bps,
+            expiry_secs: route.transit_expiry_secs,
+            tx_hash: None,
+            poll_interval: 5,
+            max_attempts: 30,
+        };
+        info!(
+            target: DEPOSIT_PHASE,
+            "neutron deposit acc expected bal = {}",
+            arrival.expected()
+        );
+
+        info!(target: DEPOSIT_PHASE, "enqueuing & ticking ica_ibc_transfer library update & transfer");
+        // nothing is in flight if the transfer failed, so there is nothing to wait for
+        self.run_neutron_subroutine(
+            DEPOSIT_PHASE,
+            ICA_TRANSFER_LABEL,
+            vec![
+                to_json_binary(&ica_ibc_transfer_update_msg)?,
+                to_json_binary(&ica_ibc_transfer_exec_msg)?,
+            ],
+        )
+        .await?
+        .ensure_succeeded(ICA_TRANSFER_LABEL)?;
+        self.transit.moved_out(
+            Domain::Gaia,
+            &route.gaia_ica,
+            &route.gaia_deposit_denom,
+            gaia_ica_bal,
+        )?;
+
+        info!(target: DEPOSIT_PHASE, "polling for neutron deposit account to receive the funds");
+
+        // block execution until funds arrive to the Neutron program deposit
+        // account
+        self.await_arrival(DEPOSIT_PHASE, arrival).await
+    }
+
+    /// carries out the steps needed to route the deposits from Ethereum program deposit
+    /// account to the Neutron deposit account via CCTP and Noble forwarding.
+    async fn eth_to_neutron_routing(
+        &self,
+        eth_deposit_acc_bal: U256,
+        route: &CctpRoute,
+    ) -> anyhow::Result<()> {
+        let eth_deposit_acc_bal_u128 = u128::try_from(eth_deposit_acc_bal)?;
+
+        // transfer can be considered complete when the current deposit account balance
+        // increases by the amount available on eth deposit account, less the tolerance
+        let pre_routing_neutron_deposit_acc_bal = self
+            .domain_balance(
+                Domain::Neutron,
+                &self.layout.neutron.deposit_account,
+                &self.layout.neutron.deposit_denom,
+            )
+            .await?;
+
+        let enqueue_msg_tx_request = call_request(
+            self.layout.ethereum.authorizations,
+            &Authorization::sendProcessorMessageCall {
+                label: CCTP_TRANSFER_LABEL.to_string(),
+                _message: cctp_transfer_processor_message(route)?,
+            },
+        );
+
+        let tx_hash = self
+            .execute_eth_tx(DEPOSIT_PHASE, CCTP_TRANSFER_LABEL, enqueue_msg_tx_request)
+            .await?;
+
+        let arrival = Arrival {
+            transfer: CCTP_TRANSFER_LABEL,
+            domain: Domain::Neutron,
+            address: &self.layout.neutron.deposit_account,
+            denom: &self.layout.neutron.deposit_denom,
+            baseline: pre_routing_neutron_deposit_acc_bal,
+            sent: eth_deposit_acc_bal_u128,
+            quoted: eth_deposit_acc_bal_u128,
+            tolerance_bps: route.arrival_tolerance_bps,
+            expiry_secs: route.transit_expiry_secs,
+            tx_hash: tx_hash.map(|h| h.to_string()),
+            poll_interval: 15, // every 15 sec
+            max_attempts: 100, // for 100 times
+        };
+        info!(
+            target: DEPOSIT_PHASE,
+            "Neutron deposit account expected bal = {}; polling...",
+            arrival.expected()
+        );
+
+        // block execution until the funds arrive to the Neutron deposit account
+        // via forwarding from Noble.
+        // poll for 15sec * 100 = 1500sec = 25min.
+        // note: cctp routes can take a while. if this becomes a consistent issue,
+        // look into self-attestation: https://docs.noble.xyz/cctp/manual_relaying
+        self.await_arrival(DEPOSIT_PHASE, arrival).await
+    }
+}
+
+/// wraps a library function into a `ProcessFunction` execute message
+fn process_function_msg<T: Serialize>(function: T) -> StdResult<Binary> {
+    to_json_binary(&valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(function))
+}
+
+/// builds the abi-encoded `SendMsgs` processor message that triggers the
+/// CCTP transfer library as an atomic subroutine
+fn cctp_transfer_processor_message(route: &CctpRoute) -> anyhow::Result<Bytes> {
+    let encoded_transfer_call = CCTPTransfer::transferCall {}.abi_encode();
+
+    let atomic_subroutine = AtomicSubroutine {
+        functions: vec![AtomicFunction {
+            contractAddress: route.cctp_transfer,
+        }],
+        // NoRetry for atomic execution
+        retryLogic: RetryLogic {
+            times: RetryTimes {
+                retryType: RetryTimesType::NoRetry,
+                amount: 0,
+            },
+            interval: sol_types::Duration {
+                durationType: DurationType::Time,
+                value: 0,
+            },
+        },
+    };
+
+    let send_msgs = SendMsgs {
+        executionId: 1, // Generated execution ID
+        priority: Priority::Medium,
+        subroutine: Subroutine {
+            subroutineType: SubroutineType::Atomic,
+            subroutine: Bytes::from(atomic_subroutine.abi_encode()),
+        },
+        expirationTime: 0, // No expiration
+        messages: vec![Bytes::from(encoded_transfer_call)],
+    };
+
+    let processor_message = ProcessorMessage {
+        messageType: ProcessorMessageType::SendMsgs,
+        message: Bytes::from(send_msgs.abi_encode()),
+    };
+
+    Ok(Bytes::from(processor_message.abi_encode()))
+}
+
+/// builds the lombard swap-and-action memo that converts the eureka denom
+/// into the lombard native denom and forwards it to the cosmos hub ICA
+fn lombard_pfm_memo(
+    lombard: &LombardHop,
+    gaia_ica: &str,
+    min_amount_out: u128,
+) -> anyhow::Result<serde_json::Value> {
+    let timeout_duration = Duration::from_secs(TWELVE_HOURS_IN_SECS);
+    let timeout_time = SystemTime::now()
+        .checked_add(timeout_duration)
+        .ok_or(anyhow!("failed to extend current time by 12h"))?;
+
+    let timeout_timestamp_nanos = timeout_time
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| anyhow!("bad times: {e}"))?
+        .as_nanos();
+
+    Ok(json!(
+        {
+          "dest_callback": {
+            "address": lombard.callback_contract
+          },
+          "wasm": {
+            "contract": lombard.entry_contract,
+            "msg": {
+              "swap_and_action": {
+                "user_swap": {
+                  "swap_exact_asset_in": {
+                    "swap_venue_name": "ledger-lbtc-convert",
+                    "operations": [
+                      {
+                        "pool": "",
+                        "denom_in": lombard.eureka_denom,
+                        "denom_out": lombard.native_denom
+                      }
+                    ]
+                  }
+                },
+                "min_asset": {
+                  "native": {
+                    "denom": lombard.native_denom,
+                    "amount": min_amount_out.to_string()
+                  }
+                },
+                "timeout_timestamp": timeout_timestamp_nanos,
+                "post_swap_action": {
+                  "ibc_transfer": {
+                    "ibc_info": {
+                      "source_channel": "channel-0",
+                      "receiver": gaia_ica,
+                      "memo": "",
+                      "recover_address": lombard.ica
+                    }
+                  }
+                },
+                "affiliates": []
+              }
+            }
+          }
+        }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+
+    use super::*;
+    use crate::strategist::layout::ChainConnection;
+
+    fn connection() -> ChainConnection {
+        ChainConnection {
+            grpc_url: "http://localhost".to_string(),
+            grpc_port: "9090".to_string(),
+            chain_id: "test-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_process_function_msg_wraps_the_function() {
+        let msg = process_function_msg(json!({ "lend": {} })).unwrap();
+
+        let decoded: Value = serde_json::from_slice(msg.as_slice()).unwrap();
+        assert_eq!(decoded, json!({ "process_function": { "lend": {} } }));
+    }
+
+    #[test]
+    fn test_cctp_transfer_processor_message_triggers_the_transfer_library() {
+        let route = CctpRoute {
+            noble: connection(),
+            noble_fee_denom: "uusdc".to_string(),
+            cctp_transfer: Address::repeat_byte(0x11),
+            arrival_tolerance_bps: CctpRoute::DEFAULT_ARRIVAL_TOLERANCE_BPS,
+            transit_expiry_secs: CctpRoute::DEFAULT_TRANSIT_EXPIRY_SECS,
+        };
+
+        let message = cctp_transfer_processor_message(&route).unwrap();
+
+        let processor_message = ProcessorMessage::abi_decode(&message, true).unwrap();
+        assert!(matches!(
+            processor_message.messageType,
+            ProcessorMessageType::SendMsgs
+        ));
+        let send_msgs = SendMsgs::abi_decode(&processor_message.message, true).unwrap();
+        assert!(matches!(
+            send_msgs.subroutine.subroutineType,
+            SubroutineType::Atomic
+        ));
+        let subroutine =
+            AtomicSubroutine::abi_decode(&send_msgs.subroutine.subroutine, true).unwrap();
+        assert_eq!(subroutine.functions.len(), 1);
+        assert_eq!(subroutine.functions[0].contractAddress, route.cctp_transfer);
+        assert_eq!(
+            send_msgs.messages,
+            vec![Bytes::from(CCTPTransfer::transferCall {}.abi_encode())]
+        );
+    }
+
+    #[test]
+    fn test_lombard_pfm_memo_converts_and_forwards_to_the_hub() {
+        let lombard = LombardHop {
+            connection: connection(),
+            ica: "lom1recover".to_string(),
+            deposit_denom: "ibc/lbtc".to_string(),
+            eureka_denom: "ibc/eureka".to_string(),
+            native_denom: "ulbtc".to_string(),
+            entry_contract: "lom1entry".to_string(),
+            callback_contract: "lom1callback".to_string(),
+        };
+
+        let memo = lombard_pfm_memo(&lombard, "cosmos1ica", 995).unwrap();
+
+        let action = &memo["wasm"]["msg"]["swap_and_action"];
+        assert_eq!(memo["wasm"]["contract"], "lom1entry");
+        assert_eq!(memo["dest_callback"]["address"], "lom1callback");
+        assert_eq!(
+            action["user_swap"]["swap_exact_asset_in"]["operations"][0],
+            json!({ "pool": "", "denom_in": "ibc/eureka", "denom_out": "ulbtc" })
+        );
+        assert_eq!(
+            action["min_asset"]["native"],
+            json!({ "denom": "ulbtc", "amount": "995" })
+        );
+        let ibc_info = &action["post_swap_action"]["ibc_transfer"]["ibc_info"];
+        assert_eq!(ibc_info["receiver"], "cosmos1ica");
+        assert_eq!(ibc_info["recover_address"], "lom1recover");
+    }
+}
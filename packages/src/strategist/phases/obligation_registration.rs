@@ -0,0 +1,290 @@
+use anyhow::anyhow;
+use futures::stream::{self, StreamExt};
+use log::{error, info, warn};
+use serde_json::json;
+
+use crate::{
+    labels::REGISTER_OBLIGATION_LABEL,
+    phases::REGISTRATION_PHASE,
+    strategist::{
+        clients::{ContractQuerier, ZkProof},
+        error::StrategistError,
+        journal::JournalStep,
+        plan::PlannedAction,
+        Strategy,
+    },
+    utils::{metrics, obligation::ClearingQueueConfig, valence_core},
+};
+
+impl Strategy {
+    /// reads the newly submitted withdrawal obligations that are not yet
+    /// present in the Clearing Queue, generates their zero-knowledge proofs,
+    /// and posts them into the Clearing queue, in order.
+    /// consists of the following stages:
+    /// 1. fetching all new withdraw obligations from the indexer and the vault,
+    ///    reading the vault at the snapshot Ethereum block
+    /// 2. generating ZKPs for batches of the newly fetched obligations,
+    ///    concurrently
+    /// 3. posting the ZKPs to the neutron authorizations module in order,
+    ///    and ticking the resulting registrations through
+    pub async fn register_withdraw_obligations(&mut self) -> anyhow::Result<()> {
+        info!(target: REGISTRATION_PHASE, "starting withdraw obligation registration phase");
+
+        // registrations left queued behind a failed one go through (or get
+        // rejected) before the queue is read for the next one to register
+        self.reconcile_phase(REGISTRATION_PHASE).await?;
+
+        let snapshot = self.snapshot().await?;
+        info!(target: REGISTRATION_PHASE, "querying at snapshot {}", serde_json::to_string(&snapshot)?);
+
+        // query the Clearing Queue library for the latest posted withdraw request ID
+        let clearing_queue_cfg: ClearingQueueConfig = self
+            .neutron_client
+            .query_contract_state(
+                &self.layout.neutron.clearing_queue,
+                valence_clearing_queue_supervaults::msg::QueryMsg::GetLibraryConfig {},
+                Some(snapshot.neutron_height),
+            )
+            .await?;
+
+        info!(
+            target: REGISTRATION_PHASE,
+            "latest_registered_obligation_id={:?}", clearing_queue_cfg.latest_id
+        );
+
+        // prepare the start_id for the indexer query. if there is no latest_id on the
+        // clearing queue config, meaning there are no obligations registered yet, we
+        // default to 0 to fetch everything. otherwise we increment the id by 1 to only
+        // fetch the new withdraw requests that have not been posted to the clearing
+        // queue yet.
+        let start_id = clearing_queue_cfg.next_id();
+
+        // fetch all obligations that were registered on the vault but are not
+        // yet registered into the queue on Neutron
+        let new_obligations = self
+            .new_withdraw_request_ids(start_id, snapshot.ethereum_block)
+            .await?;
+
+        if new_obligations.is_empty() {
+            info!(target: REGISTRATION_PHASE, "no new withdraw requests; concluding obligation registration phase...");
+            return Ok(());
+        }
+        info!(target: REGISTRATION_PHASE, "new_obligations = {new_obligations:?}");
+
+        // process the new OneWayVault Withdraw events in batches, in order
+        // from the oldest to the newest
+        for batch in new_obligations.chunks(self.layout.registration.batch_size.max(1)) {
+            self.register_batch(batch).await?;
+        }
+
+        info!(target: REGISTRATION_PHASE, "finished processing withdraw requests; concluding obligation registration phase...");
+
+        Ok(())
+    }
+
+    /// registers the batch of consecutive obligations. their proofs are
+    /// requested from the coprocessor concurrently, but posted strictly in
+    /// order, so a failed proof (or post) leaves the obligations after it
+    /// for the next cycle. the registrations that did get posted are then
+    /// ticked through in a single pass, failed post or not, up to the first
+    /// registration that fails.
+    async fn register_batch(&self, obligation_ids: &[u64]) -> anyhow::Result<()> {
+        info!(target: REGISTRATION_PHASE, "registering obligations {obligation_ids:?}");
+
+        if let Some(dry_run) = &self.dry_run {
+            for obligation_id in obligation_ids {
+                dry_run.record(
+                    REGISTRATION_PHASE,
+                    PlannedAction::ZkAuthorization {
+                        chain: "neutron".to_string(),
+                        label: REGISTER_OBLIGATION_LABEL.to_string(),
+                        coprocessor_app_id: self.layout.neutron.clearing_queue_app_id.to_string(),
+                        input: json!({"withdraw_request_id": obligation_id}),
+                    },
+                );
+            }
+            for _ in obligation_ids {
+                dry_run.record(
+                    REGISTRATION_PHASE,
+                    PlannedAction::NeutronTick {
+                        processor: self.layout.neutron.processor.to_string(),
+                    },
+                );
+            }
+            return Ok(());
+        }
+
+        // the proofs are yielded in the order they were requested in,
+        // regardless of the order they complete in
+        let mut proofs = stream::iter(obligation_ids.iter().copied())
+            .map(|obligation_id| self.prove_obligation(obligation_id))
+            .buffered(self.layout.registration.max_concurrent_proofs.max(1));
+
+        let mut posted = vec![];
+        // failed proof or post that the posting stopped at
+        let mut failure = None;
+        while let Some(proof) = proofs.next().await {
+            let (obligation_id, vault_zkp) = match proof {
+                Ok(proof) => proof,
+                Err(e) => {
+                    // the obligations after it would be registered out of
+                    // order, so they are left for the next cycle
+                    failure = Some(e);
+                    break;
+                }
+            };
+            // past the rate update deadline, the rest is left for the next
+            // cycle, like after a failed proof
+            if let Err(e) = self
+                .preemption
+                .check(&format!("posting obligation #{obligation_id}"))
+            {
+                failure = Some(e);
+                break;
+            }
+
+            // submits the decoded zkp parameters to the program authorizations module.
+            // the zk authorization pushes the registration to the processor queue,
+            // so it is journaled the same way as regular enqueues
+            let step_id = self.journal.start(
+                REGISTRATION_PHASE,
+                JournalStep::NeutronSubroutine {
+                    label: REGISTER_OBLIGATION_LABEL.to_string(),
+                },
+            )?;
+            let execution_id = match valence_core::post_zkp_on_chain(
+                self.neutron_client.as_ref(),
+                &self.layout.neutron.authorizations,
+                vault_zkp,
+            )
+            .await
+            {
+                Ok(execution_id) => execution_id,
+                Err(e) => {
+                    // the registrations posted so far are still ticked
+                    // through, rather than left in the queue. the step is
+                    // left in flight, as the post may have been included.
+                    warn!(target: REGISTRATION_PHASE, "failed to post obligation #{obligation_id}: {e}");
+                    failure = Some(e);
+                    break;
+                }
+            };
+            info!(target: REGISTRATION_PHASE, "posted obligation #{obligation_id} (execution #{execution_id})");
+
+            posted.push((step_id, execution_id));
+        }
+        // drops the proofs still in flight past a failed one
+        drop(proofs);
+
+        // tick the processor through the posted registrations. obligations
+        // are registered in order, so a failed registration fails the phase,
+        // and the ones posted after it are left in flight rather than ticked
+        // out of order. the next run reconciles them from the journal first,
+        // and resumes from the failed request.
+        for (step_id, execution_id) in posted {
+            let outcome = self
+                .tick_execution(
+                    REGISTRATION_PHASE,
+                    step_id,
+                    REGISTER_OBLIGATION_LABEL,
+                    execution_id,
+                )
+                .await?;
+
+            outcome.ensure_succeeded(REGISTER_OBLIGATION_LABEL)?;
+            self.metrics
+                .inc_counter(metrics::OBLIGATIONS_REGISTERED, &[], 1.0);
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// posts the proof request of the obligation to the coprocessor
+    async fn prove_obligation(&self, obligation_id: u64) -> anyhow::Result<(u64, ZkProof)> {
+        // build the json input for coprocessor client
+        let withdraw_id_json = json!({"withdraw_request_id": obligation_id});
+
+        info!(target: REGISTRATION_PHASE, "posting proof request to coprocessor client: {withdraw_id_json}");
+        let vault_zkp = self
+            .coprocessor_client
+            .prove(
+                &self.layout.neutron.clearing_queue_app_id,
+                &withdraw_id_json,
+            )
+            .await
+            .map_err(|e| {
+                StrategistError::ProofFailed(format!("obligation #{obligation_id}: {e:#}"))
+            })?;
+
+        info!(target: REGISTRATION_PHASE, "vault zkp resp for obligation #{obligation_id}: {vault_zkp:?}");
+
+        Ok((obligation_id, vault_zkp))
+    }
+
+    /// ids of the withdraw requests that are yet to be registered, starting
+    /// from the id that the clearing queue expects next. the OneWayVault
+    /// indexer and the vault itself are both read and cross-checked, so
+    /// that either one carries the phase if the other one fails. the vault
+    /// is what the obligations get proven against, so it prevails when the
+    /// two disagree.
+    async fn new_withdraw_request_ids(
+        &self,
+        start_id: u64,
+        ethereum_block: u64,
+    ) -> anyhow::Result<Vec<u64>> {
+        let (indexed, on_chain) = tokio::join!(
+            self.indexer_client.withdraw_request_ids(start_id),
+            valence_core::query_vault_withdraw_request_ids(
+                self.layout.ethereum.one_way_vault,
+                self.eth_client.as_ref(),
+                start_id,
+                Some(ethereum_block),
+            ),
+        );
+
+        let ids = match (indexed, on_chain) {
+            (Ok(indexed), Ok(on_chain)) => {
+                if indexed != on_chain {
+                    error!(
+                        target: REGISTRATION_PHASE,
+                        "indexer and vault disagree on the withdraw requests from #{start_id}: indexer = {indexed:?}, vault = {on_chain:?}; going with the vault"
+                    );
+                }
+                on_chain
+            }
+            (Err(e), Ok(on_chain)) => {
+                warn!(target: REGISTRATION_PHASE, "failed to query the indexer: {e}; falling back to the vault");
+                on_chain
+            }
+            (Ok(indexed), Err(e)) => {
+                warn!(target: REGISTRATION_PHASE, "failed to read the withdraw requests from the vault: {e}; going with the indexer");
+                indexed
+            }
+            (Err(indexer_err), Err(vault_err)) => {
+                return Err(anyhow!(
+                    "failed to fetch the withdraw requests from both the indexer ({indexer_err}) and the vault ({vault_err})"
+                ))
+            }
+        };
+
+        // obligations are registered in order, so only the run of ids that
+        // consecutively follows the latest registered one can be registered
+        let consecutive = ids
+            .iter()
+            .zip(start_id..)
+            .take_while(|(id, expected)| **id == *expected)
+            .count();
+        if consecutive < ids.len() {
+            error!(
+                target: REGISTRATION_PHASE,
+                "withdraw request #{} is missing from {ids:?} (clearing queue expects #{start_id} next); only registering the ones before the gap",
+                start_id + consecutive as u64
+            );
+        }
+
+        Ok(ids[..consecutive].to_vec())
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use crate::{
+    phases::SENTRY_PHASE,
+    strategist::{
+        plan::PlannedAction,
+        sentry::{SentryCondition, SentryContext},
+        Strategy,
+    },
+    utils::valence_core,
+};
+
+/// what the rest of the cycle consists of, as decided by the sentry phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SentryOutcome {
+    /// sentry predicate holds; the full cycle runs
+    Cycle,
+    /// sentry predicate does not hold, but the posted rate is about to go
+    /// stale; only the update phase runs
+    UpdateOnly,
+}
+
+impl Strategy {
+    /// condition-based sentry phase. after flushing the processor queue,
+    /// waits for the configured sentry predicate to hold, re-evaluating it
+    /// after every strategy timeout.
+    /// regardless of the predicate, the sentry stops waiting once the
+    /// mandatory rate update is due, and hands over to an update-only cycle.
+    pub async fn sentry(&mut self) -> anyhow::Result<SentryOutcome> {
+        // in dry-run mode we only plan the flush, report the predicate,
+        // and run the cycle right away
+        if let Some(dry_run) = &self.dry_run {
+            let queue = valence_core::query_neutron_processor_queue(
+                self.neutron_client.as_ref(),
+                &self.layout.neutron.processor,
+                valence_authorization_utils::authorization::Priority::Medium,
+            )
+            .await?;
+            for _ in queue.iter() {
+                dry_run.record(
+                    SENTRY_PHASE,
+                    PlannedAction::NeutronTick {
+                        processor: self.layout.neutron.processor.to_string(),
+                    },
+                );
+            }
+            let holds = self.sentry_predicate_holds().await;
+            info!(target: SENTRY_PHASE, "sentry predicate holds: {holds}");
+            return Ok(SentryOutcome::Cycle);
+        }
+
+        // before starting the cycle we flush any existing items
+        // from the processor queue
+        valence_core::flush_neutron_processor_queue(
+            self.neutron_client.as_ref(),
+            &self.layout.neutron.processor,
+            valence_authorization_utils::authorization::Priority::Medium,
+        )
+        .await?;
+
+        loop {
+            // never sleep past the mandatory rate update
+            let update_due_in = self.update_due_in().await?;
+            let timeout = Duration::from_secs(self.timeout);
+            let nap = update_due_in.map_or(timeout, |due_in| due_in.min(timeout));
+
+            info!(target: SENTRY_PHASE, "sleeping for {}sec", nap.as_secs());
+            sleep(nap).await;
+
+            if self.sentry_predicate_holds().await {
+                info!(target: SENTRY_PHASE, "sentry predicate holds; proceeding with the cycle");
+                return Ok(SentryOutcome::Cycle);
+            }
+
+            if self
+                .update_due_in()
+                .await?
+                .is_some_and(|due_in| due_in.is_zero())
+            {
+                warn!(target: SENTRY_PHASE, "sentry predicate does not hold, but the rate is about to go stale; forcing an update");
+                return Ok(SentryOutcome::UpdateOnly);
+            }
+
+            info!(target: SENTRY_PHASE, "sentry predicate does not hold; waiting");
+        }
+    }
+
+    /// evaluates the sentry predicate. predicates that fail to evaluate
+    /// are treated as not holding.
+    async fn sentry_predicate_holds(&self) -> bool {
+        let ctx = SentryContext {
+            layout: &self.layout,
+            ethereum: self.eth_client.as_ref(),
+            neutron: self.neutron_client.as_ref(),
+            indexer: self.indexer_client.as_ref(),
+        };
+
+        self.layout
+            .sentry
+            .predicate
+            .holds(&ctx)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(target: SENTRY_PHASE, "failed to evaluate the sentry predicate: {e}");
+                false
+            })
+    }
+}
@@ -0,0 +1,444 @@
+use std::{collections::HashMap, sync::atomic::Ordering};
+
+use anyhow::anyhow;
+use cosmwasm_std::{to_json_binary, Coin};
+use log::{info, warn};
+
+use crate::{
+    labels::{MARS_WITHDRAW_LABEL, SETTLE_OBLIGATION_BATCH_LABEL, SETTLE_OBLIGATION_LABEL},
+    phases::SETTLEMENT_PHASE,
+    strategist::{
+        clients::ContractQuerier,
+        error::StrategistError,
+        layout::{Domain, Position, SettlementSource},
+        liquidity::{plan_liquidity, LiquidityAction, LiquiditySources},
+        Strategy,
+    },
+    utils::{
+        mars::{query_mars_position, query_mars_red_bank, query_red_bank_market},
+        metrics,
+        obligation::{batch_obligation_queue_payouts, ObligationsResponse, PendingObligation},
+        valence_core,
+    },
+};
+
+impl Strategy {
+    /// performs the final settlement of registered withdrawal obligations in
+    /// the Clearing Queue library. this involves topping up the settlement
+    /// account with funds necessary to clear out all withdrawal obligations
+    /// in the queue.
+    /// consists of the following stages:
+    /// 1. query the pending obligations clearing queue and batch them up.
+    ///    the queue and the settlement account balances are read at a single
+    ///    snapshot so that they are consistent with each other
+    /// 2. ensure the queue is ready to be cleared. the liquidity planner
+    ///    works out the Mars and supervault withdrawals that cover the
+    ///    batched payouts that the settlement account falls short of, with
+    ///    the Mars withdrawals capped at the liquidity of the red bank
+    ///    market. if the queue cannot be covered, nothing is withdrawn and
+    ///    the phase fails.
+    /// 3. clear the queue in a FIFO manner, in batches of obligations that
+    ///    are each settled by a single processor execution
+    pub async fn settlement(&mut self) -> anyhow::Result<()> {
+        info!(target: SETTLEMENT_PHASE, "starting settlement phase");
+
+        let snapshot = self.snapshot().await?;
+        info!(target: SETTLEMENT_PHASE, "querying at snapshot {}", serde_json::to_string(&snapshot)?);
+        let neutron = self.querier(Domain::Neutron)?;
+        let height = snapshot.height(Domain::Neutron);
+
+        // query the current settlement account balances of all payout denoms
+        let mut settlement_balances: HashMap<String, u128> = HashMap::new();
+        for settlement_denom in &self.layout.settlement.denoms {
+            let balance = neutron
+                .query_balance(
+                    &self.layout.neutron.settlement_account,
+                    &settlement_denom.denom,
+                    height,
+                )
+                .await?;
+            info!(target: SETTLEMENT_PHASE, "settlement {} balance = {balance}", settlement_denom.denom);
+            settlement_balances.insert(settlement_denom.denom.to_string(), balance);
+        }
+
+        // query the Clearing Queue pending obligations
+        let ObligationsResponse { obligations } = neutron
+            .query_contract_state(
+                &self.layout.neutron.clearing_queue,
+                valence_clearing_queue_supervaults::msg::QueryMsg::PendingObligations {
+                    from: None,
+                    to: None,
+                },
+                height,
+            )
+            .await?;
+
+        self.metrics.set_gauge(
+            metrics::CLEARING_QUEUE_LENGTH,
+            &[],
+            obligations.len() as f64,
+        );
+
+        // early return if there is nothing to settle
+        if obligations.is_empty() {
+            info!(target: SETTLEMENT_PHASE, "no obligations to settle; concluding settlement phase");
+            return Ok(());
+        }
+
+        for o in &obligations {
+            info!(target: SETTLEMENT_PHASE, "obligation #{} payouts: {:?}", o.id, o.payout_coins);
+        }
+
+        // batch all active clearing queue obligations
+        let payouts = batch_obligation_queue_payouts(&obligations);
+        for payout in &payouts {
+            info!(target: SETTLEMENT_PHASE, "batched obligation = {payout}");
+        }
+
+        // plan the liquidity needed to cover all of the payouts, refusing to
+        // touch any of the positions if the queue cannot be covered
+        let sources = self
+            .liquidity_sources(&payouts, settlement_balances, height)
+            .await?;
+        let plan = plan_liquidity(&payouts, &self.layout.settlement.denoms, &sources).map_err(
+            |shortfalls| {
+                let shortfalls: Vec<String> = shortfalls
+                    .iter()
+                    .map(|s| format!("{}{}", s.amount, s.denom))
+                    .collect();
+                StrategistError::InsufficientBalance(format!(
+                    "settlement account cannot cover the clearing queue, short of {}",
+                    shortfalls.join(", ")
+                ))
+            },
+        )?;
+
+        for action in plan {
+            self.pull_liquidity(action).await?;
+        }
+
+        // process the Clearing Queue settlement requests by enqueuing the settlement
+        // messages to the processor and ticking
+        self.clear_withdraw_obligations(obligations).await?;
+
+        Ok(())
+    }
+
+    /// settlement account balances along with the Mars and supervault
+    /// liquidity that can cover the payouts it falls short of. the
+    /// positions are only queried for the denoms that are short.
+    async fn liquidity_sources(
+        &self,
+        payouts: &[Coin],
+        balances: HashMap<String, u128>,
+        height: Option<u64>,
+    ) -> anyhow::Result<LiquiditySources> {
+        let neutron = self.querier(Domain::Neutron)?;
+        let settlement_account = &self.layout.neutron.settlement_account;
+        let mut sources = LiquiditySources {
+            balances,
+            ..Default::default()
+        };
+
+        for payout in payouts {
+            let available = match sources.balances.get(&payout.denom) {
+                Some(balance) => *balance,
+                None => {
+                    let balance = neutron
+                        .query_balance(settlement_account, &payout.denom, height)
+                        .await?;
+                    sources.balances.insert(payout.denom.to_string(), balance);
+                    balance
+                }
+            };
+            if available >= payout.amount.u128() {
+                continue;
+            }
+
+            let Some(settlement_denom) = self
+                .layout
+                .settlement
+                .denoms
+                .iter()
+                .find(|d| d.denom == payout.denom)
+            else {
+                warn!(target: SETTLEMENT_PHASE, "unexpected coin among obligations: {payout}");
+                continue;
+            };
+            info!(
+                target: SETTLEMENT_PHASE,
+                "settlement_account {} balance deficit = {}",
+                payout.denom,
+                payout.amount.u128() - available
+            );
+
+            match &settlement_denom.source {
+                SettlementSource::Held => {
+                    warn!(target: SETTLEMENT_PHASE, "insufficient {} balance!", payout.denom);
+                    warn!(target: SETTLEMENT_PHASE, "available: {available}, obligation: {payout}");
+                }
+                SettlementSource::MarsWithdraw => {
+                    let (credit_manager, owner) = self
+                        .layout
+                        .update
+                        .positions
+                        .iter()
+                        .find_map(|position| match position {
+                            Position::MarsLend {
+                                credit_manager,
+                                owner,
+                            } => Some((credit_manager, owner)),
+                            _ => None,
+                        })
+                        .ok_or_else(|| {
+                            anyhow!("no Mars lending position to withdraw {} from", payout.denom)
+                        })?;
+                    let position =
+                        query_mars_position(neutron, credit_manager, owner, &payout.denom, height)
+                            .await?;
+                    // the mars lending library opens a single credit account
+                    // and withdraws from it, so the lend of any other account
+                    // could never reach the settlement account
+                    if position.accounts > 1 {
+                        return Err(StrategistError::InvariantViolated(format!(
+                            "{owner} holds {} mars credit accounts, but the lending library withdraws from a single one",
+                            position.accounts
+                        ))
+                        .into());
+                    }
+                    let lent = position.lends;
+                    info!(target: SETTLEMENT_PHASE, "mars {} lend = {lent}", payout.denom);
+
+                    // the lend can only be withdrawn as far as the red bank
+                    // market has not borrowed it out
+                    let red_bank = query_mars_red_bank(neutron, credit_manager, height).await?;
+                    let market =
+                        query_red_bank_market(neutron, &red_bank, &payout.denom, height).await?;
+                    let liquidity = market.available_liquidity();
+                    info!(
+                        target: SETTLEMENT_PHASE,
+                        "mars {} market: liquidity = {liquidity}, utilization = {}",
+                        payout.denom,
+                        market.utilization_rate
+                    );
+
+                    let deficit = payout.amount.u128() - available;
+                    if liquidity < lent && liquidity < deficit {
+                        warn!(
+                            target: SETTLEMENT_PHASE,
+                            "mars {} market liquidity of {liquidity} cannot cover the deficit of {deficit}",
+                            payout.denom
+                        );
+                        self.metrics.inc_counter(
+                            metrics::MARS_LIQUIDITY_SHORTFALLS,
+                            &[("denom", &payout.denom)],
+                            1.0,
+                        );
+                    }
+                    sources
+                        .mars
+                        .insert(payout.denom.to_string(), lent.min(liquidity));
+                }
+            }
+        }
+
+        Ok(sources)
+    }
+
+    /// executes the planned action, pulling the liquidity into the
+    /// settlement account. obligations cannot be settled without it, so a
+    /// failed action fails the phase.
+    async fn pull_liquidity(&self, action: LiquidityAction) -> anyhow::Result<()> {
+        match action {
+            LiquidityAction::MarsWithdraw { denom, amount } => {
+                info!(target: SETTLEMENT_PHASE, "withdrawing {amount}{denom} from mars lending position");
+
+                // call the Mars lending library to perform the withdrawal.
+                // This will deposit the underlying assets directly to the settlement account.
+                let mars_withdraw_msg =
+                    valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+                        valence_mars_lending::msg::FunctionMsgs::Withdraw {
+                            amount: Some(amount.into()),
+                        },
+                    );
+
+                self.run_neutron_subroutine(
+                    SETTLEMENT_PHASE,
+                    MARS_WITHDRAW_LABEL,
+                    vec![to_json_binary(&mars_withdraw_msg)?],
+                )
+                .await?
+                .ensure_succeeded(MARS_WITHDRAW_LABEL)
+            }
+        }
+    }
+
+    /// settles the `PendingObligation`s in FIFO order, packing as many of
+    /// them into every processor execution as the layout and the settlement
+    /// authorization allow for. the settlement account balance is re-checked
+    /// ahead of every batch, so that obligations it cannot cover are not
+    /// sent only to fail in the processor.
+    async fn clear_withdraw_obligations(
+        &mut self,
+        obligations: Vec<PendingObligation>,
+    ) -> anyhow::Result<()> {
+        let settlement_exec_msg = to_json_binary(
+            &valence_library_utils::msg::ExecuteMsg::<_, ()>::ProcessFunction(
+                valence_clearing_queue_supervaults::msg::FunctionMsgs::SettleNextObligation {},
+            ),
+        )?;
+
+        let batch_size = self.settlement_batch_size().await?;
+        let mut cleared = vec![];
+
+        for (i, batch) in obligations.chunks(batch_size).enumerate() {
+            let ids: Vec<u64> = batch.iter().map(|o| o.id.u64()).collect();
+            info!(target: SETTLEMENT_PHASE, "settling obligations {ids:?} in batch #{i}");
+
+            // the queue is cleared in order, so the settlement stops at the
+            // first obligation that the account cannot cover
+            let covered = self.covered_obligations(batch).await?;
+
+            // an atomic subroutine only takes exactly as many messages as it
+            // holds functions, so only full batches go through the batch
+            // authorization. the obligations of a partial batch are settled
+            // one per execution.
+            let executions = if batch_size > 1 && covered == batch_size {
+                vec![(SETTLE_OBLIGATION_BATCH_LABEL, covered)]
+            } else {
+                vec![(SETTLE_OBLIGATION_LABEL, 1); covered]
+            };
+
+            for (label, count) in executions {
+                // enqueue one settlement message per obligation and tick the
+                // processor through. the rest of the queue waits for the
+                // next cycle if the execution fails to settle
+                self.run_neutron_subroutine(
+                    SETTLEMENT_PHASE,
+                    label,
+                    vec![settlement_exec_msg.clone(); count],
+                )
+                .await?
+                .ensure_succeeded(label)?;
+
+                info!(target: SETTLEMENT_PHASE, "cleared {count} obligations of batch #{i} with {label}");
+                self.metrics
+                    .inc_counter(metrics::OBLIGATIONS_SETTLED, &[], count as f64);
+                self.metrics
+                    .inc_counter(metrics::SETTLEMENT_BATCHES, &[], 1.0);
+                cleared.push(count);
+            }
+
+            if covered < batch.len() {
+                let obligation = &batch[covered];
+                return Err(StrategistError::InsufficientBalance(format!(
+                    "settlement account cannot cover obligation #{} after clearing {} obligations in {} executions",
+                    obligation.id,
+                    cleared.iter().sum::<usize>(),
+                    cleared.len()
+                ))
+                .into());
+            }
+        }
+
+        info!(
+            target: SETTLEMENT_PHASE,
+            "cleared {} obligations in {} executions: {cleared:?}",
+            cleared.iter().sum::<usize>(),
+            cleared.len()
+        );
+
+        Ok(())
+    }
+
+    /// number of obligations settled per batch execution. every obligation
+    /// takes a `settle_next_obligation` function of its own in the batch
+    /// authorization subroutine, which has to be sent exactly as many
+    /// messages as it holds functions. without a batch authorization that
+    /// fits the layout, obligations are settled one per execution, which
+    /// is warned about once per process.
+    async fn settlement_batch_size(&self) -> anyhow::Result<usize> {
+        let max_batch_size = self.layout.settlement.max_obligations_per_batch.max(1);
+        let batch_size = if max_batch_size == 1 {
+            1
+        } else {
+            match valence_core::query_subroutine_function_count(
+                self.neutron_client.as_ref(),
+                &self.layout.neutron.authorizations,
+                SETTLE_OBLIGATION_BATCH_LABEL,
+            )
+            .await
+            {
+                Ok(functions) if functions > max_batch_size => {
+                    self.warn_batch_fallback(&format!(
+                        "{SETTLE_OBLIGATION_BATCH_LABEL} authorization holds {functions} functions, more than the {max_batch_size} obligations allowed per batch"
+                    ));
+                    1
+                }
+                Ok(functions) => functions.max(1),
+                Err(e) => {
+                    self.warn_batch_fallback(&format!(
+                        "no usable {SETTLE_OBLIGATION_BATCH_LABEL} authorization ({e}); deployments from before batched settlement need it added, see the getting started guide"
+                    ));
+                    1
+                }
+            }
+        };
+
+        self.metrics
+            .set_gauge(metrics::SETTLEMENT_BATCH_SIZE, &[], batch_size as f64);
+
+        Ok(batch_size)
+    }
+
+    /// warns about settling one obligation per execution the first time,
+    /// and only logs it afterwards
+    fn warn_batch_fallback(&self, reason: &str) {
+        if self.batch_fallback_warned.swap(true, Ordering::Relaxed) {
+            info!(target: SETTLEMENT_PHASE, "{reason}; settling one obligation per execution");
+        } else {
+            warn!(target: SETTLEMENT_PHASE, "{reason}; settling one obligation per execution");
+        }
+    }
+
+    /// number of obligations at the front of the batch that the settlement
+    /// account balance covers. the balance is re-read ahead of every batch.
+    /// dry runs count every obligation as covered, as the liquidity they
+    /// would have withdrawn never reaches the account.
+    async fn covered_obligations(&self, batch: &[PendingObligation]) -> anyhow::Result<usize> {
+        if self.dry_run.is_some() {
+            return Ok(batch.len());
+        }
+
+        let neutron = self.querier(Domain::Neutron)?;
+        let mut balances: HashMap<String, u128> = HashMap::new();
+        for payout in batch_obligation_queue_payouts(batch) {
+            let balance = neutron
+                .query_balance(&self.layout.neutron.settlement_account, &payout.denom, None)
+                .await?;
+            balances.insert(payout.denom, balance);
+        }
+
+        let mut covered = 0;
+        for obligation in batch {
+            for payout in &obligation.payout_coins {
+                let balance = balances.entry(payout.denom.clone()).or_default();
+                match balance.checked_sub(payout.amount.u128()) {
+                    Some(remaining) => *balance = remaining,
+                    None => {
+                        warn!(
+                            target: SETTLEMENT_PHASE,
+                            "settlement account holds {balance}{}, short of the {payout} owed to obligation #{}",
+                            payout.denom,
+                            obligation.id
+                        );
+                        return Ok(covered);
+                    }
+                }
+            }
+            covered += 1;
+        }
+
+        Ok(covered)
+    }
+}
@@ -0,0 +1,529 @@
+use alloy::primitives::U256;
+use cosmwasm_std::{Decimal, Uint128};
+use log::{error, info, warn};
+
+use crate::{
+    phases::UPDATE_PHASE,
+    strategist::{
+        clients::call_request,
+        error::StrategistError,
+        layout::{Domain, Position, TrackedBalance},
+        plan::PlannedAction,
+        rate_guard::RateAnomalyAction,
+        transit::InTransitValuator,
+        Strategy,
+    },
+    types::sol_types::OneWayVault,
+    utils::{
+        eth_tx::EthTxError,
+        mars::{query_mars_red_bank, query_red_bank_market, MarsLendingValuator},
+        maxbtc::MaxBtcIssuanceValuator,
+        metrics,
+        snapshot::Snapshot,
+        supervaults::SupervaultValuator,
+        valence_core,
+        valuation::{
+            value_positions, BalanceValuator, Erc20BalanceValuator, PositionValuator, TvlBreakdown,
+        },
+    },
+};
+
+/// journal label of the ethereum tx that posts the new redemption rate
+pub(crate) const UPDATE_RATE_TX: &str = "update_rate";
+/// journal label of the ethereum tx that pauses the vault
+const PAUSE_VAULT_TX: &str = "pause_vault";
+
+/// redemption rate along with the inputs it was derived from
+struct ComputedRate {
+    tvl: TvlBreakdown,
+    total_assets: u128,
+    total_shares: u128,
+    rate: Decimal,
+}
+
+impl Strategy {
+    /// performs the vault rate update. this phase involves the following stages:
+    /// 1. valuing every registered position in the rate denom, along with
+    ///    the transfers in transit, producing a per-position TVL breakdown
+    /// 2. querying the shares issued by the vault on Ethereum. all of the
+    ///    queries run concurrently, pinned to a snapshot of every domain
+    ///    height taken up front
+    /// 3. calculating the new redemption rate by dividing the total assets
+    ///    by the total shares
+    /// 4. checking the new redemption rate against the history of posted
+    ///    rates, acting on anomalies as configured in the rate guard
+    /// 5. validating the new redemption rate against the max rate update
+    ///    bounds (pausing the vault if they are exceeded)
+    /// 6. posting the updated rate to the Ethereum vault
+    ///
+    /// returns the TVL breakdown that the rate was derived from.
+    pub async fn update(&mut self) -> anyhow::Result<TvlBreakdown> {
+        info!(target: UPDATE_PHASE, "starting vault update phase");
+
+        // a rate posted by an earlier update that did not get confirmed in
+        // time is looked up before another one gets posted
+        self.reconcile_phase(UPDATE_PHASE).await?;
+
+        // export the currently posted rate state before anything else so that
+        // staleness can be alerted on even if this update fails
+        self.observe_vault_rate_state().await;
+
+        let guard = &self.layout.update.rate_guard;
+        let mut retries = 0;
+
+        let computed = loop {
+            let computed = self.compute_redemption_rate().await?;
+
+            let history = self.rate_history.samples()?;
+            let check = guard.check(&history, computed.rate)?;
+            info!(target: UPDATE_PHASE, "rate history check: {}", serde_json::to_string(&check)?);
+
+            if !check.tripped() {
+                break computed;
+            }
+
+            match guard.action {
+                RateAnomalyAction::Retry { attempts } if retries < attempts => {
+                    retries += 1;
+                    warn!(
+                        target: UPDATE_PHASE,
+                        "rate anomaly {:?}; recalculating from fresh queries (retry {retries}/{attempts})",
+                        check.violations
+                    );
+                }
+                // the posted rate goes stale if this keeps up, so the skip
+                // fails the phase rather than passing for a posted update
+                RateAnomalyAction::Retry { .. } | RateAnomalyAction::Skip => {
+                    error!(
+                        target: UPDATE_PHASE,
+                        "rate anomaly {:?}; skipping the update (action: {:?})",
+                        check.violations, guard.action
+                    );
+                    self.metrics
+                        .inc_counter(metrics::RATE_UPDATES_SKIPPED, &[], 1.0);
+
+                    return Err(StrategistError::RateAnomaly(format!(
+                        "newly calculated rate {} tripped the rate history checks: {:?}",
+                        computed.rate, check.violations
+                    ))
+                    .into());
+                }
+                RateAnomalyAction::Pause => {
+                    warn!(
+                        target: UPDATE_PHASE,
+                        "rate anomaly {:?}; pausing the vault",
+                        check.violations
+                    );
+                    let pause_request = call_request(
+                        self.layout.ethereum.one_way_vault,
+                        &OneWayVault::pauseCall {},
+                    );
+                    self.execute_eth_tx(UPDATE_PHASE, PAUSE_VAULT_TX, pause_request)
+                        .await?;
+
+                    return Err(StrategistError::InvariantViolated(format!(
+                        "newly calculated rate tripped the rate history checks: {:?}",
+                        check.violations
+                    ))
+                    .into());
+                }
+            }
+        };
+
+        let redemption_rate_sol_u256 = U256::try_from(computed.rate.atomics().u128())?;
+        info!(target: UPDATE_PHASE, "redemption_rate_sol_u256={redemption_rate_sol_u256}");
+
+        // in dry-run mode the rate bounds are only checked (without pausing
+        // the vault) and the rate gets added to the plan
+        if let Some(dry_run) = &self.dry_run {
+            let within_bounds = valence_core::redemption_rate_within_bounds(
+                self.layout.ethereum.one_way_vault,
+                self.eth_client.as_ref(),
+                redemption_rate_sol_u256,
+                self.layout.update.max_rate_decrement_bps,
+                self.layout.update.max_rate_increment_bps,
+            )
+            .await?;
+            dry_run.record(
+                UPDATE_PHASE,
+                PlannedAction::RateUpdate {
+                    tvl: computed.tvl.clone(),
+                    total_assets: computed.total_assets,
+                    total_shares: computed.total_shares,
+                    redemption_rate: computed.rate.to_string(),
+                    within_bounds,
+                },
+            );
+        }
+
+        self.post_redemption_rate(redemption_rate_sol_u256).await?;
+
+        if self.dry_run.is_none() {
+            self.rate_history.record(computed.rate)?;
+        }
+
+        self.observe_vault_rate_state().await;
+        self.observe_mars_markets().await;
+        // proposals are advisory, so they should never fail the phase
+        if let Err(e) = self.propose_rebalance().await {
+            warn!(target: UPDATE_PHASE, "failed to propose a rebalance: {e}");
+        }
+
+        Ok(computed.tvl)
+    }
+
+    /// values all positions and derives the redemption rate from them. every
+    /// query is pinned to a snapshot of the latest heights taken up front, so
+    /// that the rate is derived from a single, consistent state.
+    async fn compute_redemption_rate(&self) -> anyhow::Result<ComputedRate> {
+        let snapshot = self.snapshot().await?;
+        info!(target: UPDATE_PHASE, "querying at snapshot {}", serde_json::to_string(&snapshot)?);
+        self.metrics.set_gauge(
+            metrics::VALUATION_HEIGHT,
+            &[("domain", "ethereum")],
+            snapshot.ethereum_block as f64,
+        );
+        for domain in [
+            Domain::Neutron,
+            Domain::Gaia,
+            Domain::Lombard,
+            Domain::Noble,
+        ] {
+            if let Some(height) = snapshot.height(domain) {
+                self.metrics.set_gauge(
+                    metrics::VALUATION_HEIGHT,
+                    &[("domain", &domain.to_string())],
+                    height as f64,
+                );
+            }
+        }
+
+        // transfers that arrived as of the snapshot are counted at their
+        // destination, so they must no longer be counted in transit
+        self.settle_in_transit(UPDATE_PHASE, Some(&snapshot))
+            .await?;
+
+        // in order to calculate the vault rate we need to find the total amount of assets
+        // distributed across the program. we value every registered position in the rate
+        // denom and sum them up, while fetching the total issued shares alongside
+        let (mut tvl, total_shares) = tokio::try_join!(
+            self.tvl_breakdown(&snapshot),
+            self.total_issued_shares(&snapshot),
+        )?;
+        tvl.snapshot = Some(snapshot);
+
+        let total_assets = tvl.total()?;
+        info!(target: UPDATE_PHASE, "tvl breakdown: {}", serde_json::to_string(&tvl)?);
+        info!(target: UPDATE_PHASE, "total assets: {total_assets}");
+        for position in &tvl.positions {
+            self.metrics.set_gauge(
+                metrics::POSITION_BALANCE,
+                &[("position", &position.name)],
+                position.value as f64,
+            );
+        }
+        info!(target: UPDATE_PHASE, "eth_vault_issued_shares_u128={total_shares}");
+
+        // rate =  effective_total_assets / (effective_vault_shares * scaling_factor)
+        // multiplying the denominator by the scaling factor
+        let scaled_shares_amount =
+            Uint128::from(total_shares).checked_mul(self.layout.update.rate_scaling_factor)?;
+        let rate = Decimal::checked_from_ratio(total_assets, scaled_shares_amount)?;
+        info!(
+            target: UPDATE_PHASE,
+            "redemption rate decimal={rate} (neutron height {}, ethereum block {})",
+            snapshot.neutron_height,
+            snapshot.ethereum_block
+        );
+        self.metrics
+            .set_gauge(metrics::COMPUTED_RATE, &[], rate.to_string().parse()?);
+
+        Ok(ComputedRate {
+            tvl,
+            total_assets,
+            total_shares,
+            rate,
+        })
+    }
+
+    /// the single path through which a redemption rate gets posted to the
+    /// vault. the rate is always validated against the max rate update
+    /// bounds relative to the current rate first, and the vault gets paused
+    /// if they are exceeded.
+    async fn post_redemption_rate(&self, redemption_rate: U256) -> anyhow::Result<()> {
+        // validation pauses the vault, so it is left out of dry runs. the
+        // bounds check is part of the planned rate update instead.
+        if self.dry_run.is_none() {
+            let within_bounds = valence_core::redemption_rate_within_bounds(
+                self.layout.ethereum.one_way_vault,
+                self.eth_client.as_ref(),
+                redemption_rate,
+                self.layout.update.max_rate_decrement_bps,
+                self.layout.update.max_rate_increment_bps,
+            )
+            .await?;
+
+            if !within_bounds {
+                warn!(target: UPDATE_PHASE, "pausing the vault");
+                let pause_request = call_request(
+                    self.layout.ethereum.one_way_vault,
+                    &OneWayVault::pauseCall {},
+                );
+                self.execute_eth_tx(UPDATE_PHASE, PAUSE_VAULT_TX, pause_request)
+                    .await?;
+
+                return Err(StrategistError::InvariantViolated(
+                    "newly calculated rate exceeds the rate update thresholds".to_string(),
+                )
+                .into());
+            }
+        }
+
+        info!(target: UPDATE_PHASE, "updating ethereum vault redemption rate");
+        let update_request = call_request(
+            self.layout.ethereum.one_way_vault,
+            &OneWayVault::updateCall {
+                newRate: redemption_rate,
+            },
+        );
+
+        if let Err(e) = self
+            .execute_eth_tx(UPDATE_PHASE, UPDATE_RATE_TX, update_request)
+            .await
+        {
+            // retrying would post a second rate behind the one that may
+            // still land. the journal reconciles it ahead of the next update.
+            if let Some(unconfirmed @ EthTxError::Unconfirmed { .. }) =
+                e.downcast_ref::<EthTxError>()
+            {
+                let pending = StrategistError::TxPending(unconfirmed.to_string());
+                return Err(e.context(pending));
+            }
+
+            // a paused vault rejects every rate update, so there is no point
+            // in retrying until it gets unpaused
+            let paused = self
+                .eth_client
+                .query(
+                    self.layout.ethereum.one_way_vault,
+                    OneWayVault::vaultStateCall {},
+                    None,
+                )
+                .await
+                .is_ok_and(|state| state.paused);
+            if paused {
+                return Err(e.context(StrategistError::VaultPaused));
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// exports the posted redemption rate along with the timestamp of its
+    /// last update and the max allowed delay between the updates. failures
+    /// are logged, as metrics should never fail the phase.
+    async fn observe_vault_rate_state(&self) {
+        let vault = self.layout.ethereum.one_way_vault;
+
+        let observation: anyhow::Result<()> = async {
+            let posted_rate = self
+                .eth_client
+                .query(vault, OneWayVault::redemptionRateCall {}, None)
+                .await?
+                ._0;
+            let last_update = self
+                .eth_client
+                .query(vault, OneWayVault::lastRateUpdateTimestampCall {}, None)
+                .await?
+                ._0;
+            let config = self
+                .eth_client
+                .query(vault, OneWayVault::configCall {}, None)
+                .await?;
+
+            // posted rate is expressed in decimal atomics
+            let posted_rate_decimal =
+                Decimal::from_atomics(Uint128::new(u128::try_from(posted_rate)?), 18)?;
+
+            self.metrics.set_gauge(
+                metrics::POSTED_RATE,
+                &[],
+                posted_rate_decimal.to_string().parse()?,
+            );
+            self.metrics
+                .set_gauge(metrics::LAST_RATE_UPDATE, &[], last_update as f64);
+            self.metrics.set_gauge(
+                metrics::MAX_RATE_UPDATE_DELAY,
+                &[],
+                config.maxRateUpdateDelay as f64,
+            );
+
+            Ok(())
+        }
+        .await;
+
+        if let Err(e) = observation {
+            warn!(target: UPDATE_PHASE, "failed to observe the vault rate state: {e}");
+        }
+    }
+
+    /// exports the lend APY of the Mars markets that the strategy lends
+    /// into. failures are logged, as metrics should never fail the phase.
+    async fn observe_mars_markets(&self) {
+        let denom = &self.layout.neutron.deposit_denom;
+
+        for position in &self.layout.update.positions {
+            let Position::MarsLend { credit_manager, .. } = position else {
+                continue;
+            };
+
+            let observation: anyhow::Result<()> = async {
+                let neutron = self.querier(Domain::Neutron)?;
+                let red_bank = query_mars_red_bank(neutron, credit_manager, None).await?;
+                let market = query_red_bank_market(neutron, &red_bank, denom, None).await?;
+                let apy = market.lend_apy();
+                info!(
+                    target: UPDATE_PHASE,
+                    "mars {denom} market: lend_rate={}, lend_apy={apy}, utilization={}",
+                    market.liquidity_rate, market.utilization_rate
+                );
+
+                self.metrics
+                    .set_gauge(metrics::MARS_LEND_APY, &[("denom", denom)], apy);
+
+                Ok(())
+            }
+            .await;
+
+            if let Err(e) = observation {
+                warn!(target: UPDATE_PHASE, "failed to observe the mars {denom} market: {e}");
+            }
+        }
+    }
+
+    /// total shares issued by the vault as of the snapshot ethereum block
+    async fn total_issued_shares(&self, snapshot: &Snapshot) -> anyhow::Result<u128> {
+        let eth_vault_issued_shares_u256 = valence_core::query_vault_total_supply(
+            self.layout.ethereum.one_way_vault,
+            self.eth_client.as_ref(),
+            Some(snapshot.ethereum_block),
+        )
+        .await?;
+
+        // if there are no shares issued, update cannot be performed because it's impossible to
+        // calculate the redemption rate
+        if eth_vault_issued_shares_u256.is_zero() {
+            return Err(StrategistError::InvariantViolated(
+                "cannot calculate redemption rate with zero issued vault shares".to_string(),
+            )
+            .into());
+        }
+
+        info!(target: UPDATE_PHASE, "eth_vault_issued_shares_u256={eth_vault_issued_shares_u256}");
+
+        // perform u256 -> u128 conversion
+        let eth_vault_issued_shares_u128 = u128::try_from(eth_vault_issued_shares_u256)?;
+
+        Ok(eth_vault_issued_shares_u128)
+    }
+
+    /// builds the valuators for every position registered in the update layout
+    /// and values them concurrently, producing a per-position TVL breakdown
+    /// expressed in the rate denom as of the snapshot. funds that left one
+    /// position but are yet to arrive to another are counted as a separate
+    /// "in transit" line item.
+    async fn tvl_breakdown(&self, snapshot: &Snapshot) -> anyhow::Result<TvlBreakdown> {
+        let mut valuators = self
+            .layout
+            .update
+            .positions
+            .iter()
+            .map(|position| self.position_valuator(position, snapshot))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let in_transit = self.transit.outstanding()?;
+        for t in &in_transit {
+            info!(
+                target: UPDATE_PHASE,
+                "in transit: {} transfer #{} of {} (sent {}) to {} {} {}",
+                t.transfer.transfer,
+                t.id,
+                t.transfer.expected_out,
+                t.transfer.sent,
+                t.transfer.domain,
+                t.transfer.address,
+                t.transfer.denom
+            );
+        }
+        valuators.push(Box::new(InTransitValuator::new(in_transit)));
+
+        value_positions(&valuators).await
+    }
+
+    fn position_valuator<'a>(
+        &'a self,
+        position: &'a Position,
+        snapshot: &Snapshot,
+    ) -> anyhow::Result<Box<dyn PositionValuator + 'a>> {
+        let neutron = self.querier(Domain::Neutron)?;
+        let valuator: Box<dyn PositionValuator + 'a> = match position {
+            Position::EthereumDeposit => Box::new(Erc20BalanceValuator::new(
+                self.eth_client.as_ref(),
+                self.layout.ethereum.deposit_token,
+                self.layout.ethereum.deposit_account,
+                Some(snapshot.ethereum_block),
+            )),
+            Position::Balance(TrackedBalance {
+                domain,
+                address,
+                denom,
+            }) => Box::new(BalanceValuator::new(
+                self.querier(*domain)?,
+                snapshot.height(*domain),
+                &domain.to_string(),
+                address,
+                denom,
+            )),
+            Position::MarsLend {
+                credit_manager,
+                owner,
+            } => Box::new(MarsLendingValuator::new(
+                neutron,
+                Some(snapshot.neutron_height),
+                credit_manager,
+                owner,
+                &self.layout.neutron.deposit_denom,
+                self.layout.update.exclude_foreign_mars_debts,
+                &self.metrics,
+            )),
+            Position::Supervault {
+                vault,
+                depositor,
+                lp_holder,
+            } => Box::new(SupervaultValuator::new(
+                neutron,
+                Some(snapshot.neutron_height),
+                vault,
+                depositor,
+                lp_holder,
+                &self.layout.neutron.deposit_denom,
+            )),
+            Position::MaxBtcIssuance { contract, sources } => {
+                let sources = sources
+                    .iter()
+                    .map(|source| self.position_valuator(source, snapshot))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                Box::new(MaxBtcIssuanceValuator::new(
+                    neutron,
+                    Some(snapshot.neutron_height),
+                    contract,
+                    sources,
+                ))
+            }
+        };
+
+        Ok(valuator)
+    }
+}
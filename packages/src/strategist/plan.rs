@@ -0,0 +1,176 @@
+use std::sync::Mutex;
+
+use alloy::rpc::types::TransactionRequest;
+use cosmwasm_std::Binary;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    strategist::{cycle::CycleSummary, layout::Domain},
+    utils::valuation::TvlBreakdown,
+};
+
+/// an action that the strategist would have broadcast, had it not been
+/// running in dry-run mode
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlannedAction {
+    /// transaction signed and sent on Ethereum
+    EthereumTx {
+        label: String,
+        request: TransactionRequest,
+    },
+    /// coprocessor proof request whose zk message would be submitted
+    /// to the authorizations contract on the given chain
+    ZkAuthorization {
+        chain: String,
+        label: String,
+        coprocessor_app_id: String,
+        input: Value,
+    },
+    /// `SendMsgs` batch pushed to the Neutron processor
+    NeutronSendMsgs { label: String, messages: Vec<Value> },
+    /// processor tick on Neutron
+    NeutronTick { processor: String },
+    /// untrn top-up of a Neutron account to cover its ibc fees
+    NeutronFeeCoverage { account: String },
+    /// wait for funds to arrive to an account
+    AwaitArrival {
+        domain: Domain,
+        address: String,
+        denom: String,
+        expected: u128,
+    },
+    /// redemption rate that would be posted to the vault
+    RateUpdate {
+        tvl: TvlBreakdown,
+        total_assets: u128,
+        total_shares: u128,
+        redemption_rate: String,
+        within_bounds: bool,
+    },
+}
+
+impl PlannedAction {
+    /// decodes the processor messages into json for readability
+    pub fn neutron_send_msgs(label: &str, messages: &[Binary]) -> Self {
+        let messages = messages
+            .iter()
+            .map(|msg| {
+                serde_json::from_slice(msg.as_slice())
+                    .unwrap_or_else(|_| Value::String(msg.to_base64()))
+            })
+            .collect();
+
+        PlannedAction::NeutronSendMsgs {
+            label: label.to_string(),
+            messages,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedStep {
+    pub phase: String,
+    #[serde(flatten)]
+    pub action: PlannedAction,
+}
+
+/// structured output of a dry-run cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct CyclePlan {
+    pub strategy: String,
+    pub steps: Vec<PlannedStep>,
+    /// how every phase went, including the ones that were skipped
+    pub summary: CycleSummary,
+}
+
+/// recorder of the planned actions. present on the strategy only when
+/// running in dry-run mode.
+#[derive(Debug, Default)]
+pub struct DryRun {
+    steps: Mutex<Vec<PlannedStep>>,
+}
+
+impl DryRun {
+    pub fn record(&self, phase: &str, action: PlannedAction) {
+        if let Ok(mut steps) = self.steps.lock() {
+            steps.push(PlannedStep {
+                phase: phase.to_string(),
+                action,
+            });
+        }
+    }
+
+    /// drains the recorded steps into a plan
+    pub fn take_plan(&self, strategy: &str) -> CyclePlan {
+        let steps = self
+            .steps
+            .lock()
+            .map(|mut steps| std::mem::take(&mut *steps))
+            .unwrap_or_default();
+
+        CyclePlan {
+            strategy: strategy.to_string(),
+            steps,
+            summary: CycleSummary::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_send_msgs_are_decoded_for_readability() {
+        let messages = [
+            Binary::from(br#"{"process_function":{"lend":{}}}"#.to_vec()),
+            Binary::from(vec![0xde, 0xad]),
+        ];
+
+        let PlannedAction::NeutronSendMsgs { label, messages } =
+            PlannedAction::neutron_send_msgs("lend", &messages)
+        else {
+            panic!("expected a send msgs action");
+        };
+
+        assert_eq!(label, "lend");
+        // messages that are not json are kept as base64
+        assert_eq!(
+            messages,
+            vec![json!({"process_function": {"lend": {}}}), json!("3q0=")]
+        );
+    }
+
+    #[test]
+    fn test_take_plan_drains_the_recorded_steps_in_order() {
+        let dry_run = DryRun::default();
+        dry_run.record(
+            "deposit",
+            PlannedAction::NeutronTick {
+                processor: "processor".to_string(),
+            },
+        );
+        dry_run.record(
+            "settlement",
+            PlannedAction::NeutronFeeCoverage {
+                account: "settlement".to_string(),
+            },
+        );
+
+        let plan = dry_run.take_plan("usdc");
+        assert_eq!(plan.strategy, "usdc");
+        assert_eq!(
+            serde_json::to_value(&plan.steps).unwrap(),
+            json!([
+                {"phase": "deposit", "action": "neutron_tick", "processor": "processor"},
+                {"phase": "settlement", "action": "neutron_fee_coverage", "account": "settlement"},
+            ])
+        );
+
+        assert!(dry_run.take_plan("usdc").steps.is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+use cosmwasm_std::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// what the update phase does when the newly calculated rate trips one of
+/// the rate history checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateAnomalyAction {
+    /// do not post the rate this cycle
+    Skip,
+    /// recalculate the rate from fresh queries up to the given number of
+    /// times, skipping the update if it keeps tripping the checks
+    Retry { attempts: u32 },
+    /// pause the vault
+    Pause,
+}
+
+/// multi-epoch checks of the newly calculated rate against the history of
+/// the previously posted rates. complements the single-epoch max rate
+/// update bounds, which a slow drift or a single bad valuation just under
+/// the threshold would pass. fields missing from the strategy config take
+/// their default values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateGuard {
+    /// number of most recent posted rates the checks look at
+    pub window: usize,
+    /// minimum number of posted rates needed before the checks kick in
+    pub min_samples: usize,
+    /// max cumulative change of the rate relative to the oldest rate in
+    /// the window
+    pub max_cumulative_drift_bps: u64,
+    /// max z-score of the newly calculated rate change relative to the
+    /// rate changes within the window
+    pub max_z_score: f64,
+    pub action: RateAnomalyAction,
+}
+
+impl Default for RateGuard {
+    fn default() -> Self {
+        Self {
+            window: 30,
+            min_samples: 5,
+            max_cumulative_drift_bps: 500,
+            max_z_score: 4.0,
+            action: RateAnomalyAction::Retry { attempts: 2 },
+        }
+    }
+}
+
+/// outcome of the rate history checks, along with all of their inputs
+#[derive(Debug, Clone, Serialize)]
+pub struct RateCheck {
+    pub new_rate: Decimal,
+    /// number of posted rates that the checks were performed against
+    pub samples: usize,
+    /// oldest posted rate in the window
+    pub reference_rate: Option<Decimal>,
+    /// cumulative change relative to the reference rate, in bps
+    pub cumulative_drift_bps: Option<f64>,
+    /// z-score of the new rate change relative to the window changes
+    pub z_score: Option<f64>,
+    /// reasons the checks tripped for. empty if the rate passed.
+    pub violations: Vec<String>,
+}
+
+impl RateCheck {
+    pub fn tripped(&self) -> bool {
+        !self.violations.is_empty()
+    }
+}
+
+impl RateGuard {
+    /// checks the new rate against the (chronologically ordered) history
+    /// of posted rates
+    pub fn check(&self, history: &[RateSample], new_rate: Decimal) -> anyhow::Result<RateCheck> {
+        let window = &history[history.len().saturating_sub(self.window)..];
+
+        let mut check = RateCheck {
+            new_rate,
+            samples: window.len(),
+            reference_rate: window.first().map(|s| s.rate),
+            cumulative_drift_bps: None,
+            z_score: None,
+            violations: vec![],
+        };
+
+        if window.len() < self.min_samples {
+            return Ok(check);
+        }
+
+        let rates = window
+            .iter()
+            .map(|s| to_f64(s.rate))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let new_rate = to_f64(new_rate)?;
+
+        // cumulative drift over the whole window
+        let reference = rates[0];
+        if reference > 0.0 {
+            let drift_bps = (new_rate / reference - 1.0) * 10_000.0;
+            check.cumulative_drift_bps = Some(drift_bps);
+            if drift_bps.abs() > self.max_cumulative_drift_bps as f64 {
+                check.violations.push(format!(
+                    "cumulative drift of {drift_bps:.2}bps exceeds {}bps",
+                    self.max_cumulative_drift_bps
+                ));
+            }
+        }
+
+        // z-score of the new epoch change against the epoch changes in the window
+        let changes: Vec<f64> = rates
+            .windows(2)
+            .filter(|pair| pair[0] > 0.0)
+            .map(|pair| pair[1] / pair[0] - 1.0)
+            .collect();
+        let last = rates[rates.len() - 1];
+        if changes.len() >= 2 && last > 0.0 {
+            let mean = changes.iter().sum::<f64>() / changes.len() as f64;
+            let variance =
+                changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / changes.len() as f64;
+            let std_dev = variance.sqrt();
+            // a perfectly flat history gives no meaningful z-score
+            if std_dev > f64::EPSILON {
+                let z_score = (new_rate / last - 1.0 - mean) / std_dev;
+                check.z_score = Some(z_score);
+                if z_score.abs() > self.max_z_score {
+                    check.violations.push(format!(
+                        "rate change z-score of {z_score:.2} exceeds {}",
+                        self.max_z_score
+                    ));
+                }
+            }
+        }
+
+        Ok(check)
+    }
+}
+
+/// a rate that was posted to the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateSample {
+    pub timestamp: u64,
+    pub rate: Decimal,
+}
+
+/// append-only, newline-delimited json history of the posted rates
+pub struct RateHistory {
+    path: PathBuf,
+}
+
+impl RateHistory {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| anyhow!("failed to open rate history at {}: {e}", path.display()))?;
+
+        Ok(Self { path })
+    }
+
+    /// all posted rates, oldest first
+    pub fn samples(&self) -> anyhow::Result<Vec<RateSample>> {
+        let file = File::open(&self.path)?;
+
+        let samples = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+
+        Ok(samples)
+    }
+
+    pub fn record(&self, rate: Decimal) -> anyhow::Result<()> {
+        let sample = RateSample {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            rate,
+        };
+
+        let mut line = serde_json::to_string(&sample)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+fn to_f64(rate: Decimal) -> anyhow::Result<f64> {
+    Ok(rate.to_string().parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(rate: &str) -> Decimal {
+        rate.parse().unwrap()
+    }
+
+    fn history(rates: &[&str]) -> Vec<RateSample> {
+        rates
+            .iter()
+            .enumerate()
+            .map(|(i, r)| RateSample {
+                timestamp: i as u64,
+                rate: rate(r),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_checks_wait_for_the_min_samples() {
+        let guard = RateGuard::default();
+        let check = guard
+            .check(&history(&["1.0", "1.0", "1.0", "1.0"]), rate("2.0"))
+            .unwrap();
+
+        assert_eq!(check.samples, 4);
+        assert_eq!(check.cumulative_drift_bps, None);
+        assert!(!check.tripped());
+    }
+
+    #[test]
+    fn test_drift_is_measured_against_the_oldest_rate_in_the_window() {
+        let guard = RateGuard {
+            window: 5,
+            // the steady history makes any change off its pace an outlier
+            max_z_score: f64::MAX,
+            ..Default::default()
+        };
+        // the 0.5 rate falls out of the window
+        let history = history(&["0.5", "1.0", "1.01", "1.02", "1.03", "1.04"]);
+
+        let check = guard.check(&history, rate("1.045")).unwrap();
+        assert_eq!(check.samples, 5);
+        assert_eq!(check.reference_rate, Some(rate("1.0")));
+        assert!(!check.tripped());
+
+        let check = guard.check(&history, rate("1.06")).unwrap();
+        assert!((check.cumulative_drift_bps.unwrap() - 600.0).abs() < 1e-6);
+        assert_eq!(check.violations.len(), 1);
+        assert!(check.violations[0].contains("cumulative drift"));
+    }
+
+    #[test]
+    fn test_flat_history_has_no_z_score() {
+        let guard = RateGuard::default();
+        let check = guard.check(&history(&["1.0"; 10]), rate("1.001")).unwrap();
+
+        assert_eq!(check.z_score, None);
+        assert!(!check.tripped());
+    }
+
+    #[test]
+    fn test_outlying_rate_change_trips_the_z_score() {
+        let guard = RateGuard::default();
+        let history = history(&["1.0", "1.001", "1.002", "1.0025", "1.0035"]);
+
+        // in line with the recent changes
+        let check = guard.check(&history, rate("1.0045")).unwrap();
+        assert!(check.z_score.unwrap().abs() < guard.max_z_score);
+        assert!(!check.tripped());
+
+        // well within the drift limit, but far off the recent changes
+        let check = guard.check(&history, rate("1.0135")).unwrap();
+        assert!(check.cumulative_drift_bps.unwrap() < 500.0);
+        assert!(check.z_score.unwrap() > guard.max_z_score);
+        assert_eq!(check.violations.len(), 1);
+        assert!(check.violations[0].contains("z-score"));
+    }
+
+    #[test]
+    fn test_history_returns_the_recorded_rates_oldest_first() {
+        let path = std::env::temp_dir().join(format!("history-{}.rates.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let history = RateHistory::open(&path).unwrap();
+        assert!(history.samples().unwrap().is_empty());
+
+        history.record(rate("1.0")).unwrap();
+        history.record(rate("1.01")).unwrap();
+
+        let rates: Vec<Decimal> = RateHistory::open(&path)
+            .unwrap()
+            .samples()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.rate)
+            .collect();
+        assert_eq!(rates, vec![rate("1.0"), rate("1.01")]);
+    }
+}
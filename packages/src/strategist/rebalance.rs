@@ -0,0 +1,403 @@
+use std::{
+    collections::HashMap,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+use cosmwasm_std::{Decimal, Uint128};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    phases::UPDATE_PHASE,
+    strategist::{clients::ContractQuerier, layout::Domain, rate_guard::RateSample, Strategy},
+    utils::{
+        mars::{query_mars_red_bank, query_red_bank_market},
+        metrics,
+        supervaults::query_supervault_share_value,
+    },
+};
+
+const SECONDS_PER_YEAR: f64 = 31_536_000.0;
+/// LP shares that the share value is simulated with, large enough for the
+/// simulation rounding not to show in the share value
+const SHARE_VALUE_PROBE: u128 = 1_000_000_000_000;
+
+/// moves the split of the deposits between Mars and the supervault towards
+/// whichever of the two yields more, within bounds. the rebalancer never
+/// executes anything: it proposes the contract updates for the owner.
+///
+/// the split is a single ratio between Mars and one supervault, so only
+/// lombard_btc sets a rebalancer. wbtc splits across several supervaults,
+/// and the other strategies have no dynamic ratio query provider.
+#[derive(Debug, Clone)]
+pub struct Rebalancer {
+    /// dynamic ratio query provider that the deposit splitter reads the
+    /// split from
+    pub dynamic_ratio_query_provider: String,
+    /// receivers of the deposit split
+    pub mars_receiver: String,
+    pub supervault_receiver: String,
+    /// Mars credit manager and supervault whose yields are compared
+    pub credit_manager: String,
+    pub supervault: String,
+    pub bounds: RebalanceBounds,
+}
+
+/// bounds that the rebalancer moves the split within, and the LP share
+/// value history it derives the supervault yield from. fields missing from
+/// the strategy config take their default values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RebalanceBounds {
+    /// bounds of the share of the deposits split to Mars
+    pub min_mars_ratio: Decimal,
+    pub max_mars_ratio: Decimal,
+    /// most the Mars ratio moves by in a single proposal
+    pub max_step: Decimal,
+    /// smallest APY spread between the two that is worth rebalancing over
+    pub min_apy_spread: f64,
+    /// LP share value history that the supervault yield is derived over
+    pub window_secs: u64,
+    /// shortest LP share value history that the supervault yield is
+    /// derived from
+    pub min_history_secs: u64,
+}
+
+impl Default for RebalanceBounds {
+    /// 50% to 90% of the deposits split to Mars, moved by 5% at a time once
+    /// the two yield at least 1% apart, with the supervault yield derived
+    /// from a week to a month of LP share values
+    fn default() -> Self {
+        Self {
+            min_mars_ratio: Decimal::percent(50),
+            max_mars_ratio: Decimal::percent(90),
+            max_step: Decimal::percent(5),
+            min_apy_spread: 0.01,
+            window_secs: 30 * 86_400,
+            min_history_secs: 7 * 86_400,
+        }
+    }
+}
+
+impl RebalanceBounds {
+    /// checks that the ratio bounds are ordered, and within 0 and 1
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.min_mars_ratio > self.max_mars_ratio || self.max_mars_ratio > Decimal::one() {
+            return Err(anyhow!(
+                "rebalance bounds of {} to {} are not within 0 and 1",
+                self.min_mars_ratio,
+                self.max_mars_ratio
+            ));
+        }
+        if self.min_history_secs > self.window_secs {
+            return Err(anyhow!(
+                "rebalance window of {}sec is shorter than the {}sec of history it needs",
+                self.window_secs,
+                self.min_history_secs
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// contract update for the owner to execute
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposedMsg {
+    pub contract: String,
+    pub msg: Value,
+}
+
+/// proposed split, along with the yields it was derived from and the
+/// updates that put it in place
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceProposal {
+    pub timestamp: u64,
+    pub mars_apy: f64,
+    pub supervault_apy: f64,
+    pub current_mars_ratio: Decimal,
+    pub proposed_mars_ratio: Decimal,
+    /// updates of the dynamic ratio query provider and the clearing queue,
+    /// in order
+    pub msgs: Vec<ProposedMsg>,
+}
+
+/// ratios the dynamic ratio query provider splits the denoms in, for the
+/// queried receiver
+#[derive(Deserialize, Debug)]
+struct DynamicRatioResponse {
+    denom_ratios: HashMap<String, Decimal>,
+}
+
+impl Rebalancer {
+    /// annualized growth of the LP share value from the oldest sample in
+    /// the window to the latest one. `None` until the samples span at least
+    /// `min_history_secs`.
+    pub fn supervault_apy(&self, samples: &[RateSample], now: u64) -> Option<f64> {
+        let latest = samples.last()?;
+        let oldest = samples
+            .iter()
+            .find(|s| now.saturating_sub(s.timestamp) <= self.bounds.window_secs)?;
+
+        let elapsed = latest.timestamp.saturating_sub(oldest.timestamp);
+        if elapsed < self.bounds.min_history_secs || elapsed == 0 {
+            return None;
+        }
+
+        let (oldest, latest) = (to_f64(oldest.rate)?, to_f64(latest.rate)?);
+        if oldest <= 0.0 {
+            return None;
+        }
+
+        Some((latest / oldest).powf(SECONDS_PER_YEAR / elapsed as f64) - 1.0)
+    }
+
+    /// Mars ratio a step away from the current one, towards the position
+    /// that yields more. `None` if the spread is too small to act on, or
+    /// the ratio is already at its bound.
+    pub fn target_mars_ratio(
+        &self,
+        current: Decimal,
+        mars_apy: f64,
+        supervault_apy: f64,
+    ) -> Option<Decimal> {
+        let spread = supervault_apy - mars_apy;
+        if spread.abs() < self.bounds.min_apy_spread {
+            return None;
+        }
+
+        let target = if spread > 0.0 {
+            current.saturating_sub(self.bounds.max_step)
+        } else {
+            current.saturating_add(self.bounds.max_step)
+        }
+        .max(self.bounds.min_mars_ratio)
+        .min(self.bounds.max_mars_ratio);
+
+        (target != current).then_some(target)
+    }
+
+    /// updates that split the deposits, and settle the withdrawals, in the
+    /// given Mars ratio. the split update mirrors the split config that the
+    /// provider was instantiated with. the clearing queue is owned by the
+    /// processor, so its update has to go through the authorizations.
+    pub fn proposal_msgs(
+        &self,
+        denom: &str,
+        clearing_queue: &str,
+        mars_ratio: Decimal,
+    ) -> Vec<ProposedMsg> {
+        let supervault_ratio = Decimal::one().saturating_sub(mars_ratio);
+
+        vec![
+            ProposedMsg {
+                contract: self.dynamic_ratio_query_provider.to_string(),
+                msg: json!({
+                    "update_split_cfg": {
+                        "split_cfg": {
+                            "split_cfg": {
+                                denom: {
+                                    &self.mars_receiver: mars_ratio,
+                                    &self.supervault_receiver: supervault_ratio,
+                                }
+                            }
+                        }
+                    }
+                }),
+            },
+            ProposedMsg {
+                contract: clearing_queue.to_string(),
+                msg: json!({
+                    "update_config": {
+                        "new_config": { "mars_settlement_ratio": mars_ratio }
+                    }
+                }),
+            },
+        ]
+    }
+}
+
+impl Strategy {
+    /// samples the supervault LP share value and proposes moving the split
+    /// a step towards the position that yields more. the proposal is
+    /// logged and written out for the owner to execute.
+    pub(crate) async fn propose_rebalance(&self) -> anyhow::Result<Option<RebalanceProposal>> {
+        let Some(rebalancer) = &self.layout.update.rebalance else {
+            return Ok(None);
+        };
+        let history = self
+            .share_values
+            .as_ref()
+            .ok_or_else(|| anyhow!("no LP share value history to rebalance with"))?;
+        let neutron = self.querier(Domain::Neutron)?;
+        let denom = &self.layout.neutron.deposit_denom;
+
+        let share_value = query_supervault_share_value(
+            neutron,
+            &rebalancer.supervault,
+            &rebalancer.supervault_receiver,
+            denom,
+            Uint128::new(SHARE_VALUE_PROBE),
+            None,
+        )
+        .await?;
+        info!(target: UPDATE_PHASE, "supervault {} LP share value = {share_value}", rebalancer.supervault);
+        if self.dry_run.is_none() {
+            history.record(share_value)?;
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let Some(supervault_apy) = rebalancer.supervault_apy(&history.samples()?, now) else {
+            info!(target: UPDATE_PHASE, "not enough LP share value history to rebalance yet");
+            return Ok(None);
+        };
+        self.metrics.set_gauge(
+            metrics::SUPERVAULT_APY,
+            &[("supervault", &rebalancer.supervault)],
+            supervault_apy,
+        );
+
+        let red_bank = query_mars_red_bank(neutron, &rebalancer.credit_manager, None).await?;
+        let mars_apy = query_red_bank_market(neutron, &red_bank, denom, None)
+            .await?
+            .lend_apy();
+
+        let DynamicRatioResponse { denom_ratios } = neutron
+            .query_contract_state(
+                &rebalancer.dynamic_ratio_query_provider,
+                json!({
+                    "dynamic_ratio": {
+                        "denoms": [denom],
+                        "params": rebalancer.mars_receiver,
+                    }
+                }),
+                None,
+            )
+            .await?;
+        let current = *denom_ratios
+            .get(denom)
+            .ok_or_else(|| anyhow!("no {denom} split ratio of the Mars receiver"))?;
+
+        info!(
+            target: UPDATE_PHASE,
+            "mars apy = {mars_apy}, supervault apy = {supervault_apy}, mars ratio = {current}"
+        );
+
+        let Some(target) = rebalancer.target_mars_ratio(current, mars_apy, supervault_apy) else {
+            info!(target: UPDATE_PHASE, "mars ratio of {current} stays");
+            return Ok(None);
+        };
+
+        let proposal = RebalanceProposal {
+            timestamp: now,
+            mars_apy,
+            supervault_apy,
+            current_mars_ratio: current,
+            proposed_mars_ratio: target,
+            msgs: rebalancer.proposal_msgs(denom, &self.layout.neutron.clearing_queue, target),
+        };
+        let rendered = serde_json::to_string_pretty(&proposal)?;
+        info!(target: UPDATE_PHASE, "proposing to move the mars ratio from {current} to {target}: {rendered}");
+
+        if let (Some(dir), None) = (&self.proposals_dir, &self.dry_run) {
+            fs::create_dir_all(dir)?;
+            let path = dir.join(format!("rebalance-{now}.json"));
+            fs::write(&path, rendered)?;
+            info!(target: UPDATE_PHASE, "rebalance proposal written to {}", path.display());
+        }
+        self.metrics
+            .inc_counter(metrics::REBALANCE_PROPOSALS, &[], 1.0);
+
+        Ok(Some(proposal))
+    }
+}
+
+fn to_f64(value: Decimal) -> Option<f64> {
+    value.to_string().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebalancer() -> Rebalancer {
+        Rebalancer {
+            dynamic_ratio_query_provider: "ratio_provider".to_string(),
+            mars_receiver: "mars_deposit".to_string(),
+            supervault_receiver: "supervault_deposit".to_string(),
+            credit_manager: "credit_manager".to_string(),
+            supervault: "supervault".to_string(),
+            bounds: RebalanceBounds {
+                max_step: Decimal::percent(10),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_rebalance_steps_stay_within_the_bounds() {
+        let rebalancer = rebalancer();
+
+        // the supervault yields more, but the ratio only steps down to the floor
+        assert_eq!(
+            rebalancer.target_mars_ratio(Decimal::percent(55), 0.05, 0.10),
+            Some(Decimal::percent(50))
+        );
+        assert_eq!(
+            rebalancer.target_mars_ratio(Decimal::percent(50), 0.05, 0.10),
+            None
+        );
+        // Mars yields more
+        assert_eq!(
+            rebalancer.target_mars_ratio(Decimal::percent(80), 0.10, 0.05),
+            Some(Decimal::percent(90))
+        );
+        // the spread is too small to act on
+        assert_eq!(
+            rebalancer.target_mars_ratio(Decimal::percent(80), 0.050, 0.055),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bounds_left_out_of_the_config_keep_their_defaults() {
+        let bounds: RebalanceBounds = toml::from_str(
+            r#"
+            max_mars_ratio = "0.8"
+            window_secs = 864000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(bounds.max_mars_ratio, Decimal::percent(80));
+        assert_eq!(bounds.window_secs, 10 * 86_400);
+        assert_eq!(bounds.min_mars_ratio, Decimal::percent(50));
+        assert_eq!(bounds.max_step, Decimal::percent(5));
+        assert!(bounds.validate().is_ok());
+    }
+
+    #[test]
+    fn test_inverted_bounds_are_rejected() {
+        let ratios = RebalanceBounds {
+            min_mars_ratio: Decimal::percent(90),
+            max_mars_ratio: Decimal::percent(50),
+            ..Default::default()
+        };
+        assert!(ratios.validate().is_err());
+
+        let above_one = RebalanceBounds {
+            max_mars_ratio: Decimal::percent(110),
+            ..Default::default()
+        };
+        assert!(above_one.validate().is_err());
+
+        let history = RebalanceBounds {
+            window_secs: 86_400,
+            ..Default::default()
+        };
+        assert!(history.validate().is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use alloy::primitives::TxHash;
+use cosmwasm_std::Decimal;
+use log::{info, warn};
+
+use crate::{
+    phases::VALENCE_WORKER,
+    strategist::{
+        journal::{JournalRecord, JournalStep},
+        phases::update::UPDATE_RATE_TX,
+        Strategy,
+    },
+    types::sol_types::OneWayVault,
+    utils::valence_core,
+};
+
+impl Strategy {
+    /// reconciles the steps that were left in flight by a previous run
+    /// against the current chain state. steps are resumed where possible,
+    /// and skipped otherwise. no transfers are re-sent or waited on from
+    /// here; phases derive their actions from the on-chain balances, so a
+    /// skipped step gets picked up by the next cycle if it is still needed.
+    pub async fn reconcile_journal(&self) -> anyhow::Result<()> {
+        let in_flight = self.journal.in_flight()?;
+
+        if in_flight.is_empty() {
+            info!(target: VALENCE_WORKER, "journal: no in-flight steps to reconcile");
+            return Ok(());
+        }
+
+        self.reconcile_records(in_flight).await
+    }
+
+    /// reconciles only the in-flight steps of the given phase, e.g. for the
+    /// phase to pick up where its previous run left off
+    pub(crate) async fn reconcile_phase(&self, phase: &str) -> anyhow::Result<()> {
+        let in_flight = self
+            .journal
+            .in_flight()?
+            .into_iter()
+            .filter(|record| record.phase == phase)
+            .collect();
+
+        self.reconcile_records(in_flight).await
+    }
+
+    async fn reconcile_records(&self, records: Vec<JournalRecord>) -> anyhow::Result<()> {
+        for record in records {
+            info!(target: VALENCE_WORKER, "journal: reconciling step #{} of {} phase: {:?}", record.id, record.phase, record.step);
+            self.reconcile_step(&record).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn reconcile_step(&self, record: &JournalRecord) -> anyhow::Result<()> {
+        let JournalRecord { id, phase, .. } = record;
+
+        match &record.step {
+            // enqueue may or may not have landed. if there are batches sitting in
+            // the processor queue, the interrupted step got as far as enqueuing
+            // and we only need to tick them through
+            Some(JournalStep::NeutronSubroutine { label }) => {
+                let queue = valence_core::query_neutron_processor_queue(
+                    self.neutron_client.as_ref(),
+                    &self.layout.neutron.processor,
+                    valence_authorization_utils::authorization::Priority::Medium,
+                )
+                .await?;
+
+                if queue.is_empty() {
+                    info!(target: VALENCE_WORKER, "journal: no pending {label} batches in the processor queue; skipping step #{id}");
+                    self.journal.skip(phase, *id)?;
+                } else {
+                    info!(target: VALENCE_WORKER, "journal: ticking {} pending batches left by {label}", queue.len());
+                    for _ in queue.iter() {
+                        valence_core::tick_neutron(
+                            self.neutron_client.as_ref(),
+                            &self.layout.neutron.processor,
+                        )
+                        .await?;
+                    }
+                    self.journal.complete(phase, *id)?;
+                }
+            }
+            // the tx was interrupted before it was broadcast, or before its
+            // hash got journaled. the deposit phase re-reads the ethereum
+            // deposit account balance before routing, so a tx that went
+            // through will not be sent again
+            Some(JournalStep::EthereumTx {
+                label,
+                tx_hash: None,
+            }) => {
+                warn!(target: VALENCE_WORKER, "journal: ethereum {label} tx was interrupted before it was broadcast; skipping step #{id}");
+                self.journal.skip(phase, *id)?;
+            }
+            // the tx was broadcast, so its receipt tells whether it went through
+            Some(JournalStep::EthereumTx {
+                label,
+                tx_hash: Some(tx_hash),
+            }) => {
+                let receipt = self.eth_client.receipt(tx_hash.parse::<TxHash>()?).await?;
+
+                match receipt {
+                    Some(receipt) if receipt.success => {
+                        info!(target: VALENCE_WORKER, "journal: ethereum {label} tx {tx_hash} was included in block {}; completing step #{id}", receipt.block_number);
+                        // the rate checks of the next update need the rate
+                        // that landed on record
+                        if label == UPDATE_RATE_TX {
+                            self.record_posted_rate().await?;
+                        }
+                        self.journal.complete(phase, *id)?;
+                    }
+                    Some(receipt) => {
+                        warn!(target: VALENCE_WORKER, "journal: ethereum {label} tx {tx_hash} reverted in block {}; skipping step #{id}", receipt.block_number);
+                        self.journal.skip(phase, *id)?;
+                    }
+                    // a pending tx holds the signer nonce, so anything the
+                    // next cycle sends gets queued behind it
+                    None => {
+                        warn!(target: VALENCE_WORKER, "journal: ethereum {label} tx {tx_hash} is not on chain; it was either dropped or is still pending. skipping step #{id}");
+                        self.journal.skip(phase, *id)?;
+                    }
+                }
+            }
+            // funds were already sent out and are counted in transit until
+            // they arrive. rather than blocking startup on polling for them,
+            // the wait is left to the transit ledger, which the deposit
+            // phase settles every cycle.
+            Some(JournalStep::AwaitArrival {
+                domain,
+                address,
+                denom,
+                expected,
+                tx_hash,
+            }) => {
+                let balance = self.domain_balance(*domain, address, denom).await?;
+                info!(
+                    target: VALENCE_WORKER,
+                    "journal: {domain} {address} {denom} balance = {balance}, expected = {expected} (tx: {tx_hash:?})"
+                );
+
+                if balance >= *expected {
+                    self.journal.complete(phase, *id)?;
+                } else {
+                    info!(target: VALENCE_WORKER, "journal: funds are still in transit; leaving them to the transit ledger and skipping step #{id}");
+                    self.journal.skip(phase, *id)?;
+                }
+            }
+            None => {
+                warn!(target: VALENCE_WORKER, "journal: step #{id} has no step description; skipping");
+                self.journal.skip(phase, *id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// adds the rate currently posted on the vault to the rate history
+    async fn record_posted_rate(&self) -> anyhow::Result<()> {
+        let posted_rate = self
+            .eth_client
+            .query(
+                self.layout.ethereum.one_way_vault,
+                OneWayVault::redemptionRateCall {},
+                None,
+            )
+            .await?
+            ._0;
+        let posted_rate = Decimal::raw(u128::try_from(posted_rate)?);
+
+        info!(target: VALENCE_WORKER, "journal: recording the posted rate {posted_rate} in the rate history");
+        self.rate_history.record(posted_rate)
+    }
+}
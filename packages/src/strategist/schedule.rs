@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use alloy::primitives::Address;
+use log::info;
+use tokio::time::Instant;
+
+use crate::{
+    phases::VALENCE_WORKER,
+    strategist::{clients::EthereumDomain, error::StrategistError, Strategy},
+    types::sol_types::OneWayVault,
+    utils::metrics,
+};
+
+/// age of the posted rate relative to the vault stale rate pause window
+#[derive(Debug, Clone, Copy)]
+pub struct RateStaleness {
+    /// latest Ethereum block timestamp
+    pub now: u64,
+    pub last_rate_update: u64,
+    /// zero if the vault never pauses over a stale rate
+    pub max_rate_update_delay: u64,
+}
+
+impl RateStaleness {
+    /// seconds left before the vault pauses itself over a stale rate, if
+    /// it ever does
+    pub fn remaining_secs(&self) -> Option<u64> {
+        if self.max_rate_update_delay == 0 {
+            return None;
+        }
+
+        Some(
+            self.last_rate_update
+                .saturating_add(self.max_rate_update_delay)
+                .saturating_sub(self.now),
+        )
+    }
+}
+
+/// deadline that the phases running ahead of the mandatory rate update
+/// stop at, for the update to go out in time. phases only check it between
+/// their steps, where stopping leaves nothing half-done: every step that
+/// was started is either carried out or left in flight for the journal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Preemption {
+    deadline: Option<Instant>,
+}
+
+impl Preemption {
+    /// pre-emption once the deadline passes
+    pub fn at(deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+        }
+    }
+
+    /// whether the deadline passed
+    pub fn is_due(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// whether the deadline passes within the given time, e.g. a backoff
+    pub fn is_due_within(&self, duration: Duration) -> bool {
+        self.deadline
+            .is_some_and(|deadline| deadline.saturating_duration_since(Instant::now()) <= duration)
+    }
+
+    /// fails with `StrategistError::Preempted` once the deadline passed.
+    /// `step` describes the step that the phase would carry out next.
+    pub fn check(&self, step: &str) -> anyhow::Result<()> {
+        if self.is_due() {
+            return Err(StrategistError::Preempted(format!("stopped before {step}")).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// reads the posted rate age and the max allowed delay between the
+/// updates off the vault
+pub async fn query_rate_staleness(
+    ethereum: &dyn EthereumDomain,
+    vault: Address,
+) -> anyhow::Result<RateStaleness> {
+    let (now, last_rate_update, config) = tokio::try_join!(
+        ethereum.block_timestamp(),
+        ethereum.query(vault, OneWayVault::lastRateUpdateTimestampCall {}, None),
+        ethereum.query(vault, OneWayVault::configCall {}, None),
+    )?;
+
+    Ok(RateStaleness {
+        now,
+        last_rate_update: last_rate_update._0,
+        max_rate_update_delay: config.maxRateUpdateDelay,
+    })
+}
+
+impl Strategy {
+    /// time left until the mandatory rate update is due, i.e. until the
+    /// posted rate gets within the stale rate margin of the vault pausing
+    /// itself. `None` if the vault never pauses over a stale rate.
+    pub(crate) async fn update_due_in(&self) -> anyhow::Result<Option<Duration>> {
+        let staleness =
+            query_rate_staleness(self.eth_client.as_ref(), self.layout.ethereum.one_way_vault)
+                .await?;
+
+        let Some(remaining) = staleness.remaining_secs() else {
+            return Ok(None);
+        };
+
+        self.metrics
+            .set_gauge(metrics::STALE_RATE_PAUSE_IN, &[], remaining as f64);
+
+        let due_in = remaining.saturating_sub(self.layout.sentry.stale_rate_margin_secs);
+        info!(
+            target: VALENCE_WORKER,
+            "{remaining}sec left before the vault pauses over a stale rate; update due in {due_in}sec"
+        );
+
+        Ok(Some(Duration::from_secs(due_in)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_preemption_is_due_once_the_deadline_passes() {
+        let preemption = Preemption::at(Instant::now() + Duration::from_secs(100));
+
+        assert!(preemption.check("polling").is_ok());
+        assert!(!preemption.is_due_within(Duration::from_secs(50)));
+        assert!(preemption.is_due_within(Duration::from_secs(100)));
+
+        tokio::time::advance(Duration::from_secs(100)).await;
+
+        let err = preemption.check("polling").unwrap_err();
+        assert!(matches!(
+            StrategistError::classify(&err),
+            StrategistError::Preempted(_)
+        ));
+        assert!(err.to_string().contains("stopped before polling"));
+    }
+
+    #[test]
+    fn test_no_deadline_never_preempts() {
+        let preemption = Preemption::default();
+
+        assert!(!preemption.is_due());
+        assert!(!preemption.is_due_within(Duration::MAX));
+        assert!(preemption.check("polling").is_ok());
+    }
+}
@@ -0,0 +1,242 @@
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use alloy::primitives::U256;
+use async_trait::async_trait;
+use cosmwasm_std::Decimal;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    phases::SENTRY_PHASE,
+    strategist::{
+        clients::{ContractQuerier, CosmosQuerier, EthereumDomain, WithdrawRequestIndexer},
+        layout::StrategyLayout,
+    },
+    types::sol_types::{OneWayVault, ERC20},
+    utils::{mars, obligation::ClearingQueueConfig},
+};
+
+/// read access to the strategy domains that the sentry conditions are
+/// evaluated against
+pub struct SentryContext<'a> {
+    pub layout: &'a StrategyLayout,
+    pub ethereum: &'a dyn EthereumDomain,
+    pub neutron: &'a dyn CosmosQuerier,
+    pub indexer: &'a dyn WithdrawRequestIndexer,
+}
+
+/// a condition that gates the strategist cycle. the sentry phase only lets
+/// the cycle proceed once the configured conditions hold.
+#[async_trait]
+pub trait SentryCondition: fmt::Debug + Send + Sync {
+    /// whether the condition currently holds
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool>;
+}
+
+/// composable set of sentry conditions, configured per strategy. read
+/// from the strategy config, e.g.
+/// `predicate = { all = [{ kill_switch = "vault_paused" }, { gas_price = { max_gas_price_gwei = 50 } }] }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SentryPredicate {
+    /// always holds, making the sentry phase a plain cooldown
+    Always,
+    /// holds if every predicate holds
+    All(Vec<SentryPredicate>),
+    /// holds if at least one of the predicates holds
+    Any(Vec<SentryPredicate>),
+    Not(Box<SentryPredicate>),
+    GasPrice(GasPriceCeiling),
+    PendingDeposits(MinPendingDeposits),
+    WithdrawRequests(MinWithdrawRequests),
+    MarsMarket(MarsMarketThreshold),
+    KillSwitch(KillSwitch),
+    /// condition implemented outside of this crate. it cannot be
+    /// configured, only set up in code.
+    #[serde(skip)]
+    Custom(Arc<dyn SentryCondition>),
+}
+
+#[async_trait]
+impl SentryCondition for SentryPredicate {
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool> {
+        match self {
+            SentryPredicate::Always => Ok(true),
+            SentryPredicate::All(predicates) => {
+                for predicate in predicates {
+                    if !predicate.holds(ctx).await? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            SentryPredicate::Any(predicates) => {
+                for predicate in predicates {
+                    if predicate.holds(ctx).await? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            SentryPredicate::Not(predicate) => Ok(!predicate.holds(ctx).await?),
+            SentryPredicate::GasPrice(condition) => condition.holds(ctx).await,
+            SentryPredicate::PendingDeposits(condition) => condition.holds(ctx).await,
+            SentryPredicate::WithdrawRequests(condition) => condition.holds(ctx).await,
+            SentryPredicate::MarsMarket(condition) => condition.holds(ctx).await,
+            SentryPredicate::KillSwitch(condition) => condition.holds(ctx).await,
+            SentryPredicate::Custom(condition) => condition.holds(ctx).await,
+        }
+    }
+}
+
+/// holds while the Ethereum gas price does not exceed the ceiling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasPriceCeiling {
+    pub max_gas_price_gwei: u64,
+}
+
+#[async_trait]
+impl SentryCondition for GasPriceCeiling {
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool> {
+        let gas_price = ctx.ethereum.gas_price().await?;
+        let ceiling = u128::from(self.max_gas_price_gwei) * 1_000_000_000;
+
+        info!(target: SENTRY_PHASE, "gas price: {gas_price}wei, ceiling: {ceiling}wei");
+
+        Ok(gas_price <= ceiling)
+    }
+}
+
+/// holds once the Ethereum deposit account holds at least `min_amount`
+/// of the deposit token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinPendingDeposits {
+    pub min_amount: U256,
+}
+
+#[async_trait]
+impl SentryCondition for MinPendingDeposits {
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool> {
+        let pending = ctx
+            .ethereum
+            .query(
+                ctx.layout.ethereum.deposit_token,
+                ERC20::balanceOfCall {
+                    account: ctx.layout.ethereum.deposit_account,
+                },
+                None,
+            )
+            .await?
+            ._0;
+
+        info!(target: SENTRY_PHASE, "pending deposits: {pending}, minimum: {}", self.min_amount);
+
+        Ok(pending >= self.min_amount)
+    }
+}
+
+/// holds once at least `min_count` withdraw requests are waiting to be
+/// registered into the clearing queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinWithdrawRequests {
+    pub min_count: usize,
+}
+
+#[async_trait]
+impl SentryCondition for MinWithdrawRequests {
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool> {
+        let clearing_queue_cfg: ClearingQueueConfig = ctx
+            .neutron
+            .query_contract_state(
+                &ctx.layout.neutron.clearing_queue,
+                valence_clearing_queue_supervaults::msg::QueryMsg::GetLibraryConfig {},
+                None,
+            )
+            .await?;
+
+        let pending = ctx
+            .indexer
+            .withdraw_request_ids(clearing_queue_cfg.next_id())
+            .await?
+            .len();
+
+        info!(target: SENTRY_PHASE, "pending withdraw requests: {pending}, minimum: {}", self.min_count);
+
+        Ok(pending >= self.min_count)
+    }
+}
+
+/// holds while the Mars red bank market of `denom` is within all of the
+/// configured bounds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarsMarketThreshold {
+    pub red_bank: String,
+    pub denom: String,
+    pub min_utilization: Option<Decimal>,
+    pub max_utilization: Option<Decimal>,
+    pub min_lend_rate: Option<Decimal>,
+    pub max_lend_rate: Option<Decimal>,
+}
+
+#[async_trait]
+impl SentryCondition for MarsMarketThreshold {
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool> {
+        let market =
+            mars::query_red_bank_market(ctx.neutron, &self.red_bank, &self.denom, None).await?;
+
+        info!(
+            target: SENTRY_PHASE,
+            "mars {} market: utilization={}, lend_rate={}",
+            self.denom, market.utilization_rate, market.liquidity_rate
+        );
+
+        let within = |value: Decimal, min: Option<Decimal>, max: Option<Decimal>| {
+            min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+        };
+
+        Ok(within(
+            market.utilization_rate,
+            self.min_utilization,
+            self.max_utilization,
+        ) && within(
+            market.liquidity_rate,
+            self.min_lend_rate,
+            self.max_lend_rate,
+        ))
+    }
+}
+
+/// holds while the kill switch is not engaged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillSwitch {
+    /// engaged while the file exists
+    File(PathBuf),
+    /// engaged while the vault is paused, for whatever reason
+    VaultPaused,
+}
+
+#[async_trait]
+impl SentryCondition for KillSwitch {
+    async fn holds(&self, ctx: &SentryContext<'_>) -> anyhow::Result<bool> {
+        let engaged = match self {
+            KillSwitch::File(path) => tokio::fs::try_exists(path).await?,
+            KillSwitch::VaultPaused => {
+                ctx.ethereum
+                    .query(
+                        ctx.layout.ethereum.one_way_vault,
+                        OneWayVault::vaultStateCall {},
+                        None,
+                    )
+                    .await?
+                    .paused
+            }
+        };
+
+        if engaged {
+            info!(target: SENTRY_PHASE, "kill switch {self:?} is engaged");
+        }
+
+        Ok(!engaged)
+    }
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use anyhow::anyhow;
+use valence_domain_clients::clients::{
+    coprocessor::CoprocessorClient, ethereum::EthereumClient,
+    ibc_eureka_route_client::IBCEurekaRouteClient, neutron::NeutronClient,
+    valence_indexer::OneWayVaultIndexerClient,
+};
+
+use crate::{
+    ibc_eureka_chain_ids::{EUREKA_COSMOS_HUB_CHAIN_ID, EUREKA_ETHEREUM_CHAIN_ID},
+    strategist::{
+        clients::{
+            CosmosQuerier, EthereumDomain, EurekaRouter, LiveNeutron, NeutronDomain, Prover,
+            StrategyClients, WithdrawRequestIndexer,
+        },
+        journal::CycleJournal,
+        layout::{DepositRoute, Domain, StrategyLayout},
+        plan::DryRun,
+        rate_guard::RateHistory,
+        schedule::Preemption,
+        transit::TransitLedger,
+    },
+    utils::{
+        eth_tx::DEFAULT_CONFIRMATIONS,
+        grpc_querier::GrpcQuerier,
+        metrics::{serve_metrics, Metrics},
+        snapshot::Snapshot,
+    },
+};
+
+// main strategy struct that wraps around the StrategyLayout
+// and stores the initialized clients
+pub struct Strategy {
+    /// strategy name
+    pub label: String,
+
+    /// strategy timeout (in seconds)
+    pub timeout: u64,
+
+    /// confirmations awaited for every ethereum tx
+    pub eth_confirmations: u64,
+
+    /// declarative strategy description
+    pub layout: StrategyLayout,
+
+    /// active ethereum client
+    pub(crate) eth_client: Arc<dyn EthereumDomain>,
+    /// active neutron client
+    pub(crate) neutron_client: Arc<dyn NeutronDomain>,
+    /// active one way vault indexer client
+    pub(crate) indexer_client: Arc<dyn WithdrawRequestIndexer>,
+    /// active coprocessor client
+    pub(crate) coprocessor_client: Arc<dyn Prover>,
+    /// skip route client for IBC eureka (eureka routes only)
+    pub(crate) ibc_eureka_client: Option<Arc<dyn EurekaRouter>>,
+    /// read access to every cosmos domain used by the route
+    pub(crate) queriers: HashMap<Domain, Arc<dyn CosmosQuerier>>,
+
+    /// on-disk journal of the steps carried out by the phases
+    pub(crate) journal: CycleJournal,
+    /// recorder of the planned actions (dry-run mode only)
+    pub(crate) dry_run: Option<DryRun>,
+    /// strategist gauges and counters
+    pub(crate) metrics: Arc<Metrics>,
+    /// history of the rates posted by the strategist
+    pub(crate) rate_history: RateHistory,
+    /// transfers in flight between the domains
+    pub(crate) transit: TransitLedger,
+    /// history of the supervault LP share values (rebalancing only)
+    pub(crate) share_values: Option<RateHistory>,
+    /// directory that rebalance proposals are written to
+    pub(crate) proposals_dir: Option<PathBuf>,
+    /// deadline of the phase currently running ahead of the mandatory
+    /// rate update
+    pub(crate) preemption: Preemption,
+    /// whether the settlement already warned about settling one obligation
+    /// per execution
+    pub(crate) batch_fallback_warned: AtomicBool,
+}
+
+impl Strategy {
+    /// strategy initializer that takes in a `StrategyLayout`, and uses it
+    /// to initialize the domain clients required by its route. prerequisite
+    /// to starting the strategist.
+    ///
+    /// any steps left in flight by a previous run are reconciled against
+    /// the chain state before the strategy is handed out.
+    pub async fn new(layout: StrategyLayout) -> anyhow::Result<Self> {
+        let mut strategy = Self::connect(layout, None).await?;
+
+        let journal_path = env::var("JOURNAL_PATH")
+            .unwrap_or_else(|_| format!("{}.journal.jsonl", strategy.label));
+        strategy.journal = CycleJournal::open(&journal_path)?;
+
+        let transit_path =
+            env::var("TRANSIT_PATH").unwrap_or_else(|_| format!("{}.transit.json", strategy.label));
+        strategy.transit = TransitLedger::open(&transit_path)?;
+
+        // metrics are always collected, but only served if the address is set
+        if let Ok(metrics_addr) = env::var("METRICS_ADDR") {
+            serve_metrics(strategy.metrics.clone(), &metrics_addr).await?;
+        }
+
+        strategy.reconcile_journal().await?;
+
+        Ok(strategy)
+    }
+
+    /// initializes the strategy in dry-run mode. phases run against live
+    /// queries, but every tx, processor enqueue and proof request gets
+    /// recorded into the cycle plan instead of being broadcast.
+    pub async fn new_dry_run(layout: StrategyLayout) -> anyhow::Result<Self> {
+        Self::connect(layout, Some(DryRun::default())).await
+    }
+
+    /// initializes the domain clients. journaling is left disabled, and
+    /// transfers in transit are only tracked in memory.
+    async fn connect(layout: StrategyLayout, dry_run: Option<DryRun>) -> anyhow::Result<Self> {
+        layout.cycle.validate()?;
+        if let Some(rebalancer) = &layout.update.rebalance {
+            rebalancer.bounds.validate()?;
+        }
+
+        let mnemonic =
+            env::var("MNEMONIC").map_err(|e| anyhow!("mnemonic must be provided: {e}"))?;
+        let label = env::var("LABEL").map_err(|e| anyhow!("label must be provided: {e}"))?;
+        let indexer_api_key = env::var("INDEXER_API_KEY")
+            .map_err(|e| anyhow!("indexer api key must be provided: {e}"))?;
+        let indexer_api_url = env::var("INDEXER_API_URL")
+            .map_err(|e| anyhow!("indexer api url key must be provided: {e}"))?;
+        let strategy_timeout: u64 = env::var("STRATEGY_TIMEOUT")
+            .map_err(|e| anyhow!("Strategy timeout must be provided: {e}"))?
+            .parse()?;
+        let rate_history_path =
+            env::var("RATE_HISTORY_PATH").unwrap_or_else(|_| format!("{label}.rates.jsonl"));
+        let eth_confirmations: u64 = match env::var("ETH_CONFIRMATIONS") {
+            Ok(confirmations) => confirmations.parse()?,
+            Err(_) => DEFAULT_CONFIRMATIONS,
+        };
+
+        let neutron_querier = GrpcQuerier::connect(
+            &layout.neutron.connection.grpc_url,
+            &layout.neutron.connection.grpc_port,
+        )?;
+        let neutron_client = LiveNeutron {
+            client: NeutronClient::new(
+                &layout.neutron.connection.grpc_url,
+                &layout.neutron.connection.grpc_port,
+                &mnemonic,
+                &layout.neutron.connection.chain_id,
+            )
+            .await?,
+            querier: neutron_querier.clone(),
+        };
+
+        let eth_client = EthereumClient::new(&layout.ethereum.rpc_url, &mnemonic, None)?;
+
+        let indexer_client = OneWayVaultIndexerClient::new(
+            &indexer_api_url,
+            &indexer_api_key,
+            &layout.ethereum.one_way_vault.to_string(),
+        );
+
+        let coprocessor_client = CoprocessorClient::default();
+
+        let mut queriers: HashMap<Domain, Arc<dyn CosmosQuerier>> =
+            HashMap::from([(Domain::Neutron, Arc::new(neutron_querier) as _)]);
+        let mut ibc_eureka_client: Option<Arc<dyn EurekaRouter>> = None;
+
+        match &layout.route {
+            DepositRoute::Eureka(route) => {
+                let eureka_api_url = env::var("EUREKA_API_URL")
+                    .map_err(|e| anyhow!("IBC Eureka route api url must be provided: {e}"))?;
+
+                queriers.insert(
+                    Domain::Gaia,
+                    Arc::new(GrpcQuerier::connect(
+                        &route.gaia.grpc_url,
+                        &route.gaia.grpc_port,
+                    )?),
+                );
+
+                if let Some(lombard) = &route.lombard {
+                    queriers.insert(
+                        Domain::Lombard,
+                        Arc::new(GrpcQuerier::connect(
+                            &lombard.connection.grpc_url,
+                            &lombard.connection.grpc_port,
+                        )?),
+                    );
+                }
+
+                ibc_eureka_client = Some(Arc::new(IBCEurekaRouteClient::new(
+                    &eureka_api_url,
+                    EUREKA_ETHEREUM_CHAIN_ID,
+                    &layout.ethereum.deposit_token.to_string(),
+                    EUREKA_COSMOS_HUB_CHAIN_ID,
+                    &route.gaia_deposit_denom,
+                )));
+            }
+            DepositRoute::Cctp(route) => {
+                queriers.insert(
+                    Domain::Noble,
+                    Arc::new(GrpcQuerier::connect(
+                        &route.noble.grpc_url,
+                        &route.noble.grpc_port,
+                    )?),
+                );
+            }
+        }
+
+        let clients = StrategyClients {
+            ethereum: Arc::new(eth_client),
+            neutron: Arc::new(neutron_client),
+            queriers,
+            indexer: Arc::new(indexer_client),
+            prover: Arc::new(coprocessor_client),
+            eureka_router: ibc_eureka_client,
+        };
+
+        let mut strategy = Self::from_clients(
+            label,
+            strategy_timeout,
+            layout,
+            clients,
+            RateHistory::open(&rate_history_path)?,
+            dry_run,
+        );
+        strategy.eth_confirmations = eth_confirmations;
+
+        if strategy.layout.update.rebalance.is_some() {
+            let share_values_path = env::var("SHARE_VALUE_HISTORY_PATH")
+                .unwrap_or_else(|_| format!("{}.share_values.jsonl", strategy.label));
+            strategy.share_values = Some(RateHistory::open(&share_values_path)?);
+            strategy.proposals_dir = Some(PathBuf::from(
+                env::var("REBALANCE_PROPOSALS_DIR")
+                    .unwrap_or_else(|_| format!("{}.proposals", strategy.label)),
+            ));
+        }
+
+        Ok(strategy)
+    }
+
+    /// assembles the strategy from already initialized domain clients, e.g.
+    /// the in-memory mock ones. journaling is left disabled, and transfers
+    /// in transit are only tracked in memory.
+    pub fn from_clients(
+        label: String,
+        timeout: u64,
+        layout: StrategyLayout,
+        clients: StrategyClients,
+        rate_history: RateHistory,
+        dry_run: Option<DryRun>,
+    ) -> Self {
+        Self {
+            label,
+            timeout,
+            eth_confirmations: DEFAULT_CONFIRMATIONS,
+            layout,
+            eth_client: clients.ethereum,
+            neutron_client: clients.neutron,
+            indexer_client: clients.indexer,
+            coprocessor_client: clients.prover,
+            ibc_eureka_client: clients.eureka_router,
+            queriers: clients.queriers,
+            journal: CycleJournal::disabled(),
+            dry_run,
+            metrics: Arc::new(Metrics::default()),
+            rate_history,
+            transit: TransitLedger::in_memory(),
+            share_values: None,
+            proposals_dir: None,
+            preemption: Preemption::default(),
+            batch_fallback_warned: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn ibc_eureka_client(&self) -> anyhow::Result<&dyn EurekaRouter> {
+        self.ibc_eureka_client
+            .as_deref()
+            .ok_or_else(|| anyhow!("IBC-Eureka route client is not configured for this strategy"))
+    }
+
+    /// read access to the given cosmos domain
+    pub(crate) fn querier(&self, domain: Domain) -> anyhow::Result<&dyn CosmosQuerier> {
+        self.queriers
+            .get(&domain)
+            .map(|querier| querier.as_ref())
+            .ok_or_else(|| anyhow!("{domain} querier is not configured for this strategy"))
+    }
+
+    /// captures the latest ethereum block along with the latest height of
+    /// every cosmos domain used by the route, for the phases to pin their
+    /// reads to
+    pub(crate) async fn snapshot(&self) -> anyhow::Result<Snapshot> {
+        let latest_height = |domain: Domain| async move {
+            match self.queriers.get(&domain) {
+                Some(querier) => querier.latest_height().await.map(Some),
+                None => Ok(None),
+            }
+        };
+
+        let (ethereum_block, neutron_height, gaia_height, lombard_height, noble_height) = tokio::try_join!(
+            self.eth_client.block_number(),
+            self.querier(Domain::Neutron)?.latest_height(),
+            latest_height(Domain::Gaia),
+            latest_height(Domain::Lombard),
+            latest_height(Domain::Noble),
+        )?;
+
+        Ok(Snapshot {
+            ethereum_block,
+            neutron_height,
+            gaia_height,
+            lombard_height,
+            noble_height,
+        })
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    strategist::{layout::Domain, Strategy},
+    utils::{metrics, snapshot::Snapshot, valuation::PositionValuator},
+};
+
+/// name of the TVL breakdown line item that the transfers in transit are
+/// counted under
+pub const IN_TRANSIT_POSITION: &str = "in transit";
+
+/// transfer that moved funds out of an account held by the program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboundTransfer {
+    /// label of the transfer, e.g. `cctp_transfer`
+    pub transfer: String,
+    /// destination of the funds
+    pub domain: Domain,
+    pub address: String,
+    pub denom: String,
+    /// amount that left the source account
+    pub sent: u128,
+    /// amount that the transfer is quoted to deliver. the transfer counts
+    /// towards the TVL with this amount until it arrives.
+    pub expected_out: u128,
+    /// destination balance before the transfer
+    pub baseline: u128,
+    /// smallest delivery that marks the arrival of the transfer
+    pub min_delivery: u128,
+    pub tx_hash: Option<String>,
+    /// seconds after which a transfer that never arrived stops being counted
+    pub expiry_secs: u64,
+}
+
+/// transfer that is yet to arrive to its destination
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InTransit {
+    pub id: u64,
+    /// unix timestamp of the transfer
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub transfer: OutboundTransfer,
+}
+
+impl InTransit {
+    /// destination balance that marks the arrival of the transfer
+    pub fn expected_balance(&self) -> u128 {
+        self.transfer.baseline + self.transfer.min_delivery
+    }
+
+    pub fn expired(&self, now: u64) -> bool {
+        now >= self.timestamp.saturating_add(self.transfer.expiry_secs)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LedgerState {
+    next_id: u64,
+    transfers: Vec<InTransit>,
+}
+
+/// transfers in flight between the domains. the ledger is persisted as a
+/// json file so that a restarted strategist keeps counting them, or kept
+/// in memory where nothing gets broadcast.
+pub struct TransitLedger {
+    path: Option<PathBuf>,
+    state: Mutex<LedgerState>,
+}
+
+impl TransitLedger {
+    /// opens (or creates) the ledger at the given path
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let state = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| anyhow!("failed to read transit ledger at {}: {e}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => LedgerState::default(),
+            Err(e) => {
+                return Err(anyhow!(
+                    "failed to open transit ledger at {}: {e}",
+                    path.display()
+                ))
+            }
+        };
+
+        Ok(Self {
+            path: Some(path),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// ledger that only lives as long as the strategy
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: Mutex::new(LedgerState::default()),
+        }
+    }
+
+    /// all transfers in transit, oldest first
+    pub fn outstanding(&self) -> anyhow::Result<Vec<InTransit>> {
+        Ok(self.lock()?.transfers.clone())
+    }
+
+    /// records the transfer as in transit and returns its id
+    pub fn record(&self, transfer: OutboundTransfer) -> anyhow::Result<u64> {
+        let timestamp = now()?;
+        self.update(|state| {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.transfers.push(InTransit {
+                id,
+                timestamp,
+                transfer,
+            });
+            id
+        })
+    }
+
+    /// removes the transfer once it arrived or expired
+    pub fn remove(&self, id: u64) -> anyhow::Result<()> {
+        self.update(|state| state.transfers.retain(|t| t.id != id))
+    }
+
+    /// accounts for funds that were moved out of the destination of the
+    /// transfers in transit, so that their arrival is still recognized
+    /// against the balance that is left behind
+    pub fn moved_out(
+        &self,
+        domain: Domain,
+        address: &str,
+        denom: &str,
+        amount: u128,
+    ) -> anyhow::Result<()> {
+        self.update(|state| {
+            for t in state.transfers.iter_mut().map(|t| &mut t.transfer) {
+                if t.domain == domain && t.address == address && t.denom == denom {
+                    t.baseline = t.baseline.saturating_sub(amount);
+                }
+            }
+        })
+    }
+
+    fn lock(&self) -> anyhow::Result<std::sync::MutexGuard<'_, LedgerState>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("transit ledger lock poisoned"))
+    }
+
+    /// applies the change and persists the ledger
+    fn update<T>(&self, f: impl FnOnce(&mut LedgerState) -> T) -> anyhow::Result<T> {
+        let mut state = self.lock()?;
+        let result = f(&mut state);
+
+        if let Some(path) = &self.path {
+            // write to a temporary file first, so that a crash mid-write
+            // never leaves a truncated ledger behind
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, serde_json::to_vec(&*state)?)?;
+            fs::rename(&tmp, path)?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// value of the transfers in transit, counted at their quoted amounts
+pub struct InTransitValuator {
+    transfers: Vec<InTransit>,
+}
+
+impl InTransitValuator {
+    pub fn new(transfers: Vec<InTransit>) -> Self {
+        Self { transfers }
+    }
+}
+
+#[async_trait]
+impl PositionValuator for InTransitValuator {
+    fn name(&self) -> String {
+        IN_TRANSIT_POSITION.to_string()
+    }
+
+    async fn value(&self) -> anyhow::Result<u128> {
+        self.transfers.iter().try_fold(0u128, |acc, t| {
+            acc.checked_add(t.transfer.expected_out)
+                .ok_or_else(|| anyhow!("in transit value overflow"))
+        })
+    }
+}
+
+impl Strategy {
+    /// drops the transfers that arrived to their destination, or expired
+    /// without arriving, from the ledger. balances are read at the
+    /// snapshot heights, if given.
+    pub(crate) async fn settle_in_transit(
+        &self,
+        phase: &str,
+        snapshot: Option<&Snapshot>,
+    ) -> anyhow::Result<()> {
+        let now = now()?;
+
+        for in_transit in self.transit.outstanding()? {
+            let t = &in_transit.transfer;
+
+            if in_transit.expired(now) {
+                warn!(
+                    target: phase,
+                    "{} transfer #{} of {} to {} expired without arriving; no longer counting it in transit",
+                    t.transfer, in_transit.id, t.expected_out, t.address
+                );
+                self.metrics.inc_counter(
+                    metrics::TRANSFERS_EXPIRED,
+                    &[("transfer", &t.transfer)],
+                    1.0,
+                );
+                self.transit.remove(in_transit.id)?;
+                continue;
+            }
+
+            let balance = self
+                .querier(t.domain)?
+                .query_balance(
+                    &t.address,
+                    &t.denom,
+                    snapshot.and_then(|s| s.height(t.domain)),
+                )
+                .await?;
+            if balance >= in_transit.expected_balance() {
+                info!(
+                    target: phase,
+                    "{} transfer #{} arrived to {} (balance = {balance})",
+                    t.transfer, in_transit.id, t.address
+                );
+                self.transit.remove(in_transit.id)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn now() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
@@ -0,0 +1,300 @@
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::{error, info, warn};
+use tokio::time::{self, sleep};
+use valence_strategist_utils::worker::ValenceWorker;
+
+use crate::{
+    phases::{
+        DEPOSIT_PHASE, REGISTRATION_PHASE, SENTRY_PHASE, SETTLEMENT_PHASE, UPDATE_PHASE,
+        VALENCE_WORKER,
+    },
+    strategist::{
+        cycle::{CycleSummary, PhaseStatus},
+        error::StrategistError,
+        layout::{Phase, PhaseCriticality, RetryPolicy},
+        phases::sentry::SentryOutcome,
+        plan::CyclePlan,
+        schedule::Preemption,
+        Strategy,
+    },
+    utils::metrics,
+};
+
+/// runs the phase under its retry policy, observing every attempt. only
+/// the failures classified as retryable get retried.
+macro_rules! run_phase {
+    ($strategy:ident, $phase:expr, $policy:ident, $run:ident) => {{
+        let mut attempt = 1;
+        loop {
+            let started = Instant::now();
+            let result = $strategy.$run().await;
+            $strategy.observe_phase($phase, started, &result);
+
+            match result {
+                Ok(value) => break Ok(value),
+                Err(e) => {
+                    let policy = $strategy.layout.retry.$policy;
+                    match $strategy.retry_backoff($phase, policy, attempt, &e) {
+                        Some(backoff) => {
+                            sleep(backoff).await;
+                            attempt += 1;
+                        }
+                        None => break Err(e),
+                    }
+                }
+            }
+        }
+    }};
+}
+
+// implement the ValenceWorker trait for the Strategy struct.
+// This trait defines the main loop of the strategy and inherits
+// the default implementation for spawning the worker.
+#[async_trait]
+impl ValenceWorker for Strategy {
+    fn get_name(&self) -> String {
+        format!("Valence X-Vault: {}", self.label)
+    }
+
+    async fn cycle(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        info!(target: VALENCE_WORKER, "{}: Starting cycle...", self.get_name());
+
+        if let Err(e) = self.run_phases().await {
+            let class = StrategistError::classify(&e);
+            error!(target: VALENCE_WORKER, "{}: cycle failed ({}): {e:#}", self.get_name(), class.kind());
+            self.metrics
+                .inc_counter(metrics::CYCLE_FAILURES, &[("kind", class.kind())], 1.0);
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Strategy {
+    /// runs a single cycle in dry-run mode and returns the plan of
+    /// everything that would have been broadcast
+    pub async fn dry_run_cycle(&mut self) -> anyhow::Result<CyclePlan> {
+        if self.dry_run.is_none() {
+            return Err(anyhow!("strategy was not initialized in dry-run mode"));
+        }
+
+        info!(target: VALENCE_WORKER, "{}: Starting dry-run cycle...", self.get_name());
+
+        let summary = self.run_phases().await?;
+
+        self.dry_run
+            .as_ref()
+            .map(|dry_run| CyclePlan {
+                summary,
+                ..dry_run.take_plan(&self.label)
+            })
+            .ok_or_else(|| anyhow!("dry-run recorder is missing"))
+    }
+
+    /// runs the sentry, followed by the phases in the order configured in
+    /// the cycle layout. a phase only runs once the phases it depends on
+    /// succeeded. failures of best-effort phases are only reported in the
+    /// cycle summary, while a failed mandatory phase fails the cycle once
+    /// the rest of the phases have run.
+    pub(crate) async fn run_phases(&mut self) -> anyhow::Result<CycleSummary> {
+        // go into sentry (pre-flight) phase
+        let outcome = run_phase!(self, SENTRY_PHASE, sentry, sentry)?;
+
+        // the cycle must not hold up the mandatory rate update. phases
+        // still running past it (e.g. polling for a deposit) stop at their
+        // next safe point, leaving their in-flight steps to the journal.
+        let mut update_deadline = match (outcome, &self.dry_run) {
+            (SentryOutcome::Cycle, None) => self
+                .update_due_in()
+                .await?
+                .map(|due_in| Preemption::at(time::Instant::now() + due_in)),
+            _ => None,
+        };
+
+        let mut summary = CycleSummary::default();
+        let mut mandatory_failure = None;
+        let mut preempted = false;
+
+        for spec in self.layout.cycle.phases.clone() {
+            let phase = spec.phase;
+
+            // only the phases ahead of the update get pre-empted
+            self.preemption = match phase {
+                Phase::Update => Preemption::default(),
+                _ => update_deadline.unwrap_or_default(),
+            };
+            if phase == Phase::Update {
+                update_deadline = None;
+            }
+
+            // if the sentry only let the cycle through to keep the rate from
+            // going stale, we go straight to the update
+            let skip_reason = if outcome == SentryOutcome::UpdateOnly && phase != Phase::Update {
+                Some("sentry only let the mandatory rate update through".to_string())
+            } else if (preempted || self.preemption.is_due()) && phase != Phase::Update {
+                Some("cycle was pre-empted by the mandatory rate update".to_string())
+            } else {
+                spec.depends_on
+                    .iter()
+                    .find(|dependency| !summary.succeeded(**dependency))
+                    .map(|dependency| {
+                        format!("depends on the {dependency} phase, which did not succeed")
+                    })
+            };
+            if let Some(reason) = skip_reason {
+                summary.record(phase, PhaseStatus::Skipped { reason });
+                continue;
+            }
+
+            let status = match self.run_cycle_phase(phase).await {
+                Ok(()) => PhaseStatus::Succeeded,
+                Err(e)
+                    if matches!(StrategistError::classify(&e), StrategistError::Preempted(_)) =>
+                {
+                    warn!(target: VALENCE_WORKER, "mandatory rate update is due; pre-empted the {phase} phase: {e:#}");
+                    self.metrics
+                        .inc_counter(metrics::UPDATE_PREEMPTIONS, &[], 1.0);
+                    preempted = true;
+                    PhaseStatus::Skipped {
+                        reason: "pre-empted by the mandatory rate update".to_string(),
+                    }
+                }
+                Err(e) => {
+                    let status = PhaseStatus::Failed {
+                        kind: StrategistError::classify(&e).kind().to_string(),
+                        error: format!("{e:#}"),
+                    };
+                    if spec.criticality == PhaseCriticality::Mandatory
+                        && mandatory_failure.is_none()
+                    {
+                        mandatory_failure = Some(e);
+                    }
+                    status
+                }
+            };
+            summary.record(phase, status);
+        }
+        self.preemption = Preemption::default();
+
+        for report in &summary.phases {
+            match &report.status {
+                PhaseStatus::Succeeded => {}
+                PhaseStatus::Failed { kind, error } => {
+                    warn!(target: VALENCE_WORKER, "{} phase failed ({kind}): {error}", report.phase)
+                }
+                PhaseStatus::Skipped { reason } => {
+                    warn!(target: VALENCE_WORKER, "{} phase skipped: {reason}", report.phase)
+                }
+            }
+        }
+        info!(target: VALENCE_WORKER, "cycle summary: {}", serde_json::to_string(&summary)?);
+
+        // with the rate posted, we pick up where the pre-empted phases
+        // left off
+        if preempted && summary.succeeded(Phase::Update) {
+            self.reconcile_journal().await?;
+        }
+
+        // the steps carried out this cycle no longer need replaying
+        if let Err(e) = self.journal.compact() {
+            warn!(target: VALENCE_WORKER, "failed to compact the cycle journal: {e:#}");
+        }
+
+        match mandatory_failure {
+            Some(e) => Err(e),
+            None => Ok(summary),
+        }
+    }
+
+    /// runs the phase under its retry policy
+    async fn run_cycle_phase(&mut self, phase: Phase) -> anyhow::Result<()> {
+        match phase {
+            Phase::Deposit => run_phase!(self, DEPOSIT_PHASE, deposit, deposit),
+            Phase::Registration => run_phase!(
+                self,
+                REGISTRATION_PHASE,
+                registration,
+                register_withdraw_obligations
+            ),
+            Phase::Settlement => run_phase!(self, SETTLEMENT_PHASE, settlement, settlement),
+            Phase::Update => run_phase!(self, UPDATE_PHASE, update, update).map(|_| ()),
+        }
+    }
+
+    /// records the phase duration and, if it failed, its error count by
+    /// the kind of failure
+    fn observe_phase<T>(&self, phase: &str, started: Instant, result: &anyhow::Result<T>) {
+        self.metrics.set_gauge(
+            metrics::PHASE_DURATION,
+            &[("phase", phase)],
+            started.elapsed().as_secs_f64(),
+        );
+        if let Err(e) = result {
+            let class = StrategistError::classify(e);
+            // pre-emptions are counted on their own
+            if matches!(class, StrategistError::Preempted(_)) {
+                return;
+            }
+            self.metrics.inc_counter(
+                metrics::PHASE_ERRORS,
+                &[("phase", phase), ("kind", class.kind())],
+                1.0,
+            );
+        }
+    }
+
+    /// classifies the phase failure and returns the backoff before the next
+    /// attempt, if the failure is retryable and the policy allows for it.
+    /// dry runs are never retried, as they would record the plan twice.
+    fn retry_backoff(
+        &self,
+        phase: &str,
+        policy: RetryPolicy,
+        attempt: u32,
+        err: &anyhow::Error,
+    ) -> Option<Duration> {
+        let class = StrategistError::classify(err);
+        if self.dry_run.is_some() || !class.is_retryable() {
+            warn!(target: VALENCE_WORKER, "{phase} phase failed ({}): {err:#}", class.kind());
+            return None;
+        }
+
+        let Some(backoff) = policy.backoff(attempt) else {
+            warn!(
+                target: VALENCE_WORKER,
+                "{phase} phase failed ({}) after {attempt} attempts: {err:#}",
+                class.kind()
+            );
+            return None;
+        };
+
+        // a retry past the update deadline would only get pre-empted
+        if self.preemption.is_due_within(backoff) {
+            warn!(
+                target: VALENCE_WORKER,
+                "{phase} phase failed ({}): {err:#}; not retrying past the rate update deadline",
+                class.kind()
+            );
+            return None;
+        }
+
+        warn!(
+            target: VALENCE_WORKER,
+            "{phase} phase failed ({}) on attempt {attempt}/{}: {err:#}; retrying in {}sec",
+            class.kind(),
+            policy.max_attempts,
+            backoff.as_secs()
+        );
+        self.metrics
+            .inc_counter(metrics::PHASE_RETRIES, &[("phase", phase)], 1.0);
+
+        Some(backoff)
+    }
+}
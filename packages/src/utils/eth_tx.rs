@@ -0,0 +1,210 @@
+use std::{collections::HashMap, fmt, sync::OnceLock, time::Duration};
+
+use alloy::{
+    dyn_abi::JsonAbiExt,
+    hex,
+    json_abi::{Error as AbiError, JsonAbi},
+    primitives::{Selector, TxHash},
+    rpc::types::TransactionRequest,
+    sol_types::decode_revert_reason,
+};
+use log::{info, warn};
+use serde_json::Value;
+use tokio::time::sleep;
+
+use crate::strategist::clients::EthereumDomain;
+
+/// confirmations awaited before a transaction is considered final, unless
+/// configured otherwise
+pub const DEFAULT_CONFIRMATIONS: u64 = 1;
+
+/// interval between the receipt polls
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// receipt polls before the transaction is given up on (5min)
+const RECEIPT_MAX_ATTEMPTS: u32 = 100;
+
+const ETH_TX: &str = "eth_tx";
+
+/// contract artifacts whose custom errors are decoded from revert data
+const BUNDLED_ARTIFACTS: [&str; 8] = [
+    include_str!("../contracts/evm/Authorization.sol/Authorization.json"),
+    include_str!("../contracts/evm/OneWayVault.sol/OneWayVault.json"),
+    include_str!("../contracts/evm/LiteProcessor.sol/LiteProcessor.json"),
+    include_str!("../contracts/evm/BaseAccount.sol/BaseAccount.json"),
+    include_str!("../contracts/evm/IBCEurekaTransfer.sol/IBCEurekaTransfer.json"),
+    include_str!("../contracts/evm/CCTPTransfer.sol/CCTPTransfer.json"),
+    include_str!("../contracts/evm/ERC20.sol/ERC20.json"),
+    include_str!("../contracts/evm/ERC1967Proxy.sol/ERC1967Proxy.json"),
+];
+
+/// failed Ethereum transaction submission
+#[derive(Debug)]
+pub enum EthTxError {
+    /// transaction could not be signed or broadcast
+    Submission(anyhow::Error),
+    /// transaction did not get (enough confirmations) into the chain in time.
+    /// it may still land.
+    Unconfirmed { tx_hash: TxHash, confirmations: u64 },
+    /// transaction was included, but reverted
+    Reverted {
+        tx_hash: TxHash,
+        block: u64,
+        /// decoded revert reason, if it could be obtained
+        reason: Option<String>,
+    },
+}
+
+impl fmt::Display for EthTxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EthTxError::Submission(e) => write!(f, "failed to submit the transaction: {e}"),
+            EthTxError::Unconfirmed {
+                tx_hash,
+                confirmations,
+            } => write!(
+                f,
+                "transaction {tx_hash} did not reach {confirmations} confirmations in time"
+            ),
+            EthTxError::Reverted {
+                tx_hash,
+                block,
+                reason,
+            } => write!(
+                f,
+                "transaction {tx_hash} reverted in block {block}: {}",
+                reason.as_deref().unwrap_or("unknown reason")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EthTxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EthTxError::Submission(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// signs and broadcasts the transaction, returning its hash
+pub async fn broadcast(
+    client: &dyn EthereumDomain,
+    request: &TransactionRequest,
+) -> Result<TxHash, EthTxError> {
+    let tx_hash = client
+        .send(request.clone())
+        .await
+        .map_err(EthTxError::Submission)?;
+    info!(target: ETH_TX, "sent transaction {tx_hash}");
+
+    Ok(tx_hash)
+}
+
+/// waits for the receipt of the broadcast transaction to reach the given
+/// number of confirmations. reverted transactions are replayed to obtain
+/// their revert reason.
+pub async fn confirm(
+    client: &dyn EthereumDomain,
+    request: TransactionRequest,
+    tx_hash: TxHash,
+    confirmations: u64,
+) -> Result<TxHash, EthTxError> {
+    info!(target: ETH_TX, "awaiting {confirmations} confirmations of {tx_hash}");
+
+    for attempt in 1..=RECEIPT_MAX_ATTEMPTS {
+        // rpc hiccups are retried until we run out of attempts, as the
+        // transaction is already out there
+        let confirmed = match client.receipt(tx_hash).await {
+            Ok(Some(receipt)) if !receipt.success => {
+                // replaying the transaction on top of the preceding block
+                // approximates the state it was executed against
+                let reason = match client
+                    .revert_data(
+                        request.clone().from(receipt.from),
+                        receipt.block_number.saturating_sub(1),
+                    )
+                    .await
+                {
+                    Ok(data) => data.map(|data| decode_revert(&data)),
+                    Err(e) => {
+                        warn!(target: ETH_TX, "failed to replay reverted transaction {tx_hash}: {e}");
+                        None
+                    }
+                };
+
+                return Err(EthTxError::Reverted {
+                    tx_hash,
+                    block: receipt.block_number,
+                    reason,
+                });
+            }
+            Ok(Some(receipt)) => match client.block_number().await {
+                Ok(latest) => latest.saturating_sub(receipt.block_number) + 1 >= confirmations,
+                Err(e) => {
+                    warn!(target: ETH_TX, "failed to query the latest block: {e}");
+                    false
+                }
+            },
+            Ok(None) => false,
+            Err(e) => {
+                warn!(target: ETH_TX, "failed to query the receipt of {tx_hash}: {e}");
+                false
+            }
+        };
+
+        if confirmed {
+            info!(target: ETH_TX, "transaction {tx_hash} confirmed");
+            return Ok(tx_hash);
+        }
+
+        if attempt < RECEIPT_MAX_ATTEMPTS {
+            sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+    }
+
+    Err(EthTxError::Unconfirmed {
+        tx_hash,
+        confirmations,
+    })
+}
+
+/// decodes the revert data into a readable reason. custom errors of the
+/// bundled contracts are decoded along with their arguments, followed by
+/// the standard `Error(string)` and `Panic(uint256)` reverts.
+pub fn decode_revert(data: &[u8]) -> String {
+    if let Some(error) = data
+        .get(..4)
+        .and_then(|selector| bundled_errors().get(&Selector::from_slice(selector)))
+    {
+        return match error.abi_decode_input(&data[4..], true) {
+            Ok(args) => {
+                let args: Vec<String> = args.iter().map(|arg| format!("{arg:?}")).collect();
+                format!("{}({})", error.name, args.join(", "))
+            }
+            Err(_) => error.signature(),
+        };
+    }
+
+    decode_revert_reason(data).unwrap_or_else(|| format!("0x{}", hex::encode(data)))
+}
+
+/// custom errors declared across the bundled contract ABIs, by selector
+fn bundled_errors() -> &'static HashMap<Selector, AbiError> {
+    static ERRORS: OnceLock<HashMap<Selector, AbiError>> = OnceLock::new();
+
+    ERRORS.get_or_init(|| {
+        let mut errors = HashMap::new();
+        for artifact in BUNDLED_ARTIFACTS {
+            let abi = serde_json::from_str::<Value>(artifact)
+                .ok()
+                .and_then(|artifact| {
+                    serde_json::from_value::<JsonAbi>(artifact["abi"].clone()).ok()
+                });
+            for error in abi.iter().flat_map(|abi| abi.errors()) {
+                errors.insert(error.selector(), error.clone());
+            }
+        }
+        errors
+    })
+}
@@ -0,0 +1,168 @@
+use anyhow::anyhow;
+use cosmrs::proto::{
+    cosmos::{
+        bank::v1beta1::{query_client::QueryClient as BankQueryClient, QueryBalanceRequest},
+        base::tendermint::v1beta1::{
+            service_client::ServiceClient as TendermintServiceClient, GetLatestBlockRequest,
+        },
+    },
+    cosmwasm::wasm::v1::{
+        query_client::QueryClient as WasmQueryClient, QuerySmartContractStateRequest,
+    },
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tonic::{
+    transport::{Channel, ClientTlsConfig},
+    Request,
+};
+
+/// grpc metadata key that cosmos-sdk nodes use to serve queries at a
+/// specific block height
+const BLOCK_HEIGHT_METADATA_KEY: &str = "x-cosmos-block-height";
+
+/// read-only grpc querier of a cosmos chain whose queries can be served at
+/// a given block height. the domain clients always query the latest state,
+/// which makes it impossible to derive consistent values from multiple
+/// queries.
+///
+/// every query takes an optional height. `None` queries the latest state.
+#[derive(Clone)]
+pub struct GrpcQuerier {
+    channel: Channel,
+}
+
+impl GrpcQuerier {
+    /// connects to the grpc endpoint of the chain
+    pub fn connect(grpc_url: &str, grpc_port: &str) -> anyhow::Result<Self> {
+        let endpoint = format!("{grpc_url}:{grpc_port}");
+
+        let mut channel = Channel::from_shared(endpoint.clone())
+            .map_err(|e| anyhow!("invalid grpc endpoint {endpoint}: {e}"))?;
+        if grpc_url.starts_with("https") {
+            channel = channel.tls_config(ClientTlsConfig::new().with_native_roots())?;
+        }
+
+        // connect lazily so that an unreachable node only fails the queries,
+        // not the strategist initialization
+        Ok(Self {
+            channel: channel.connect_lazy(),
+        })
+    }
+
+    /// queries the latest block height of the chain
+    pub async fn latest_height(&self) -> anyhow::Result<u64> {
+        let response = TendermintServiceClient::new(self.channel.clone())
+            .get_latest_block(GetLatestBlockRequest {})
+            .await?
+            .into_inner();
+
+        let height = response
+            .sdk_block
+            .and_then(|block| block.header)
+            .map(|header| header.height)
+            .or_else(|| {
+                response
+                    .block
+                    .and_then(|block| block.header)
+                    .map(|header| header.height)
+            })
+            .ok_or_else(|| anyhow!("latest block response is missing the header"))?;
+
+        Ok(u64::try_from(height)?)
+    }
+
+    /// queries the bank balance of the given address
+    pub async fn query_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        height: Option<u64>,
+    ) -> anyhow::Result<u128> {
+        let request = at_height(
+            QueryBalanceRequest {
+                address: address.to_string(),
+                denom: denom.to_string(),
+            },
+            height,
+        )?;
+
+        let balance = BankQueryClient::new(self.channel.clone())
+            .balance(request)
+            .await?
+            .into_inner()
+            .balance;
+
+        match balance {
+            Some(coin) => Ok(coin.amount.parse()?),
+            None => Ok(0),
+        }
+    }
+
+    /// performs a smart query of the given contract
+    pub async fn query_contract_state<T: DeserializeOwned>(
+        &self,
+        contract: &str,
+        query: impl Serialize,
+        height: Option<u64>,
+    ) -> anyhow::Result<T> {
+        let response = self
+            .query_contract_raw(contract, serde_json::to_vec(&query)?, height)
+            .await?;
+
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    /// performs a smart query of the given contract with the json-encoded
+    /// query message, returning the json-encoded response
+    pub async fn query_contract_raw(
+        &self,
+        contract: &str,
+        query: Vec<u8>,
+        height: Option<u64>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let request = at_height(
+            QuerySmartContractStateRequest {
+                address: contract.to_string(),
+                query_data: query,
+            },
+            height,
+        )?;
+
+        let response = WasmQueryClient::new(self.channel.clone())
+            .smart_contract_state(request)
+            .await?
+            .into_inner();
+
+        Ok(response.data)
+    }
+}
+
+/// wraps the message into a request served at the given height
+fn at_height<T>(message: T, height: Option<u64>) -> anyhow::Result<Request<T>> {
+    let mut request = Request::new(message);
+
+    if let Some(height) = height {
+        request
+            .metadata_mut()
+            .insert(BLOCK_HEIGHT_METADATA_KEY, height.to_string().parse()?);
+    }
+
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_is_served_at_the_pinned_height() {
+        let request = at_height(GetLatestBlockRequest {}, Some(42)).unwrap();
+        assert_eq!(
+            request.metadata().get(BLOCK_HEIGHT_METADATA_KEY).unwrap(),
+            "42"
+        );
+
+        let request = at_height(GetLatestBlockRequest {}, None).unwrap();
+        assert!(request.metadata().get(BLOCK_HEIGHT_METADATA_KEY).is_none());
+    }
+}
@@ -1,80 +1,391 @@
 use anyhow::anyhow;
-use log::info;
-use valence_domain_clients::{clients::neutron::NeutronClient, cosmos::wasm_client::WasmClient};
+use async_trait::async_trait;
+use cosmwasm_std::{Coin, Decimal, Uint128};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
 use valence_lending_utils::mars::{Account, Positions, QueryMsg};
 
-use crate::phases::UPDATE_PHASE;
+use crate::{
+    phases::UPDATE_PHASE,
+    strategist::{
+        clients::{ContractQuerier, CosmosQuerier},
+        error::StrategistError,
+    },
+    utils::{
+        metrics::{self, Metrics},
+        valuation::PositionValuator,
+    },
+};
 
-pub async fn query_mars_lending_denom_amount(
-    client: &NeutronClient,
+/// positions in a single denom, summed up across all of the credit
+/// accounts of an owner
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarsPosition {
+    pub accounts: usize,
+    pub lends: u128,
+    pub deposits: u128,
+    pub debts: u128,
+    /// debts in other denoms, which cannot be valued in the position denom
+    pub foreign_debts: Vec<Coin>,
+}
+
+impl MarsPosition {
+    /// lends and deposits less the debts
+    pub fn net_value(&self) -> anyhow::Result<u128> {
+        self.lends
+            .checked_add(self.deposits)
+            .ok_or_else(|| anyhow!("mars position value overflow"))?
+            .checked_sub(self.debts)
+            .ok_or_else(|| {
+                anyhow!(
+                    "mars debts of {} exceed the lends and deposits of {}",
+                    self.debts,
+                    self.lends + self.deposits
+                )
+            })
+    }
+}
+
+/// number of credit accounts queried per page
+const ACCOUNTS_PAGE_LIMIT: u32 = 10;
+
+/// credit manager queries that the mars lending library does not expose.
+/// there is no mars types crate in the workspace, so the messages are
+/// mirrored here.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum CreditManagerQueryMsg {
+    Config {},
+}
+
+/// red bank queries read by the strategist
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum RedBankQueryMsg {
+    MarketV2 { denom: String },
+}
+
+/// credit manager config fields read by the strategist
+#[derive(Deserialize, Debug)]
+pub struct CreditManagerConfig {
+    pub red_bank: String,
+}
+
+/// red bank market fields read by the strategist
+#[derive(Deserialize, Debug)]
+pub struct RedBankMarket {
+    pub denom: String,
+    /// lend (deposit) interest rate
+    pub liquidity_rate: Decimal,
+    pub borrow_rate: Decimal,
+    /// share of the deposited amount that is borrowed out
+    pub utilization_rate: Decimal,
+    /// total amount deposited into the market
+    pub collateral_total_amount: Uint128,
+    /// total amount borrowed out of the market
+    pub debt_total_amount: Uint128,
+}
+
+impl RedBankMarket {
+    /// amount that is not borrowed out, and so can be withdrawn
+    pub fn available_liquidity(&self) -> u128 {
+        self.collateral_total_amount
+            .saturating_sub(self.debt_total_amount)
+            .u128()
+    }
+
+    /// lend rate compounded over a year. the red bank accrues interest
+    /// every time the market is touched, so it is compounded continuously.
+    pub fn lend_apy(&self) -> f64 {
+        let rate: f64 = self.liquidity_rate.to_string().parse().unwrap_or_default();
+        rate.exp_m1()
+    }
+}
+
+/// net Mars position of all the credit accounts owned by `owner`,
+/// expressed in the lent denom
+pub struct MarsLendingValuator<'a> {
+    querier: &'a dyn CosmosQuerier,
+    /// height the position is valued at. `None` values the latest state.
+    height: Option<u64>,
+    credit_manager: String,
+    owner: String,
+    denom: String,
+    /// whether debts in other denoms are left out of the value, rather
+    /// than failing it
+    exclude_foreign_debts: bool,
+    metrics: &'a Metrics,
+}
+
+impl<'a> MarsLendingValuator<'a> {
+    pub fn new(
+        querier: &'a dyn CosmosQuerier,
+        height: Option<u64>,
+        credit_manager: &str,
+        owner: &str,
+        denom: &str,
+        exclude_foreign_debts: bool,
+        metrics: &'a Metrics,
+    ) -> Self {
+        Self {
+            querier,
+            height,
+            credit_manager: credit_manager.to_string(),
+            owner: owner.to_string(),
+            denom: denom.to_string(),
+            exclude_foreign_debts,
+            metrics,
+        }
+    }
+
+    /// alerts on the debts in other denoms, which there is no price for.
+    /// leaving them out overstates the position, so unless the strategy is
+    /// configured to exclude them, they fail the valuation.
+    fn check_foreign_debts(&self, foreign_debts: &[Coin]) -> anyhow::Result<()> {
+        for debt in foreign_debts {
+            error!(
+                target: UPDATE_PHASE,
+                "mars {} owes {debt}, which cannot be valued in {}", self.owner, self.denom
+            );
+            self.metrics.set_gauge(
+                metrics::MARS_FOREIGN_DEBTS,
+                &[("owner", &self.owner), ("denom", &debt.denom)],
+                debt.amount.u128() as f64,
+            );
+        }
+
+        match foreign_debts.first() {
+            Some(debt) if !self.exclude_foreign_debts => Err(StrategistError::InvariantViolated(
+                format!(
+                    "mars {} owes {debt}, which cannot be valued in {}; set exclude_foreign_mars_debts to value the position without it",
+                    self.owner, self.denom
+                ),
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl PositionValuator for MarsLendingValuator<'_> {
+    fn name(&self) -> String {
+        format!("mars {} {} lend", self.owner, self.denom)
+    }
+
+    async fn value(&self) -> anyhow::Result<u128> {
+        let position = query_mars_position(
+            self.querier,
+            &self.credit_manager,
+            &self.owner,
+            &self.denom,
+            self.height,
+        )
+        .await?;
+
+        info!(
+            target: UPDATE_PHASE,
+            "mars {} {} position across {} credit accounts: lends={}, deposits={}, debts={}",
+            self.owner, self.denom, position.accounts, position.lends, position.deposits, position.debts
+        );
+        self.check_foreign_debts(&position.foreign_debts)?;
+
+        position.net_value()
+    }
+}
+
+/// lends, deposits and debts of `denom` summed up across all of the credit
+/// accounts of `acc_owner`, queried at the given height (or the latest one,
+/// if `None`). an owner without any credit account has an empty position.
+///
+/// positions in other denoms cannot be valued in `denom`, so lends and
+/// deposits of them are left out, while debts of them are reported
+/// separately, for the caller to decide on.
+pub async fn query_mars_position(
+    client: &dyn CosmosQuerier,
     credit_manager: &str,
     acc_owner: &str,
     denom: &str,
-) -> anyhow::Result<u128> {
-    // get the first credit account. while credit accounts are returned as a vec,
-    // mars lending library should only ever create one credit account and re-use it
-    // for all LP actions, so we get the [0]
+    height: Option<u64>,
+) -> anyhow::Result<MarsPosition> {
     let mars_credit_accounts =
-        query_mars_credit_accounts(client, credit_manager, acc_owner).await?;
-
-    info!(target: UPDATE_PHASE, "credit accounts: {:?}", mars_credit_accounts);
+        query_mars_credit_accounts(client, credit_manager, acc_owner, height).await?;
 
-    let first_credit_account = mars_credit_accounts
-        .first()
-        .ok_or_else(|| anyhow!("no credit account found for owner {acc_owner}"))?;
+    let mut position = MarsPosition {
+        accounts: mars_credit_accounts.len(),
+        ..Default::default()
+    };
 
-    let active_positions = query_mars_credit_account_positions(
-        client,
-        credit_manager,
-        first_credit_account.id.to_string(),
-    )
-    .await?;
+    for account in mars_credit_accounts {
+        let positions =
+            query_mars_credit_account_positions(client, credit_manager, account.id.clone(), height)
+                .await?;
 
-    info!(target: UPDATE_PHASE, "active first credit account positions: {:?}", active_positions);
+        info!(target: UPDATE_PHASE, "credit account {} positions: {:?}", account.id, positions);
 
-    // iterate over active lending positions until the target denom is found
-    for lend in active_positions.lends {
-        if lend.denom == denom {
-            return Ok(lend.amount.u128());
+        for debt in positions.debts.iter().filter(|debt| debt.denom != denom) {
+            warn!(
+                target: UPDATE_PHASE,
+                "credit account {} owes {debt}, which cannot be valued in {denom}", account.id
+            );
+            match position
+                .foreign_debts
+                .iter_mut()
+                .find(|coin| coin.denom == debt.denom)
+            {
+                Some(coin) => coin.amount += debt.amount,
+                None => position.foreign_debts.push(debt.clone()),
+            }
         }
+        for coin in positions.lends.iter().chain(&positions.deposits) {
+            if coin.denom != denom {
+                warn!(
+                    target: UPDATE_PHASE,
+                    "credit account {} holds {coin}, which is not valued in {denom}", account.id
+                );
+            }
+        }
+
+        position.lends += sum_denom(&positions.lends, denom);
+        position.deposits += sum_denom(&positions.deposits, denom);
+        position.debts += positions
+            .debts
+            .iter()
+            .filter(|debt| debt.denom == denom)
+            .map(|debt| debt.amount.u128())
+            .sum::<u128>();
     }
 
-    // if target denom was not among the active lending positions,
-    // we return an error to prevent incorrect accounting
-    Err(anyhow::format_err!(
-        "no {denom} active lending positions found"
-    ))
+    Ok(position)
 }
 
+fn sum_denom(coins: &[Coin], denom: &str) -> u128 {
+    coins
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount.u128())
+        .sum()
+}
+
+/// all of the credit accounts owned by `acc_owner`, page by page
 async fn query_mars_credit_accounts(
-    client: &NeutronClient,
+    client: &dyn CosmosQuerier,
     credit_manager: &str,
     acc_owner: &str,
+    height: Option<u64>,
 ) -> anyhow::Result<Vec<Account>> {
-    // query the mars credit account created and owned by the mars input account
-    let mars_input_acc_credit_accounts: Vec<Account> = client
-        .query_contract_state(
-            credit_manager,
-            QueryMsg::Accounts {
-                owner: acc_owner.to_string(),
-                start_after: None,
-                limit: None,
-            },
-        )
-        .await?;
+    let mut accounts: Vec<Account> = vec![];
+
+    loop {
+        let page: Vec<Account> = client
+            .query_contract_state(
+                credit_manager,
+                QueryMsg::Accounts {
+                    owner: acc_owner.to_string(),
+                    start_after: accounts.last().map(|account| account.id.clone()),
+                    limit: Some(ACCOUNTS_PAGE_LIMIT),
+                },
+                height,
+            )
+            .await?;
 
-    Ok(mars_input_acc_credit_accounts)
+        let last_page = page.len() < ACCOUNTS_PAGE_LIMIT as usize;
+        accounts.extend(page);
+        if last_page {
+            return Ok(accounts);
+        }
+    }
 }
 
 async fn query_mars_credit_account_positions(
-    client: &NeutronClient,
+    client: &dyn CosmosQuerier,
     credit_manager: &str,
     account_id: String,
+    height: Option<u64>,
 ) -> anyhow::Result<Positions> {
     // query mars positions owned by the credit account id
     let mars_positions_response: Positions = client
-        .query_contract_state(credit_manager, QueryMsg::Positions { account_id })
+        .query_contract_state(credit_manager, QueryMsg::Positions { account_id }, height)
         .await?;
 
     Ok(mars_positions_response)
 }
+
+/// address of the red bank that the credit manager lends into
+pub async fn query_mars_red_bank(
+    client: &dyn CosmosQuerier,
+    credit_manager: &str,
+    height: Option<u64>,
+) -> anyhow::Result<String> {
+    let config: CreditManagerConfig = client
+        .query_contract_state(credit_manager, CreditManagerQueryMsg::Config {}, height)
+        .await?;
+
+    Ok(config.red_bank)
+}
+
+/// red bank market state of `denom`, queried at the given height (or the
+/// latest one, if `None`)
+pub async fn query_red_bank_market(
+    client: &dyn CosmosQuerier,
+    red_bank: &str,
+    denom: &str,
+    height: Option<u64>,
+) -> anyhow::Result<RedBankMarket> {
+    client
+        .query_contract_state(
+            red_bank,
+            RedBankQueryMsg::MarketV2 {
+                denom: denom.to_string(),
+            },
+            height,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_value_takes_the_debts_off_the_lends_and_deposits() {
+        let position = MarsPosition {
+            accounts: 2,
+            lends: 700,
+            deposits: 300,
+            debts: 250,
+            ..Default::default()
+        };
+
+        assert_eq!(position.net_value().unwrap(), 750);
+        assert_eq!(MarsPosition::default().net_value().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_net_value_fails_on_debts_exceeding_the_position() {
+        let position = MarsPosition {
+            accounts: 1,
+            lends: 100,
+            deposits: 50,
+            debts: 151,
+            ..Default::default()
+        };
+
+        let err = position.net_value().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("debts of 151 exceed the lends and deposits of 150"));
+    }
+
+    #[test]
+    fn test_net_value_fails_on_overflow() {
+        let position = MarsPosition {
+            accounts: 1,
+            lends: u128::MAX,
+            deposits: 1,
+            ..Default::default()
+        };
+
+        assert!(position.net_value().is_err());
+    }
+}
@@ -1,5 +1,13 @@
+use async_trait::async_trait;
 use cosmwasm_std::Uint128;
-use valence_domain_clients::{clients::neutron::NeutronClient, cosmos::wasm_client::WasmClient};
+use futures::future::try_join_all;
+use log::info;
+
+use crate::{
+    phases::UPDATE_PHASE,
+    strategist::clients::{ContractQuerier, CosmosQuerier},
+    utils::valuation::PositionValuator,
+};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -14,17 +22,71 @@ struct SimulateDepositResponse {
 }
 
 pub async fn query_maxbtc_simulate_deposit(
-    client: &NeutronClient,
+    client: &dyn CosmosQuerier,
     maxbtc_contract: &str,
     amount: u128,
+    height: Option<u64>,
 ) -> anyhow::Result<u128> {
     let simulate_deposit = QueryMsg::SimulateDeposit {
         amount: Uint128::new(amount),
     };
 
     let response: SimulateDepositResponse = client
-        .query_contract_state(maxbtc_contract, simulate_deposit)
+        .query_contract_state(maxbtc_contract, simulate_deposit, height)
         .await?;
 
     Ok(response.minted_amount.u128())
 }
+
+/// deposit token held across a set of source positions, valued as the
+/// amount of maxBTC that issuing it would mint. sources are summed up
+/// first so that the issuance gets simulated only once.
+pub struct MaxBtcIssuanceValuator<'a> {
+    querier: &'a dyn CosmosQuerier,
+    /// height the position is valued at. `None` values the latest state.
+    height: Option<u64>,
+    maxbtc_contract: String,
+    sources: Vec<Box<dyn PositionValuator + 'a>>,
+}
+
+impl<'a> MaxBtcIssuanceValuator<'a> {
+    pub fn new(
+        querier: &'a dyn CosmosQuerier,
+        height: Option<u64>,
+        maxbtc_contract: &str,
+        sources: Vec<Box<dyn PositionValuator + 'a>>,
+    ) -> Self {
+        Self {
+            querier,
+            height,
+            maxbtc_contract: maxbtc_contract.to_string(),
+            sources,
+        }
+    }
+}
+
+#[async_trait]
+impl PositionValuator for MaxBtcIssuanceValuator<'_> {
+    fn name(&self) -> String {
+        let sources: Vec<String> = self.sources.iter().map(|s| s.name()).collect();
+        format!("maxbtc issuance of [{}]", sources.join(", "))
+    }
+
+    async fn value(&self) -> anyhow::Result<u128> {
+        let source_values = try_join_all(self.sources.iter().map(|source| async move {
+            let source_value = source.value().await?;
+            info!(target: UPDATE_PHASE, "maxbtc issuance source {} = {source_value}", source.name());
+            anyhow::Ok(source_value)
+        }))
+        .await?;
+        let deposit_token_total: u128 = source_values.iter().sum();
+
+        query_maxbtc_simulate_deposit(
+            self.querier,
+            &self.maxbtc_contract,
+            deposit_token_total,
+            self.height,
+        )
+        .await
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+};
+
+use log::{info, warn};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+const METRICS: &str = "metrics";
+
+/// value of every registered position queried in the update phase
+pub const POSITION_BALANCE: &str = "strategist_position_balance";
+/// block height that the latest rate calculation queried each domain at
+pub const VALUATION_HEIGHT: &str = "strategist_valuation_height";
+/// redemption rate computed by the update phase
+pub const COMPUTED_RATE: &str = "strategist_computed_redemption_rate";
+/// redemption rate currently posted on the vault
+pub const POSTED_RATE: &str = "strategist_posted_redemption_rate";
+/// unix timestamp of the last successful `OneWayVault.update`
+pub const LAST_RATE_UPDATE: &str = "strategist_last_rate_update_timestamp_seconds";
+/// max delay between rate updates before the vault pauses itself
+pub const MAX_RATE_UPDATE_DELAY: &str = "strategist_max_rate_update_delay_seconds";
+/// seconds left before the vault pauses itself over a stale rate
+pub const STALE_RATE_PAUSE_IN: &str = "strategist_stale_rate_pause_in_seconds";
+/// number of rate updates skipped over a rate anomaly
+pub const RATE_UPDATES_SKIPPED: &str = "strategist_rate_updates_skipped_total";
+/// number of cycles cut short to post a rate update before it goes stale
+pub const UPDATE_PREEMPTIONS: &str = "strategist_update_preemptions_total";
+/// number of pending obligations in the clearing queue
+pub const CLEARING_QUEUE_LENGTH: &str = "strategist_clearing_queue_length";
+/// number of withdraw obligations registered into the clearing queue
+pub const OBLIGATIONS_REGISTERED: &str = "strategist_obligations_registered_total";
+/// number of withdraw obligations settled from the clearing queue
+pub const OBLIGATIONS_SETTLED: &str = "strategist_obligations_settled_total";
+/// number of processor executions that withdraw obligations were settled in
+pub const SETTLEMENT_BATCHES: &str = "strategist_settlement_batches_total";
+/// number of obligations settled per processor execution
+pub const SETTLEMENT_BATCH_SIZE: &str = "strategist_settlement_batch_size";
+/// number of settlements that the Mars market liquidity fell short for
+pub const MARS_LIQUIDITY_SHORTFALLS: &str = "strategist_mars_liquidity_shortfalls_total";
+/// debts of the Mars credit accounts in denoms that cannot be valued
+pub const MARS_FOREIGN_DEBTS: &str = "strategist_mars_foreign_debts";
+/// current lend APY of every Mars market that the strategy lends into
+pub const MARS_LEND_APY: &str = "strategist_mars_lend_apy";
+/// supervault APY derived from the growth of its LP share value
+pub const SUPERVAULT_APY: &str = "strategist_supervault_apy";
+/// number of Mars vs supervault split updates proposed to the owner
+pub const REBALANCE_PROPOSALS: &str = "strategist_rebalance_proposals_total";
+/// duration of the latest run of every phase
+pub const PHASE_DURATION: &str = "strategist_phase_duration_seconds";
+/// number of failed runs of every phase, by kind of failure
+pub const PHASE_ERRORS: &str = "strategist_phase_errors_total";
+/// number of phase runs retried after a retryable failure
+pub const PHASE_RETRIES: &str = "strategist_phase_retries_total";
+/// number of failed cycles, by kind of failure
+pub const CYCLE_FAILURES: &str = "strategist_cycle_failures_total";
+/// share of the quoted amount delivered by the latest transfer of every route
+pub const TRANSFER_DELIVERY: &str = "strategist_transfer_delivery_ratio";
+/// number of transfers that delivered less than their quote allows for
+pub const TRANSFER_SHORTFALLS: &str = "strategist_transfer_shortfalls_total";
+/// number of transfers that stopped being counted in transit without arriving
+pub const TRANSFERS_EXPIRED: &str = "strategist_transfers_expired_total";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetricKind {
+    Gauge,
+    Counter,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Gauge => "gauge",
+            MetricKind::Counter => "counter",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MetricFamily {
+    kind: MetricKind,
+    help: &'static str,
+    /// rendered label set -> value
+    samples: BTreeMap<String, f64>,
+}
+
+/// minimal in-process registry of strategist gauges and counters,
+/// rendered in the prometheus text exposition format
+#[derive(Debug, Default)]
+pub struct Metrics {
+    families: Mutex<BTreeMap<&'static str, MetricFamily>>,
+}
+
+impl Metrics {
+    /// sets the gauge to the given value
+    pub fn set_gauge(&self, name: &'static str, labels: &[(&str, &str)], value: f64) {
+        self.update(name, MetricKind::Gauge, labels, |v| *v = value);
+    }
+
+    /// increments the counter by the given amount
+    pub fn inc_counter(&self, name: &'static str, labels: &[(&str, &str)], by: f64) {
+        self.update(name, MetricKind::Counter, labels, |v| *v += by);
+    }
+
+    /// renders all metrics in the prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let Ok(families) = self.families.lock() else {
+            return out;
+        };
+
+        for (name, family) in families.iter() {
+            let _ = writeln!(out, "# HELP {name} {}", family.help);
+            let _ = writeln!(out, "# TYPE {name} {}", family.kind.as_str());
+            for (labels, value) in &family.samples {
+                let _ = writeln!(out, "{name}{labels} {value}");
+            }
+        }
+
+        out
+    }
+
+    fn update(
+        &self,
+        name: &'static str,
+        kind: MetricKind,
+        labels: &[(&str, &str)],
+        f: impl FnOnce(&mut f64),
+    ) {
+        let Ok(mut families) = self.families.lock() else {
+            return;
+        };
+
+        let family = families.entry(name).or_insert_with(|| MetricFamily {
+            kind,
+            help: help(name),
+            samples: BTreeMap::new(),
+        });
+
+        f(family.samples.entry(render_labels(labels)).or_insert(0.0));
+    }
+}
+
+fn help(name: &str) -> &'static str {
+    match name {
+        POSITION_BALANCE => "value of the position queried in the update phase",
+        VALUATION_HEIGHT => "block height that the latest rate calculation queried at",
+        COMPUTED_RATE => "redemption rate computed by the update phase",
+        POSTED_RATE => "redemption rate posted on the vault",
+        LAST_RATE_UPDATE => "unix timestamp of the last successful vault rate update",
+        MAX_RATE_UPDATE_DELAY => "max delay between rate updates before the vault pauses",
+        STALE_RATE_PAUSE_IN => "seconds left before the vault pauses over a stale rate",
+        RATE_UPDATES_SKIPPED => "number of rate updates skipped over a rate anomaly",
+        UPDATE_PREEMPTIONS => "number of cycles pre-empted by a mandatory rate update",
+        CLEARING_QUEUE_LENGTH => "number of pending obligations in the clearing queue",
+        OBLIGATIONS_REGISTERED => "number of withdraw obligations registered",
+        OBLIGATIONS_SETTLED => "number of withdraw obligations settled",
+        SETTLEMENT_BATCHES => "number of batches that withdraw obligations were settled in",
+        SETTLEMENT_BATCH_SIZE => "number of obligations settled per processor execution",
+        MARS_LIQUIDITY_SHORTFALLS => "number of settlements short of Mars market liquidity",
+        MARS_FOREIGN_DEBTS => "Mars debt in a denom that cannot be valued",
+        MARS_LEND_APY => "current lend APY of the Mars market",
+        SUPERVAULT_APY => "supervault APY derived from its LP share value",
+        REBALANCE_PROPOSALS => "number of split updates proposed to the owner",
+        PHASE_DURATION => "duration of the latest phase run",
+        PHASE_ERRORS => "number of failed phase runs",
+        PHASE_RETRIES => "number of retried phase runs",
+        CYCLE_FAILURES => "number of failed cycles",
+        TRANSFER_DELIVERY => "share of the quoted amount delivered by the latest transfer",
+        TRANSFER_SHORTFALLS => "number of transfers that fell short of their quote",
+        TRANSFERS_EXPIRED => "number of transfers that expired in transit",
+        _ => "strategist metric",
+    }
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+
+    let rendered: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| {
+            let escaped = v
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{k}=\"{escaped}\"")
+        })
+        .collect();
+
+    format!("{{{}}}", rendered.join(","))
+}
+
+/// binds the listener and serves the metrics on `GET /metrics` in the
+/// background. every other path responds with 404.
+pub async fn serve_metrics(metrics: Arc<Metrics>, addr: &str) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(target: METRICS, "serving metrics on http://{addr}/metrics");
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let metrics = metrics.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream, &metrics).await {
+                            warn!(target: METRICS, "failed to serve metrics request: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!(target: METRICS, "failed to accept metrics connection: {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+async fn respond(mut stream: TcpStream, metrics: &Metrics) -> anyhow::Result<()> {
+    // we only care about the request line, so a single read suffices
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+
+    let response = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+        ["GET", "/metrics"] => {
+            let body = metrics.render();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_groups_the_samples_by_metric() {
+        let metrics = Metrics::default();
+        metrics.set_gauge(POSITION_BALANCE, &[("position", "mars")], 10.0);
+        metrics.set_gauge(POSITION_BALANCE, &[("position", "mars")], 7.0);
+        metrics.set_gauge(POSITION_BALANCE, &[("position", "ica")], 3.0);
+        metrics.inc_counter(OBLIGATIONS_SETTLED, &[], 2.0);
+        metrics.inc_counter(OBLIGATIONS_SETTLED, &[], 1.0);
+
+        let expected = format!(
+            "# HELP {OBLIGATIONS_SETTLED} {}\n\
+             # TYPE {OBLIGATIONS_SETTLED} counter\n\
+             {OBLIGATIONS_SETTLED} 3\n\
+             # HELP {POSITION_BALANCE} {}\n\
+             # TYPE {POSITION_BALANCE} gauge\n\
+             {POSITION_BALANCE}{{position=\"ica\"}} 3\n\
+             {POSITION_BALANCE}{{position=\"mars\"}} 7\n",
+            help(OBLIGATIONS_SETTLED),
+            help(POSITION_BALANCE),
+        );
+        assert_eq!(metrics.render(), expected);
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(
+            render_labels(&[("phase", "update"), ("error", "a \"b\"\\c\nd")]),
+            r#"{phase="update",error="a \"b\"\\c\nd"}"#
+        );
+        assert_eq!(render_labels(&[]), "");
+    }
+}
@@ -1,13 +1,18 @@
 use valence_domain_clients::coprocessor::base_client::{Base64, Proof};
 
 pub mod crypto_provider;
+pub mod eth_tx;
+pub mod grpc_querier;
 pub mod logging;
 pub mod mars;
 pub mod maxbtc;
+pub mod metrics;
 pub mod obligation;
 pub mod skip;
+pub mod snapshot;
 pub mod supervaults;
 pub mod valence_core;
+pub mod valuation;
 
 /// Decodes the base64 bytes of the proof and public inputs.
 pub fn decode(a: Proof) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
@@ -1,10 +1,38 @@
 use std::collections::HashMap;
 
-use cosmwasm_std::{coin, Coin};
-use valence_clearing_queue_supervaults::state::WithdrawalObligation;
+use cosmwasm_std::{coin, Coin, Uint64};
+use serde::Deserialize;
+
+/// clearing queue library config fields read by the strategist
+#[derive(Deserialize, Debug)]
+pub struct ClearingQueueConfig {
+    /// id of the latest registered obligation, if any
+    pub latest_id: Option<Uint64>,
+}
+
+impl ClearingQueueConfig {
+    /// id of the next withdraw request to be registered. if there are no
+    /// obligations registered yet, that is the very first request.
+    pub fn next_id(&self) -> u64 {
+        self.latest_id.map_or(0, |id| id.u64().saturating_add(1))
+    }
+}
+
+/// clearing queue pending obligations response
+#[derive(Deserialize, Debug)]
+pub struct ObligationsResponse {
+    pub obligations: Vec<PendingObligation>,
+}
+
+/// withdrawal obligation fields read by the strategist
+#[derive(Deserialize, Debug, Clone)]
+pub struct PendingObligation {
+    pub id: Uint64,
+    pub payout_coins: Vec<Coin>,
+}
 
 /// batches a given vec of withdrawal obligation payouts into a vec of coins
-pub fn batch_obligation_queue_payouts(obligations: &[WithdrawalObligation]) -> Vec<Coin> {
+pub fn batch_obligation_queue_payouts(obligations: &[PendingObligation]) -> Vec<Coin> {
     let mut totals: HashMap<String, u128> = HashMap::new();
 
     for ob in obligations {
@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+use crate::strategist::layout::Domain;
+
+/// block heights of every domain that a set of reads is pinned to. reading
+/// everything at a single snapshot keeps values that are derived from
+/// multiple queries (e.g. assets and shares) consistent with each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Snapshot {
+    pub ethereum_block: u64,
+    pub neutron_height: u64,
+    /// only present on routes that go through the cosmos hub
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gaia_height: Option<u64>,
+    /// only present on routes that hop through lombard
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lombard_height: Option<u64>,
+    /// only present on cctp routes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noble_height: Option<u64>,
+}
+
+impl Snapshot {
+    /// height of the given cosmos domain. `None` if the snapshot does not
+    /// cover the domain, in which case its latest state should be read.
+    pub fn height(&self, domain: Domain) -> Option<u64> {
+        match domain {
+            Domain::Neutron => Some(self.neutron_height),
+            Domain::Gaia => self.gaia_height,
+            Domain::Lombard => self.lombard_height,
+            Domain::Noble => self.noble_height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domains_outside_the_route_are_read_at_their_latest_height() {
+        let snapshot = Snapshot {
+            ethereum_block: 100,
+            neutron_height: 200,
+            gaia_height: Some(300),
+            lombard_height: None,
+            noble_height: None,
+        };
+
+        assert_eq!(snapshot.height(Domain::Neutron), Some(200));
+        assert_eq!(snapshot.height(Domain::Gaia), Some(300));
+        assert_eq!(snapshot.height(Domain::Lombard), None);
+        assert_eq!(snapshot.height(Domain::Noble), None);
+
+        assert_eq!(
+            serde_json::to_value(snapshot).unwrap(),
+            serde_json::json!({
+                "ethereum_block": 100,
+                "neutron_height": 200,
+                "gaia_height": 300,
+            })
+        );
+    }
+}
@@ -1,28 +1,99 @@
+use async_trait::async_trait;
 use cosmwasm_std::{Addr, Decimal, Uint128};
 use log::warn;
-use mmvault::state::Config;
-use valence_domain_clients::{
-    clients::neutron::NeutronClient,
-    cosmos::{base_client::BaseClient, wasm_client::WasmClient},
+use serde::Deserialize;
+
+use crate::{
+    phases::UPDATE_PHASE,
+    strategist::clients::{ContractQuerier, CosmosQuerier},
+    utils::valuation::PositionValuator,
 };
 
-use crate::phases::UPDATE_PHASE;
+/// supervault config fields read by the strategist
+#[derive(Deserialize, Debug)]
+pub struct SupervaultConfig {
+    pub lp_denom: String,
+    pub pair_data: SupervaultPairData,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SupervaultPairData {
+    pub token_0: SupervaultToken,
+    pub token_1: SupervaultToken,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SupervaultToken {
+    pub denom: String,
+}
+
+/// supervault LP shares held by `lp_holder`, expressed in the deposit denom
+pub struct SupervaultValuator<'a> {
+    querier: &'a dyn CosmosQuerier,
+    /// height the position is valued at. `None` values the latest state.
+    height: Option<u64>,
+    supervault: String,
+    depositor: String,
+    lp_holder: String,
+    deposit_denom: String,
+}
+
+impl<'a> SupervaultValuator<'a> {
+    pub fn new(
+        querier: &'a dyn CosmosQuerier,
+        height: Option<u64>,
+        supervault: &str,
+        depositor: &str,
+        lp_holder: &str,
+        deposit_denom: &str,
+    ) -> Self {
+        Self {
+            querier,
+            height,
+            supervault: supervault.to_string(),
+            depositor: depositor.to_string(),
+            lp_holder: lp_holder.to_string(),
+            deposit_denom: deposit_denom.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl PositionValuator for SupervaultValuator<'_> {
+    fn name(&self) -> String {
+        format!("supervault {} LP", self.supervault)
+    }
+
+    async fn value(&self) -> anyhow::Result<u128> {
+        query_supervault_tvl_expressed_in_denom(
+            self.querier,
+            &self.supervault,
+            &self.depositor,
+            &self.lp_holder,
+            &self.deposit_denom,
+            self.height,
+        )
+        .await
+    }
+}
 
 /// calculates total value of the active supervault position,
-/// expressed in the deposit token denom
+/// expressed in the deposit token denom, at the given height (or the
+/// latest one, if `None`)
 pub async fn query_supervault_tvl_expressed_in_denom(
-    client: &NeutronClient,
+    client: &dyn CosmosQuerier,
     supervault: &str,
     deposit_acc: &str,
     settlement_acc: &str,
     deposit_denom: &str,
+    height: Option<u64>,
 ) -> anyhow::Result<u128> {
     // query the supervault config to get the pair denom ordering
-    let supervault_cfg = query_supervault_cfg(client, supervault).await?;
+    let supervault_cfg = query_supervault_cfg(client, supervault, height).await?;
 
     // query the settlement acc lp token balance
     let lp_shares_balance: Uint128 = client
-        .query_balance(settlement_acc, &supervault_cfg.lp_denom)
+        .query_balance(settlement_acc, &supervault_cfg.lp_denom, height)
         .await?
         .into();
 
@@ -32,10 +103,63 @@ pub async fn query_supervault_tvl_expressed_in_denom(
         return Ok(0);
     }
 
-    // simulate the liquidation of all LP shares owned by the settlement account.
-    // this simulation returns a tuple of expected asset amounts, in order.
+    // express the LP shares entirely in terms of the deposit denom
+    let exchange_rate = deposit_denom_per_share(
+        client,
+        supervault,
+        &supervault_cfg,
+        deposit_acc,
+        deposit_denom,
+        lp_shares_balance,
+        height,
+    )
+    .await?;
+
+    // multiply the lp_shares balance by the derived (deposit_token / lp_shares) exchange rate
+    // to get the lp_shares balance value expressed in deposit token denom
+    let lp_shares_deposit_denom_equivalent = lp_shares_balance.checked_mul_floor(exchange_rate)?;
+
+    Ok(lp_shares_deposit_denom_equivalent.u128())
+}
+
+/// value of a single LP share of the supervault, expressed in the deposit
+/// denom, as derived from simulating the withdrawal of `shares`. queried
+/// at the given height (or the latest one, if `None`).
+pub async fn query_supervault_share_value(
+    client: &dyn CosmosQuerier,
+    supervault: &str,
+    depositor: &str,
+    deposit_denom: &str,
+    shares: Uint128,
+    height: Option<u64>,
+) -> anyhow::Result<Decimal> {
+    let supervault_cfg = query_supervault_cfg(client, supervault, height).await?;
+
+    deposit_denom_per_share(
+        client,
+        supervault,
+        &supervault_cfg,
+        depositor,
+        deposit_denom,
+        shares,
+        height,
+    )
+    .await
+}
+
+async fn deposit_denom_per_share(
+    client: &dyn CosmosQuerier,
+    supervault: &str,
+    supervault_cfg: &SupervaultConfig,
+    depositor: &str,
+    deposit_denom: &str,
+    shares: Uint128,
+    height: Option<u64>,
+) -> anyhow::Result<Decimal> {
+    // simulate the liquidation of the LP shares. this simulation returns a
+    // tuple of expected asset amounts, in order.
     let (withdraw_amount_0, withdraw_amount_1) =
-        simulate_supervault_withdraw_liquidity(client, supervault, lp_shares_balance).await?;
+        simulate_supervault_withdraw_liquidity(client, supervault, shares, height).await?;
 
     // the returned amounts above include a non-deposit denom which is not
     // relevant for our TVL calculation that is denominated in the deposit
@@ -46,54 +170,48 @@ pub async fn query_supervault_tvl_expressed_in_denom(
     // 2. simulating LP for that amount to know the deposit_token -> shares
     // exchange rate
     // 3. assuming the same rate for whole position
-    let exchange_rate = {
-        // match the supervault config pair denom ordering
-        let (amount_0, amount_1, withdraw_amount) =
-            if deposit_denom == supervault_cfg.pair_data.token_0.denom {
-                (withdraw_amount_0, Uint128::zero(), withdraw_amount_0)
-            } else {
-                (Uint128::zero(), withdraw_amount_1, withdraw_amount_1)
-            };
-
-        // simulate LP with the deposit token. amount here does not really matter,
-        // but to avoid some rounding errors with small amounts we pass the expected
-        // withdraw amount to get a reasonable value.
-        let expected_lp_shares = simulate_supervault_provide_liquidity(
-            client,
-            supervault,
-            deposit_acc,
-            amount_0,
-            amount_1,
-        )
-        .await?;
-
-        // if expected share amount is zero we return tvl of 0
-        if expected_lp_shares.is_zero() {
-            return Ok(0);
-        }
 
-        Decimal::from_ratio(withdraw_amount, expected_lp_shares)
-    };
+    // match the supervault config pair denom ordering
+    let (amount_0, amount_1, withdraw_amount) =
+        if deposit_denom == supervault_cfg.pair_data.token_0.denom {
+            (withdraw_amount_0, Uint128::zero(), withdraw_amount_0)
+        } else {
+            (Uint128::zero(), withdraw_amount_1, withdraw_amount_1)
+        };
 
-    // multiply the lp_shares balance by the derived (deposit_token / lp_shares) exchange rate
-    // to get the lp_shares balance value expressed in deposit token denom
-    let lp_shares_deposit_denom_equivalent = lp_shares_balance.checked_mul_floor(exchange_rate)?;
+    // simulate LP with the deposit token. amount here does not really matter,
+    // but to avoid some rounding errors with small amounts we pass the expected
+    // withdraw amount to get a reasonable value.
+    let expected_lp_shares = simulate_supervault_provide_liquidity(
+        client, supervault, depositor, amount_0, amount_1, height,
+    )
+    .await?;
+
+    // if expected share amount is zero the shares are worth nothing
+    if expected_lp_shares.is_zero() {
+        return Ok(Decimal::zero());
+    }
 
-    Ok(lp_shares_deposit_denom_equivalent.u128())
+    Ok(Decimal::from_ratio(withdraw_amount, expected_lp_shares))
 }
 
-async fn query_supervault_cfg(client: &NeutronClient, supervault: &str) -> anyhow::Result<Config> {
-    let supervault_cfg: Config = client
-        .query_contract_state(supervault, mmvault::msg::QueryMsg::GetConfig {})
+async fn query_supervault_cfg(
+    client: &dyn CosmosQuerier,
+    supervault: &str,
+    height: Option<u64>,
+) -> anyhow::Result<SupervaultConfig> {
+    let supervault_cfg: SupervaultConfig = client
+        .query_contract_state(supervault, mmvault::msg::QueryMsg::GetConfig {}, height)
         .await?;
 
     Ok(supervault_cfg)
 }
 
 async fn simulate_supervault_withdraw_liquidity(
-    client: &NeutronClient,
+    client: &dyn CosmosQuerier,
     supervault: &str,
     shares: Uint128,
+    height: Option<u64>,
 ) -> anyhow::Result<(Uint128, Uint128)> {
     // simulate the liquidation of all LP shares owned by the settlement account.
     // this simulation returns a tuple of expected asset amounts, in order.
@@ -101,6 +219,7 @@ async fn simulate_supervault_withdraw_liquidity(
         .query_contract_state(
             supervault,
             mmvault::msg::QueryMsg::SimulateWithdrawLiquidity { amount: shares },
+            height,
         )
         .await?;
 
@@ -108,11 +227,12 @@ async fn simulate_supervault_withdraw_liquidity(
 }
 
 async fn simulate_supervault_provide_liquidity(
-    client: &NeutronClient,
+    client: &dyn CosmosQuerier,
     supervault: &str,
     depositor: &str,
     amount_0: Uint128,
     amount_1: Uint128,
+    height: Option<u64>,
 ) -> anyhow::Result<Uint128> {
     // simulate LP with the deposit token. amount here does not really matter,
     // but to avoid some rounding errors with small amounts we pass the expected
@@ -125,6 +245,7 @@ async fn simulate_supervault_provide_liquidity(
                 amount_1,
                 sender: Addr::unchecked(depositor.to_string()),
             },
+            height,
         )
         .await?;
 
@@ -1,34 +1,103 @@
 use std::cmp::Ordering;
 
-use alloy::{
-    primitives::{Address, U256},
-    providers::Provider,
-};
+use alloy::primitives::{Address, U256};
+use anyhow::anyhow;
 use cosmwasm_std::{Binary, Decimal};
+use futures::stream::{self, StreamExt, TryStreamExt};
 
-use anyhow::anyhow;
 use log::{debug, info, warn};
-use valence_authorization_utils::msg::ProcessorMessage;
-use valence_domain_clients::{
-    clients::{ethereum::EthereumClient, neutron::NeutronClient},
-    cosmos::{base_client::BaseClient, wasm_client::WasmClient},
-    evm::base_client::{CustomProvider, EvmBaseClient},
+use serde::Deserialize;
+use valence_authorization_utils::{
+    authorization::{Authorization, Subroutine},
+    callback::{ExecutionResult, ProcessorCallbackInfo},
+    msg::ProcessorMessage,
 };
 
 use crate::{
     labels::REGISTER_OBLIGATION_LABEL,
     phases::{DEPOSIT_PHASE, REGISTRATION_PHASE, SENTRY_PHASE, UPDATE_PHASE},
+    strategist::{
+        clients::{ContractQuerier, EthereumDomain, NeutronDomain, NeutronTx, ZkProof},
+        error::StrategistError,
+        schedule::Preemption,
+    },
     types::sol_types::OneWayVault,
 };
 
 const ICA_CONTRACT_FUNDING_AMT: u128 = 200_000;
 
+/// attribute of the wasm event in which the authorizations contract
+/// reports the execution id of the batch it pushed to the processor
+const EXECUTION_ID_ATTRIBUTE: &str = "execution_id";
+
+/// most processor ticks spent on getting an execution out of the queue.
+/// batches enqueued by others may be ahead of it, and failed batches may
+/// be re-queued for a retry.
+const MAX_EXECUTION_TICKS: usize = 10;
+
+/// most vault queries run at once when reading the withdraw requests
+const MAX_CONCURRENT_VAULT_QUERIES: usize = 8;
+
+/// processor queue batch fields read by the strategist
+#[derive(Deserialize, Debug)]
+struct QueuedBatch {
+    /// execution id assigned by the authorizations contract
+    id: u64,
+}
+
+/// outcome of a subroutine execution in the processor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    /// every message of the subroutine was executed
+    Succeeded,
+    /// subroutine failed (or never got to run), along with the reason
+    Failed(String),
+    /// batch is still in the processor queue, e.g. waiting for a retry
+    Pending,
+}
+
+impl From<ExecutionResult> for ExecutionOutcome {
+    fn from(result: ExecutionResult) -> Self {
+        match result {
+            ExecutionResult::InProcess => ExecutionOutcome::Pending,
+            ExecutionResult::Success => ExecutionOutcome::Succeeded,
+            ExecutionResult::Rejected(e) | ExecutionResult::UnexpectedError(e) => {
+                ExecutionOutcome::Failed(e)
+            }
+            ExecutionResult::PartiallyExecuted(executed, e) => {
+                ExecutionOutcome::Failed(format!("{e} (after {executed} executed messages)"))
+            }
+            ExecutionResult::RemovedByOwner => {
+                ExecutionOutcome::Failed("removed from the queue by the owner".to_string())
+            }
+            ExecutionResult::Timeout(_) => {
+                ExecutionOutcome::Failed("expired before it was executed".to_string())
+            }
+        }
+    }
+}
+
+impl ExecutionOutcome {
+    /// errors out unless the subroutine with the given label succeeded
+    pub fn ensure_succeeded(&self, label: &str) -> anyhow::Result<()> {
+        match self {
+            ExecutionOutcome::Succeeded => Ok(()),
+            ExecutionOutcome::Failed(e) => {
+                Err(StrategistError::TxReverted(format!("{label} subroutine failed: {e}")).into())
+            }
+            ExecutionOutcome::Pending => Err(anyhow!("{label} subroutine is still pending")),
+        }
+    }
+}
+
+/// enqueues the messages under the given label, returning the execution id
+/// of the resulting processor batch
 pub async fn enqueue_neutron(
-    client: &NeutronClient,
+    client: &dyn NeutronDomain,
     authorizations: &str,
     label: &str,
     messages: Vec<Binary>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<u64> {
     let mut encoded_messages = vec![];
 
     for message in messages {
@@ -37,118 +106,223 @@ pub async fn enqueue_neutron(
         encoded_messages.push(processor_msg);
     }
 
-    let tx_resp = client
+    let tx = client
         .execute_wasm(
             authorizations,
-            valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
-                valence_authorization_utils::msg::PermissionlessMsg::SendMsgs {
-                    label: label.to_string(),
-                    messages: encoded_messages,
-                    ttl: None,
-                },
-            ),
-            vec![],
-            None,
+            serde_json::to_value(
+                valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
+                    valence_authorization_utils::msg::PermissionlessMsg::SendMsgs {
+                        label: label.to_string(),
+                        messages: encoded_messages,
+                        ttl: None,
+                    },
+                ),
+            )?,
         )
         .await?;
 
-    debug!("tx hash: {}", tx_resp.hash);
+    debug!("tx hash: {}", tx.hash);
 
-    client.poll_for_tx(&tx_resp.hash).await?;
-
-    Ok(())
+    enqueued_execution_id(&tx, authorizations)
 }
 
 /// ticks the processor on neutron
-pub async fn tick_neutron(client: &NeutronClient, processor: &str) -> anyhow::Result<()> {
-    let tx_resp = client
+pub async fn tick_neutron(client: &dyn NeutronDomain, processor: &str) -> anyhow::Result<()> {
+    let tx = client
         .execute_wasm(
             processor,
-            valence_processor_utils::msg::ExecuteMsg::PermissionlessAction(
-                valence_processor_utils::msg::PermissionlessMsg::Tick {},
-            ),
-            vec![],
+            serde_json::to_value(
+                valence_processor_utils::msg::ExecuteMsg::PermissionlessAction(
+                    valence_processor_utils::msg::PermissionlessMsg::Tick {},
+                ),
+            )?,
+        )
+        .await?;
+
+    debug!("tx hash: {}", tx.hash);
+
+    Ok(())
+}
+
+/// ticks the processor until the given execution leaves the queue (or up
+/// to `MAX_EXECUTION_TICKS` times), and reads back its outcome from the
+/// processor callback recorded by the authorizations contract. the ticking
+/// stops early once pre-empted, leaving the execution in the queue.
+pub async fn tick_neutron_execution(
+    client: &dyn NeutronDomain,
+    authorizations: &str,
+    processor: &str,
+    execution_id: u64,
+    preemption: Preemption,
+) -> anyhow::Result<ExecutionOutcome> {
+    let mut ticks = 0;
+    while queued_execution_ids(client, processor)
+        .await?
+        .contains(&execution_id)
+    {
+        if ticks == MAX_EXECUTION_TICKS {
+            warn!("execution #{execution_id} is still queued after {ticks} ticks");
+            break;
+        }
+        preemption.check(&format!("ticking execution #{execution_id}"))?;
+        tick_neutron(client, processor).await?;
+        ticks += 1;
+    }
+
+    let callback: ProcessorCallbackInfo = client
+        .query_contract_state(
+            authorizations,
+            valence_authorization_utils::msg::QueryMsg::ProcessorCallback { execution_id },
             None,
         )
         .await?;
 
-    debug!("tx hash: {}", tx_resp.hash);
+    debug!(
+        "execution #{execution_id} result: {:?}",
+        callback.execution_result
+    );
+
+    Ok(callback.execution_result.into())
+}
 
-    client.poll_for_tx(&tx_resp.hash).await?;
+/// execution id reported by the authorizations contract for the batch
+/// that the tx pushed to the processor. enqueuing is permissionless, so
+/// the back of the queue may as well hold someone else's batch.
+fn enqueued_execution_id(tx: &NeutronTx, authorizations: &str) -> anyhow::Result<u64> {
+    let execution_id = tx
+        .wasm_attribute(authorizations, EXECUTION_ID_ATTRIBUTE)
+        .ok_or_else(|| anyhow!("tx {} did not report an execution id", tx.hash))?;
+
+    execution_id.parse().map_err(|e| {
+        anyhow!(
+            "malformed execution id {execution_id} in tx {}: {e}",
+            tx.hash
+        )
+    })
+}
 
-    Ok(())
+/// execution ids of the medium priority batches in the processor queue
+async fn queued_execution_ids(
+    client: &dyn NeutronDomain,
+    processor: &str,
+) -> anyhow::Result<Vec<u64>> {
+    let queue: Vec<QueuedBatch> = client
+        .query_contract_state(
+            processor,
+            valence_processor_utils::msg::QueryMsg::GetQueue {
+                from: None,
+                to: None,
+                priority: valence_authorization_utils::authorization::Priority::Medium,
+            },
+            None,
+        )
+        .await?;
+
+    Ok(queue.into_iter().map(|batch| batch.id).collect())
+}
+
+/// number of functions in the subroutine of the authorization with the
+/// given label, i.e. the number of messages that a single execution of it
+/// may carry
+pub async fn query_subroutine_function_count(
+    client: &dyn NeutronDomain,
+    authorizations: &str,
+    label: &str,
+) -> anyhow::Result<usize> {
+    const PAGE_LIMIT: u32 = 50;
+
+    let mut start_after = None;
+    loop {
+        let page: Vec<Authorization> = client
+            .query_contract_state(
+                authorizations,
+                valence_authorization_utils::msg::QueryMsg::Authorizations {
+                    start_after: start_after.clone(),
+                    limit: Some(PAGE_LIMIT),
+                },
+                None,
+            )
+            .await?;
+
+        if let Some(authorization) = page.iter().find(|a| a.label == label) {
+            return Ok(match &authorization.subroutine {
+                Subroutine::Atomic(subroutine) => subroutine.functions.len(),
+                Subroutine::NonAtomic(subroutine) => subroutine.functions.len(),
+            });
+        }
+
+        if page.len() < PAGE_LIMIT as usize {
+            return Err(anyhow!("no {label} authorization in {authorizations}"));
+        }
+        start_after = page.last().map(|a| a.label.clone());
+    }
 }
 
 /// constructs the zk authorization execution message and executes it.
 /// authorizations module will perform the zk verification and, if
-/// successful, push it to the processor for execution
+/// successful, push it to the processor for execution. returns the
+/// execution id of the resulting processor batch.
 pub async fn post_zkp_on_chain(
-    client: &NeutronClient,
+    client: &dyn NeutronDomain,
     authorizations: &str,
-    (proof_program, inputs_program): (Vec<u8>, Vec<u8>),
-    proof_domain: Vec<u8>,
-) -> anyhow::Result<()> {
+    proof: ZkProof,
+) -> anyhow::Result<u64> {
     // construct the zk authorization registration message
     let execute_zk_authorization_msg =
         valence_authorization_utils::msg::PermissionlessMsg::ExecuteZkAuthorization {
             label: REGISTER_OBLIGATION_LABEL.to_string(),
-            inputs: Binary::from(inputs_program),
-            proof: Binary::from(proof_program),
-            payload: Binary::from(proof_domain),
+            inputs: Binary::from(proof.inputs_program),
+            proof: Binary::from(proof.proof_program),
+            payload: Binary::from(proof.proof_domain),
         };
 
     // execute the zk authorization. this will perform the verification
     // and, if successful, push the msg to the processor
     info!(target: REGISTRATION_PHASE, "executing zk authorization");
 
-    let tx_resp = client
+    let tx = client
         .execute_wasm(
             authorizations,
-            valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
-                execute_zk_authorization_msg,
-            ),
-            vec![],
-            None,
+            serde_json::to_value(
+                valence_authorization_utils::msg::ExecuteMsg::PermissionlessAction(
+                    execute_zk_authorization_msg,
+                ),
+            )?,
         )
         .await?;
 
-    // poll for inclusion to avoid account sequence mismatch errors
-    client.poll_for_tx(&tx_resp.hash).await?;
+    debug!("tx hash: {}", tx.hash);
 
-    Ok(())
+    enqueued_execution_id(&tx, authorizations)
 }
 
 pub async fn ensure_neutron_account_fees_coverage(
-    client: &NeutronClient,
+    client: &dyn NeutronDomain,
     acc: &str,
 ) -> anyhow::Result<()> {
-    let account_ntrn_balance = client.query_balance(acc, "untrn").await?;
+    let account_ntrn_balance = client.query_balance(acc, "untrn", None).await?;
 
     if account_ntrn_balance < ICA_CONTRACT_FUNDING_AMT {
         let delta = ICA_CONTRACT_FUNDING_AMT - account_ntrn_balance;
 
         info!(target: DEPOSIT_PHASE, "Funding neutron account with {delta}untrn for ibc tx fees...");
-        let transfer_rx = client.transfer(acc, delta, "untrn", None).await?;
-        client.poll_for_tx(&transfer_rx.hash).await?;
+        client.transfer(acc, delta, "untrn").await?;
     }
 
     Ok(())
 }
 
-/// checks that the newly calculated redemption rate is within the acceptable
-/// rate update bounds relative to the current rate. pauses the vault otherwise.
-pub async fn validate_new_redemption_rate(
+/// read-only check of whether the newly calculated redemption rate is within
+/// the acceptable rate update bounds relative to the current rate
+pub async fn redemption_rate_within_bounds(
     vault: Address,
-    client: &EthereumClient,
-    eth_rp: &CustomProvider,
+    client: &dyn EthereumDomain,
     new_redemption_rate: U256,
     max_rate_decrement_bps: u64,
     max_rate_increment_bps: u64,
-) -> anyhow::Result<()> {
-    let one_way_vault_contract = OneWayVault::new(vault, &eth_rp);
-
+) -> anyhow::Result<bool> {
     let current_vault_rate = client
-        .query(one_way_vault_contract.redemptionRate())
+        .query(vault, OneWayVault::redemptionRateCall {}, None)
         .await?
         ._0;
 
@@ -159,7 +333,23 @@ pub async fn validate_new_redemption_rate(
     let redemption_rate_u128 = u128::try_from(new_redemption_rate)?;
     info!(target: UPDATE_PHASE, "new_rate = {redemption_rate_u128}");
 
-    let rate_change_decimal = Decimal::checked_from_ratio(redemption_rate_u128, current_rate_u128)?;
+    rate_change_within_bounds(
+        current_rate_u128,
+        redemption_rate_u128,
+        max_rate_decrement_bps,
+        max_rate_increment_bps,
+    )
+}
+
+/// whether the change from the current to the new rate stays within the
+/// max decrement and increment, both in basis points of the current rate
+fn rate_change_within_bounds(
+    current_rate: u128,
+    new_rate: u128,
+    max_rate_decrement_bps: u64,
+    max_rate_increment_bps: u64,
+) -> anyhow::Result<bool> {
+    let rate_change_decimal = Decimal::checked_from_ratio(new_rate, current_rate)?;
 
     info!(target: UPDATE_PHASE, "new to old rate ratio = {rate_change_decimal}");
 
@@ -170,16 +360,8 @@ pub async fn validate_new_redemption_rate(
             info!(target: UPDATE_PHASE, "redemption rate epoch delta = -{rate_delta}");
             let decrement_threshold = Decimal::bps(max_rate_decrement_bps);
             if rate_delta > decrement_threshold {
-                warn!(target: UPDATE_PHASE, "rate delta exceeds the threshold of {decrement_threshold}; pausing the vault");
-                let pause_request = one_way_vault_contract.pause().into_transaction_request();
-                let pause_vault_exec_response = client.sign_and_send(pause_request).await?;
-                eth_rp
-                    .get_transaction_receipt(pause_vault_exec_response.transaction_hash)
-                    .await?;
-
-                return Err(anyhow!(
-                    "newly calculated rate exceeds the rate update thresholds"
-                ));
+                warn!(target: UPDATE_PHASE, "rate delta exceeds the threshold of {decrement_threshold}");
+                return Ok(false);
             }
         }
         // rate change is exactly 1.0 -> redemption rate did not change
@@ -192,21 +374,69 @@ pub async fn validate_new_redemption_rate(
             info!(target: UPDATE_PHASE, "redemption rate epoch delta = +{rate_delta}");
             let increment_threshold = Decimal::bps(max_rate_increment_bps);
             if rate_delta > increment_threshold {
-                warn!(target: UPDATE_PHASE, "rate delta exceeds the threshold of {increment_threshold}; pausing the vault");
-                let pause_request = one_way_vault_contract.pause().into_transaction_request();
-                let pause_vault_exec_response = client.sign_and_send(pause_request).await?;
-                eth_rp
-                    .get_transaction_receipt(pause_vault_exec_response.transaction_hash)
-                    .await?;
-
-                return Err(anyhow!(
-                    "newly calculated rate exceeds the rate update thresholds"
-                ));
+                warn!(target: UPDATE_PHASE, "rate delta exceeds the threshold of {increment_threshold}");
+                return Ok(false);
             }
         }
     }
 
-    Ok(())
+    Ok(true)
+}
+
+/// total shares issued by the vault at the given block (or the latest one,
+/// if `None`)
+pub async fn query_vault_total_supply(
+    vault: Address,
+    client: &dyn EthereumDomain,
+    block: Option<u64>,
+) -> anyhow::Result<U256> {
+    let total_supply = client
+        .query(vault, OneWayVault::totalSupplyCall {}, block)
+        .await?
+        ._0;
+
+    Ok(total_supply)
+}
+
+/// ids of the withdraw requests submitted to the vault starting from the
+/// given id, read directly from the vault at the given block (or the latest
+/// one, if `None`). the vault assigns consecutive ids,
+/// `currentWithdrawRequestId` being the one the next request gets. the
+/// requests are read concurrently, up to `MAX_CONCURRENT_VAULT_QUERIES` at
+/// once.
+pub async fn query_vault_withdraw_request_ids(
+    vault: Address,
+    client: &dyn EthereumDomain,
+    from_id: u64,
+    block: Option<u64>,
+) -> anyhow::Result<Vec<u64>> {
+    let next_id = client
+        .query(vault, OneWayVault::currentWithdrawRequestIdCall {}, block)
+        .await?
+        ._0;
+
+    let requests: Vec<_> = stream::iter(from_id..next_id)
+        .map(|id| async move {
+            let request = client
+                .query(vault, OneWayVault::withdrawRequestsCall { _0: id }, block)
+                .await?;
+            anyhow::Ok((id, request))
+        })
+        .buffered(MAX_CONCURRENT_VAULT_QUERIES)
+        .try_collect()
+        .await?;
+
+    let mut ids = vec![];
+    for (id, request) in requests {
+        // requests that are not stored in the vault read back zeroed
+        if request.owner == Address::ZERO {
+            warn!(target: REGISTRATION_PHASE, "withdraw request #{id} is missing from the vault");
+            continue;
+        }
+        ids.push(request.id);
+    }
+
+    Ok(ids)
 }
 
 /// helper function to flush leftover elements of given priority from
@@ -214,13 +444,37 @@ pub async fn validate_new_redemption_rate(
 /// useful to unblock the processor from cycles that resulted in leftover
 /// items for any reason.
 pub async fn flush_neutron_processor_queue(
-    client: &NeutronClient,
+    client: &dyn NeutronDomain,
     processor: &str,
     priority: valence_authorization_utils::authorization::Priority,
 ) -> anyhow::Result<()> {
     info!(target: SENTRY_PHASE, "flushing {priority:?} priority elements from the processor queue");
 
-    let queue: Vec<valence_processor_utils::processor::MessageBatch> = client
+    let queue = query_neutron_processor_queue(client, processor, priority).await?;
+
+    if queue.is_empty() {
+        info!(target: SENTRY_PHASE, "processor queue is empty; nothing to flush");
+    } else {
+        for _ in queue.iter() {
+            tick_neutron(client, processor).await?;
+        }
+        info!(target: SENTRY_PHASE, "flushed {} elements from the queue", queue.len());
+    }
+
+    Ok(())
+}
+
+/// queries all elements of given priority that are currently sitting in
+/// the processor queue
+///
+/// the batches are returned as raw json, only their count is relevant
+/// to the strategist
+pub async fn query_neutron_processor_queue(
+    client: &dyn NeutronDomain,
+    processor: &str,
+    priority: valence_authorization_utils::authorization::Priority,
+) -> anyhow::Result<Vec<serde_json::Value>> {
+    let queue = client
         .query_contract_state(
             processor,
             valence_processor_utils::msg::QueryMsg::GetQueue {
@@ -228,17 +482,38 @@ pub async fn flush_neutron_processor_queue(
                 to: None,
                 priority,
             },
+            None,
         )
         .await?;
 
-    if queue.is_empty() {
-        info!(target: SENTRY_PHASE, "processor queue is empty; nothing to flush");
-    } else {
-        for _ in queue.iter() {
-            tick_neutron(client, processor).await?;
-        }
-        info!(target: SENTRY_PHASE, "flushed {} elements from the queue", queue.len());
+    Ok(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u128 = 1_000_000;
+
+    #[test]
+    fn test_rate_change_up_to_the_bounds_is_accepted() {
+        // 1% down and 2% up
+        assert!(rate_change_within_bounds(RATE, 990_000, 100, 200).unwrap());
+        assert!(rate_change_within_bounds(RATE, 1_020_000, 100, 200).unwrap());
+        assert!(rate_change_within_bounds(RATE, RATE, 0, 0).unwrap());
     }
 
-    Ok(())
+    #[test]
+    fn test_rate_change_past_the_bounds_is_refused() {
+        assert!(!rate_change_within_bounds(RATE, 989_999, 100, 200).unwrap());
+        assert!(!rate_change_within_bounds(RATE, 1_020_001, 100, 200).unwrap());
+        // the bounds apply to their own direction only
+        assert!(rate_change_within_bounds(RATE, 985_000, 200, 100).unwrap());
+        assert!(!rate_change_within_bounds(RATE, 1_015_000, 200, 100).unwrap());
+    }
+
+    #[test]
+    fn test_rate_change_from_a_zero_rate_fails() {
+        assert!(rate_change_within_bounds(0, RATE, 100, 200).is_err());
+    }
 }
@@ -0,0 +1,230 @@
+use alloy::primitives::Address;
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use log::info;
+use serde::Serialize;
+
+use crate::{
+    phases::UPDATE_PHASE,
+    strategist::clients::{CosmosQuerier, EthereumDomain},
+    types::sol_types::ERC20,
+    utils::snapshot::Snapshot,
+};
+
+/// a single venue (or account) holding vault assets. implementors express
+/// the position value in the denom that the redemption rate is calculated in.
+///
+/// adding a new venue to the rate accounting only requires a new implementation
+/// of this trait.
+#[async_trait]
+pub trait PositionValuator: Send + Sync {
+    /// human-readable position identifier used in the TVL breakdown
+    fn name(&self) -> String;
+
+    /// queries the current value of the position
+    async fn value(&self) -> anyhow::Result<u128>;
+}
+
+/// value of a single position at the time of valuation
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionValue {
+    pub name: String,
+    pub value: u128,
+}
+
+/// per-position TVL breakdown produced by the update phase
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TvlBreakdown {
+    pub positions: Vec<PositionValue>,
+    /// heights that the positions were valued at
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+}
+
+impl TvlBreakdown {
+    /// sum of all position values
+    pub fn total(&self) -> anyhow::Result<u128> {
+        self.positions.iter().try_fold(0u128, |acc, p| {
+            acc.checked_add(p.value)
+                .ok_or_else(|| anyhow::anyhow!("tvl overflow while adding {}", p.name))
+        })
+    }
+}
+
+/// values every position concurrently, logging each of them along the way.
+/// the breakdown lists the positions in the order of the valuators.
+pub async fn value_positions(
+    valuators: &[Box<dyn PositionValuator + '_>],
+) -> anyhow::Result<TvlBreakdown> {
+    let positions = try_join_all(valuators.iter().map(|valuator| async move {
+        let name = valuator.name();
+        let value = valuator.value().await?;
+        info!(target: UPDATE_PHASE, "{name} = {value}");
+        anyhow::Ok(PositionValue { name, value })
+    }))
+    .await?;
+
+    Ok(TvlBreakdown {
+        positions,
+        ..Default::default()
+    })
+}
+
+/// plain bank balance of an account on any cosmos domain
+pub struct BalanceValuator<'a> {
+    querier: &'a dyn CosmosQuerier,
+    /// height the balance is read at. `None` reads the latest state.
+    height: Option<u64>,
+    domain: String,
+    address: String,
+    denom: String,
+}
+
+impl<'a> BalanceValuator<'a> {
+    pub fn new(
+        querier: &'a dyn CosmosQuerier,
+        height: Option<u64>,
+        domain: &str,
+        address: &str,
+        denom: &str,
+    ) -> Self {
+        Self {
+            querier,
+            height,
+            domain: domain.to_string(),
+            address: address.to_string(),
+            denom: denom.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl PositionValuator for BalanceValuator<'_> {
+    fn name(&self) -> String {
+        format!("{} {} {} balance", self.domain, self.address, self.denom)
+    }
+
+    async fn value(&self) -> anyhow::Result<u128> {
+        self.querier
+            .query_balance(&self.address, &self.denom, self.height)
+            .await
+    }
+}
+
+/// ERC20 balance of an account on Ethereum
+pub struct Erc20BalanceValuator<'a> {
+    client: &'a dyn EthereumDomain,
+    token: Address,
+    holder: Address,
+    /// block the balance is read at. `None` reads the latest block.
+    block: Option<u64>,
+}
+
+impl<'a> Erc20BalanceValuator<'a> {
+    pub fn new(
+        client: &'a dyn EthereumDomain,
+        token: Address,
+        holder: Address,
+        block: Option<u64>,
+    ) -> Self {
+        Self {
+            client,
+            token,
+            holder,
+            block,
+        }
+    }
+}
+
+#[async_trait]
+impl PositionValuator for Erc20BalanceValuator<'_> {
+    fn name(&self) -> String {
+        format!("ethereum {} {} balance", self.holder, self.token)
+    }
+
+    async fn value(&self) -> anyhow::Result<u128> {
+        let balance_u256 = self
+            .client
+            .query(
+                self.token,
+                ERC20::balanceOfCall {
+                    account: self.holder,
+                },
+                self.block,
+            )
+            .await?
+            ._0;
+
+        // perform u256 -> u128 conversion
+        Ok(u128::try_from(balance_u256)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// position of a fixed value, or one that fails to be valued
+    struct FixedValuator(&'static str, Option<u128>);
+
+    #[async_trait]
+    impl PositionValuator for FixedValuator {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        async fn value(&self) -> anyhow::Result<u128> {
+            self.1
+                .ok_or_else(|| anyhow::anyhow!("{} is unavailable", self.0))
+        }
+    }
+
+    fn valuators(positions: &[(&'static str, Option<u128>)]) -> Vec<Box<dyn PositionValuator>> {
+        positions
+            .iter()
+            .map(|(name, value)| Box::new(FixedValuator(name, *value)) as Box<dyn PositionValuator>)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_lists_the_positions_in_order() {
+        let tvl = value_positions(&valuators(&[
+            ("mars", Some(700)),
+            ("supervault", Some(300)),
+        ]))
+        .await
+        .unwrap();
+
+        let positions: Vec<(&str, u128)> = tvl
+            .positions
+            .iter()
+            .map(|p| (p.name.as_str(), p.value))
+            .collect();
+        assert_eq!(positions, vec![("mars", 700), ("supervault", 300)]);
+        assert_eq!(tvl.total().unwrap(), 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_fails_with_any_of_the_positions() {
+        let err = value_positions(&valuators(&[("mars", Some(700)), ("supervault", None)]))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("supervault is unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_total_refuses_to_overflow() {
+        let tvl = value_positions(&valuators(&[
+            ("mars", Some(u128::MAX)),
+            ("supervault", Some(1)),
+        ]))
+        .await
+        .unwrap();
+
+        let err = tvl.total().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("tvl overflow while adding supervault"));
+    }
+}
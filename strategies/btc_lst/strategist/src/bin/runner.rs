@@ -1,4 +1,4 @@
-use btc_lst_strategist::strategy_config::Strategy;
+use btc_lst_strategist::strategy_config::{Strategy, StrategyConfig};
 use log::{info, warn};
 use packages::utils::{crypto_provider::setup_crypto_provider, logging::setup_logging};
 use std::env;
@@ -31,8 +31,20 @@ async fn main() -> anyhow::Result<()> {
     info!(target: RUNNER, "  Gaia: {gaia_cfg_path}");
 
     // initialize the strategy from configuration files
-    let strategy =
-        Strategy::from_files(&neutron_cfg_path, &gaia_cfg_path, &ethereum_cfg_path).await?;
+    let strategy_cfg =
+        StrategyConfig::from_files(&neutron_cfg_path, &gaia_cfg_path, &ethereum_cfg_path)?;
+
+    // in dry-run mode we run a single cycle without broadcasting anything
+    // and print out the plan of what the cycle would have done
+    if env::args().any(|arg| arg == "--dry-run") {
+        info!(target: RUNNER, "running a single dry-run cycle");
+        let mut strategy = Strategy::new_dry_run(strategy_cfg.into()).await?;
+        let plan = strategy.dry_run_cycle().await?;
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+
+    let strategy = Strategy::new(strategy_cfg.into()).await?;
 
     info!(target: RUNNER, "strategy initialized");
     info!(target: RUNNER, "starting the strategist");
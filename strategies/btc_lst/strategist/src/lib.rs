@@ -1,3 +1 @@
-pub mod phases;
-pub mod strategist;
 pub mod strategy_config;
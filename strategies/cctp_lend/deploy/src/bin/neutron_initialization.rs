@@ -6,8 +6,9 @@ use cosmwasm_std::Binary;
 use packages::{
     labels::{
         LEND_AND_PROVIDE_LIQUIDITY_LABEL, MARS_WITHDRAW_LABEL, REGISTER_OBLIGATION_LABEL,
-        SETTLE_OBLIGATION_LABEL,
+        SETTLE_OBLIGATION_BATCH_LABEL, SETTLE_OBLIGATION_LABEL,
     },
+    strategist::layout::SettlementLayout,
     types::inputs::ClearingQueueCoprocessorApp,
     utils::crypto_provider::setup_crypto_provider,
     verification::VERIFICATION_ROUTE,
@@ -158,7 +159,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let subroutine_settle_obligation = AtomicSubroutineBuilder::new()
-        .with_function(settle_obligation_function)
+        .with_function(settle_obligation_function.clone())
         .build();
     let authorization_settle_obligation = AuthorizationBuilder::new()
         .with_label(SETTLE_OBLIGATION_LABEL)
@@ -167,6 +168,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .build();
     authorizations.push(authorization_settle_obligation);
 
+    // Authorization for settling a full batch of obligations in one execution.
+    // Atomic subroutines take exactly one message per function, so the strategist
+    // only sends full batches through it and settles the rest one by one
+    let settle_batch_size = SettlementLayout::DEFAULT_MAX_OBLIGATIONS_PER_BATCH;
+    let subroutine_settle_obligation_batch = (0..settle_batch_size)
+        .fold(AtomicSubroutineBuilder::new(), |builder, _| {
+            builder.with_function(settle_obligation_function.clone())
+        })
+        .build();
+    let authorization_settle_obligation_batch = AuthorizationBuilder::new()
+        .with_label(SETTLE_OBLIGATION_BATCH_LABEL)
+        .with_mode(authorization_permissioned_mode.clone())
+        .with_subroutine(subroutine_settle_obligation_batch)
+        .build();
+    authorizations.push(authorization_settle_obligation_batch);
+
     // Add all authorizations to the authorization contract
     let create_authorizations = valence_authorization_utils::msg::ExecuteMsg::PermissionedAction(
         valence_authorization_utils::msg::PermissionedMsg::CreateAuthorizations { authorizations },
@@ -1,5 +0,0 @@
-pub mod deposit;
-pub mod obligation_registration;
-pub mod sentry;
-pub mod settlement;
-pub mod update;
@@ -1,17 +1,29 @@
-use std::{env, path::Path};
+use std::path::Path;
 
 use anyhow::anyhow;
 use cctp_lend_types::{
     ethereum_config::EthereumStrategyConfig, neutron_config::NeutronStrategyConfig,
     noble_config::NobleStrategyConfig,
 };
-use serde::{Deserialize, Serialize};
-use valence_domain_clients::clients::{
-    coprocessor::CoprocessorClient, ethereum::EthereumClient, neutron::NeutronClient,
-    noble::NobleClient, valence_indexer::OneWayVaultIndexerClient,
+use packages::{
+    labels::LEND_AND_PROVIDE_LIQUIDITY_LABEL,
+    strategist::layout::{
+        CctpRoute, ChainConnection, DepositRoute, Domain, EntryAction, EthereumLayout,
+        NeutronLayout, Position, PositionEntry, RegistrationLayout, SettlementDenom,
+        SettlementLayout, SettlementSource, StrategyLayout, TrackedBalance, UpdateLayout,
+    },
 };
+use serde::{Deserialize, Serialize};
 use valence_strategist_utils::worker::ValenceWorkerTomlSerde;
 
+pub use packages::strategist::Strategy;
+
+/// noble fee denom
+const NOBLE_FEE_DENOM: &str = "uusdc";
+/// position entry is triggered as soon as anything arrives to the
+/// Neutron deposit account
+const MIN_ENTRY_BALANCE: u128 = 1;
+
 /// top-level config that wraps around each domain configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyConfig {
@@ -20,94 +32,15 @@ pub struct StrategyConfig {
     pub noble: NobleStrategyConfig,
 }
 
-// main strategy struct that wraps around the StrategyConfig
-// and stores the initialized clients
-pub struct Strategy {
-    /// strategy name
-    pub label: String,
-
-    /// strategy timeout (in seconds)
-    pub timeout: u64,
-
-    /// top level strategy configuration
-    pub cfg: StrategyConfig,
-
-    /// active ethereum client
-    pub(crate) eth_client: EthereumClient,
-    /// active neutron client
-    pub(crate) neutron_client: NeutronClient,
-    /// active noble client
-    pub(crate) noble_client: NobleClient,
-    /// active one way vault indexer client
-    pub(crate) indexer_client: OneWayVaultIndexerClient,
-    /// active coprocessor client
-    pub(crate) coprocessor_client: CoprocessorClient,
-}
-
-#[allow(dead_code)]
-impl Strategy {
-    /// strategy initializer that takes in a `StrategyConfig`, and uses it
-    /// to initialize the respective domain clients. prerequisite to starting
-    /// the strategist.
-    pub async fn new(cfg: StrategyConfig) -> anyhow::Result<Self> {
-        let mnemonic =
-            env::var("MNEMONIC").map_err(|e| anyhow!("mnemonic must be provided: {e}"))?;
-        let label = env::var("LABEL").map_err(|e| anyhow!("label must be provided: {e}"))?;
-        let indexer_api_key = env::var("INDEXER_API_KEY")
-            .map_err(|e| anyhow!("indexer api key must be provided: {e}"))?;
-        let indexer_api_url = env::var("INDEXER_API_URL")
-            .map_err(|e| anyhow!("indexer api url key must be provided: {e}"))?;
-        let strategy_timeout: u64 = env::var("STRATEGY_TIMEOUT")
-            .map_err(|e| anyhow!("Strategy timeout must be provided: {e}"))?
-            .parse()?;
-
-        let noble_client = NobleClient::new(
-            &cfg.noble.grpc_url,
-            &cfg.noble.grpc_port,
-            &mnemonic,
-            &cfg.noble.chain_id,
-            "uusdc",
-        )
-        .await?;
-
-        let neutron_client = NeutronClient::new(
-            &cfg.neutron.grpc_url,
-            &cfg.neutron.grpc_port,
-            &mnemonic,
-            &cfg.neutron.chain_id,
-        )
-        .await?;
-
-        let eth_client = EthereumClient::new(&cfg.ethereum.rpc_url, &mnemonic, None)?;
-
-        let indexer_client = OneWayVaultIndexerClient::new(
-            &indexer_api_url,
-            &indexer_api_key,
-            &cfg.ethereum.libraries.one_way_vault.to_string(),
-        );
-
-        let coprocessor_client = CoprocessorClient::default();
-
-        Ok(Self {
-            cfg,
-            timeout: strategy_timeout,
-            eth_client,
-            neutron_client,
-            label,
-            indexer_client,
-            coprocessor_client,
-            noble_client,
-        })
-    }
-
-    /// constructor helper that takes in four paths:
+impl StrategyConfig {
+    /// constructor helper that takes in three paths:
     /// - neutron config path
     /// - ethereum config path
     /// - noble config path
     ///
     /// reads the configs from those paths, sets up each domain config,
-    /// wraps them in a `StrategyConfig`, and uses that to call the initializer above.
-    pub async fn from_files<P: AsRef<Path>>(
+    /// and wraps them in a `StrategyConfig`.
+    pub fn from_files<P: AsRef<Path>>(
         neutron_path: P,
         eth_path: P,
         noble_path: P,
@@ -119,12 +52,104 @@ impl Strategy {
         let noble_cfg = NobleStrategyConfig::from_file(noble_path)
             .map_err(|e| anyhow!("invalid noble config: {:?}", e))?;
 
-        let strategy_cfg = StrategyConfig {
+        Ok(StrategyConfig {
             ethereum: eth_cfg,
             neutron: neutron_cfg,
             noble: noble_cfg,
-        };
+        })
+    }
+}
 
-        Self::new(strategy_cfg).await
+impl From<StrategyConfig> for StrategyLayout {
+    fn from(cfg: StrategyConfig) -> Self {
+        let neutron = cfg.neutron;
+        let accounts = neutron.accounts;
+        let denoms = neutron.denoms;
+
+        let balances = vec![
+            TrackedBalance {
+                domain: Domain::Noble,
+                address: cfg.noble.forwarding_account.to_string(),
+                denom: cfg.noble.chain_denom.to_string(),
+            },
+            TrackedBalance {
+                domain: Domain::Neutron,
+                address: accounts.deposit.to_string(),
+                denom: denoms.deposit_token.to_string(),
+            },
+            TrackedBalance {
+                domain: Domain::Neutron,
+                address: accounts.settlement.to_string(),
+                denom: denoms.deposit_token.to_string(),
+            },
+        ];
+
+        let mut positions = vec![Position::EthereumDeposit];
+        positions.extend(balances.into_iter().map(Position::Balance));
+        positions.push(Position::MarsLend {
+            credit_manager: neutron.mars_credit_manager,
+            owner: accounts.deposit.to_string(),
+        });
+
+        StrategyLayout {
+            ethereum: EthereumLayout {
+                rpc_url: cfg.ethereum.rpc_url,
+                authorizations: cfg.ethereum.authorizations,
+                one_way_vault: cfg.ethereum.libraries.one_way_vault,
+                deposit_account: cfg.ethereum.accounts.deposit,
+                deposit_token: cfg.ethereum.denoms.deposit_token,
+                transfer_threshold_amt: cfg.ethereum.ibc_transfer_threshold_amt,
+            },
+            neutron: NeutronLayout {
+                connection: ChainConnection {
+                    grpc_url: neutron.grpc_url,
+                    grpc_port: neutron.grpc_port,
+                    chain_id: neutron.chain_id,
+                },
+                authorizations: neutron.authorizations,
+                processor: neutron.processor,
+                deposit_account: accounts.deposit,
+                settlement_account: accounts.settlement,
+                deposit_denom: denoms.deposit_token.to_string(),
+                clearing_queue: neutron.libraries.clearing_queue,
+                clearing_queue_app_id: neutron.coprocessor_app_ids.clearing_queue,
+            },
+            route: DepositRoute::Cctp(CctpRoute {
+                noble: ChainConnection {
+                    grpc_url: cfg.noble.grpc_url,
+                    grpc_port: cfg.noble.grpc_port,
+                    chain_id: cfg.noble.chain_id,
+                },
+                noble_fee_denom: NOBLE_FEE_DENOM.to_string(),
+                cctp_transfer: cfg.ethereum.libraries.cctp_transfer,
+                arrival_tolerance_bps: CctpRoute::DEFAULT_ARRIVAL_TOLERANCE_BPS,
+                transit_expiry_secs: CctpRoute::DEFAULT_TRANSIT_EXPIRY_SECS,
+            }),
+            entry: PositionEntry {
+                label: LEND_AND_PROVIDE_LIQUIDITY_LABEL.to_string(),
+                actions: vec![EntryAction::Lend],
+                min_balance: MIN_ENTRY_BALANCE,
+            },
+            registration: RegistrationLayout::default(),
+            settlement: SettlementLayout {
+                denoms: vec![SettlementDenom {
+                    denom: denoms.deposit_token.to_string(),
+                    source: SettlementSource::MarsWithdraw,
+                }],
+                max_obligations_per_batch: SettlementLayout::DEFAULT_MAX_OBLIGATIONS_PER_BATCH,
+            },
+            update: UpdateLayout {
+                rate_scaling_factor: cfg.ethereum.rate_scaling_factor,
+                max_rate_increment_bps: cfg.ethereum.max_rate_increment_bps,
+                max_rate_decrement_bps: cfg.ethereum.max_rate_decrement_bps,
+                rate_guard: cfg.ethereum.rate_guard,
+                exclude_foreign_mars_debts: cfg.ethereum.exclude_foreign_mars_debts,
+                rebalance: None,
+                positions,
+            },
+            sentry: cfg.ethereum.sentry,
+            retry: cfg.ethereum.retry,
+            cycle: cfg.ethereum.cycle,
+        }
     }
 }
@@ -1,5 +1,9 @@
 use alloy::primitives::{Address, U256};
 use cosmwasm_std::Uint128;
+use packages::strategist::{
+    layout::{CycleLayout, RetryLayout, SentryLayout},
+    rate_guard::RateGuard,
+};
 use serde::{Deserialize, Serialize};
 use valence_strategist_utils::worker::ValenceWorkerTomlSerde;
 
@@ -33,6 +37,26 @@ pub struct EthereumStrategyConfig {
     pub accounts: EthereumAccounts,
     /// all libraries relevant to the eth-side of strategy
     pub libraries: EthereumLibraries,
+    /// leave Mars debts in denoms other than the deposit denom out of the
+    /// rate instead of failing the update. they cannot be valued, so the
+    /// rate overstates the assets by them; they are alerted on either way.
+    #[serde(default)]
+    pub exclude_foreign_mars_debts: bool,
+    /// checks of the newly calculated rate against the previously posted
+    /// rates, along with the action taken when they trip
+    #[serde(default)]
+    pub rate_guard: RateGuard,
+    /// conditions gating the strategist cycle, and how long before the
+    /// rate goes stale an update is forced regardless
+    #[serde(default)]
+    pub sentry: SentryLayout,
+    /// retry policies of the phases, each one defaulting to its own
+    #[serde(default)]
+    pub retry: RetryLayout,
+    /// order of the cycle phases, their dependencies and criticality,
+    /// validated when the strategist starts
+    #[serde(default)]
+    pub cycle: CycleLayout,
 }
 
 impl ValenceWorkerTomlSerde for EthereumStrategyConfig {}
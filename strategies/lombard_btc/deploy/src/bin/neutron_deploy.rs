@@ -629,6 +629,7 @@ async fn main() -> anyhow::Result<()> {
 
     let libraries = NeutronLibraries {
         deposit_splitter: deposit_splitter_library_address,
+        dynamic_ratio_query_provider: Some(dynamic_ratio_query_provider_address),
         mars_lending: mars_lending_library_address,
         supervault_lper: supervaults_lper_library_address,
         clearing_queue: clearing_queue_library_address,
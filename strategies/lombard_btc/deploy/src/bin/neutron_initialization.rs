@@ -6,8 +6,10 @@ use lombard_btc_types::neutron_config::NeutronStrategyConfig;
 use packages::{
     labels::{
         ICA_TRANSFER_LABEL, LEND_AND_PROVIDE_LIQUIDITY_LABEL, MARS_WITHDRAW_LABEL,
-        PHASE_SHIFT_LABEL, REGISTER_OBLIGATION_LABEL, SETTLE_OBLIGATION_LABEL,
+        PHASE_SHIFT_LABEL, REGISTER_OBLIGATION_LABEL, SETTLE_OBLIGATION_BATCH_LABEL,
+        SETTLE_OBLIGATION_LABEL,
     },
+    strategist::layout::SettlementLayout,
     types::inputs::ClearingQueueCoprocessorApp,
     utils::crypto_provider::setup_crypto_provider,
     verification::VERIFICATION_ROUTE,
@@ -277,7 +279,7 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let subroutine_settle_obligation = AtomicSubroutineBuilder::new()
-        .with_function(settle_obligation_function)
+        .with_function(settle_obligation_function.clone())
         .build();
     let authorization_settle_obligation = AuthorizationBuilder::new()
         .with_label(SETTLE_OBLIGATION_LABEL)
@@ -286,6 +288,22 @@ async fn main() -> anyhow::Result<()> {
         .build();
     authorizations.push(authorization_settle_obligation);
 
+    // Authorization for settling a full batch of obligations in one execution.
+    // Atomic subroutines take exactly one message per function, so the strategist
+    // only sends full batches through it and settles the rest one by one
+    let settle_batch_size = SettlementLayout::DEFAULT_MAX_OBLIGATIONS_PER_BATCH;
+    let subroutine_settle_obligation_batch = (0..settle_batch_size)
+        .fold(AtomicSubroutineBuilder::new(), |builder, _| {
+            builder.with_function(settle_obligation_function.clone())
+        })
+        .build();
+    let authorization_settle_obligation_batch = AuthorizationBuilder::new()
+        .with_label(SETTLE_OBLIGATION_BATCH_LABEL)
+        .with_mode(authorization_permissioned_mode.clone())
+        .with_subroutine(subroutine_settle_obligation_batch)
+        .build();
+    authorizations.push(authorization_settle_obligation_batch);
+
     //////// PHASE SHIFT AUTHORIZATION ////////
     // This authorization is special, it will be executed from the Neutron DAO via DAODAO
     // and involves multiple steps including updating configs with values we don't know during deployment
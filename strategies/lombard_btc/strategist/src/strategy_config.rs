@@ -1,20 +1,32 @@
-use std::{env, path::Path};
+use std::path::Path;
 
 use anyhow::anyhow;
 use lombard_btc_types::{
     ethereum_config::EthereumStrategyConfig, gaia_config::GaiaStrategyConfig,
     lombard_config::LombardStrategyConfig, neutron_config::NeutronStrategyConfig,
 };
-use packages::ibc_eureka_chain_ids::{EUREKA_COSMOS_HUB_CHAIN_ID, EUREKA_ETHEREUM_CHAIN_ID};
-use valence_domain_clients::clients::{
-    coprocessor::CoprocessorClient, ethereum::EthereumClient, gaia::CosmosHubClient,
-    ibc_eureka_route_client::IBCEurekaRouteClient, lombard::LombardClient, neutron::NeutronClient,
-    valence_indexer::OneWayVaultIndexerClient,
+use packages::{
+    labels::LEND_AND_PROVIDE_LIQUIDITY_LABEL,
+    strategist::{
+        layout::{
+            ChainConnection, DepositRoute, Domain, EntryAction, EthereumLayout, EurekaRoute,
+            LombardHop, NeutronLayout, Position, PositionEntry, RegistrationLayout,
+            SettlementDenom, SettlementLayout, SettlementSource, StrategyLayout, TrackedBalance,
+            UpdateLayout,
+        },
+        rebalance::Rebalancer,
+    },
 };
 
 use serde::{Deserialize, Serialize};
 use valence_strategist_utils::worker::ValenceWorkerTomlSerde;
 
+pub use packages::strategist::Strategy;
+
+/// there is 1 supervault and 1 mars lending, so we need at least 2 tokens
+/// to perform a split
+const MIN_SPLIT_BALANCE: u128 = 2;
+
 /// top-level config that wraps around each domain configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyConfig {
@@ -24,110 +36,7 @@ pub struct StrategyConfig {
     pub lombard: LombardStrategyConfig,
 }
 
-// main strategy struct that wraps around the StrategyConfig
-// and stores the initialized clients
-pub struct Strategy {
-    /// strategy name
-    pub label: String,
-
-    /// strategy timeout (in seconds)
-    pub timeout: u64,
-
-    /// top level strategy configuration
-    pub cfg: StrategyConfig,
-
-    /// active ethereum client
-    pub(crate) eth_client: EthereumClient,
-    /// active cosmos hub client
-    pub(crate) gaia_client: CosmosHubClient,
-    /// active neutron client
-    pub(crate) neutron_client: NeutronClient,
-    /// active lombard client
-    pub(crate) lombard_client: LombardClient,
-    /// active one way vault indexer client
-    pub(crate) indexer_client: OneWayVaultIndexerClient,
-    /// skip route client for IBC eureka
-    pub(crate) ibc_eureka_client: IBCEurekaRouteClient,
-    /// active coprocessor client
-    pub(crate) coprocessor_client: CoprocessorClient,
-}
-
-#[allow(dead_code)]
-impl Strategy {
-    /// strategy initializer that takes in a `StrategyConfig`, and uses it
-    /// to initialize the respective domain clients. prerequisite to starting
-    /// the strategist.
-    pub async fn new(cfg: StrategyConfig) -> anyhow::Result<Self> {
-        let mnemonic =
-            env::var("MNEMONIC").map_err(|e| anyhow!("mnemonic must be provided: {e}"))?;
-        let label = env::var("LABEL").map_err(|e| anyhow!("label must be provided: {e}"))?;
-        let indexer_api_key = env::var("INDEXER_API_KEY")
-            .map_err(|e| anyhow!("indexer api key must be provided: {e}"))?;
-        let indexer_api_url = env::var("INDEXER_API_URL")
-            .map_err(|e| anyhow!("indexer api url key must be provided: {e}"))?;
-        let eureka_api_url = env::var("EUREKA_API_URL")
-            .map_err(|e| anyhow!("IBC Eureka route api url must be provided: {e}"))?;
-        let strategy_timeout: u64 = env::var("STRATEGY_TIMEOUT")
-            .map_err(|e| anyhow!("Strategy timeout must be provided: {e}"))?
-            .parse()?;
-
-        let gaia_client = CosmosHubClient::new(
-            &cfg.gaia.grpc_url,
-            &cfg.gaia.grpc_port,
-            &mnemonic,
-            &cfg.gaia.chain_id,
-            &cfg.gaia.chain_denom,
-        )
-        .await?;
-
-        let lombard_client = LombardClient::new(
-            &cfg.lombard.grpc_url,
-            &cfg.lombard.grpc_port,
-            &mnemonic,
-            &cfg.lombard.chain_id,
-        )
-        .await?;
-
-        let neutron_client = NeutronClient::new(
-            &cfg.neutron.grpc_url,
-            &cfg.neutron.grpc_port,
-            &mnemonic,
-            &cfg.neutron.chain_id,
-        )
-        .await?;
-
-        let eth_client = EthereumClient::new(&cfg.ethereum.rpc_url, &mnemonic, None)?;
-
-        let indexer_client = OneWayVaultIndexerClient::new(
-            &indexer_api_url,
-            &indexer_api_key,
-            &cfg.ethereum.libraries.one_way_vault.to_string(),
-        );
-
-        let coprocessor_client = CoprocessorClient::default();
-
-        let ibc_eureka_client = IBCEurekaRouteClient::new(
-            &eureka_api_url,
-            EUREKA_ETHEREUM_CHAIN_ID,
-            &cfg.ethereum.denoms.deposit_token.to_string(),
-            EUREKA_COSMOS_HUB_CHAIN_ID,
-            &cfg.gaia.deposit_denom,
-        );
-
-        Ok(Self {
-            cfg,
-            timeout: strategy_timeout,
-            eth_client,
-            gaia_client,
-            neutron_client,
-            label,
-            indexer_client,
-            coprocessor_client,
-            ibc_eureka_client,
-            lombard_client,
-        })
-    }
-
+impl StrategyConfig {
     /// constructor helper that takes in four paths:
     /// - neutron config path
     /// - ethereum config path
@@ -135,8 +44,8 @@ impl Strategy {
     /// - lombard config path
     ///
     /// reads the configs from those paths, sets up each domain config,
-    /// wraps them in a `StrategyConfig`, and uses that to call the initializer above.
-    pub async fn from_files<P: AsRef<Path>>(
+    /// and wraps them in a `StrategyConfig`.
+    pub fn from_files<P: AsRef<Path>>(
         neutron_path: P,
         gaia_path: P,
         eth_path: P,
@@ -151,13 +60,158 @@ impl Strategy {
         let lombard_cfg = LombardStrategyConfig::from_file(lombard_path)
             .map_err(|e| anyhow!("invalid lombard config: {:?}", e))?;
 
-        let strategy_cfg = StrategyConfig {
+        Ok(StrategyConfig {
             ethereum: eth_cfg,
             neutron: neutron_cfg,
             gaia: gaia_cfg,
             lombard: lombard_cfg,
+        })
+    }
+}
+
+impl From<StrategyConfig> for StrategyLayout {
+    fn from(cfg: StrategyConfig) -> Self {
+        let neutron = cfg.neutron;
+        let accounts = neutron.accounts;
+        let denoms = neutron.denoms;
+
+        let neutron_deposit_balance = |address: &str| TrackedBalance {
+            domain: Domain::Neutron,
+            address: address.to_string(),
+            denom: denoms.deposit_token.to_string(),
         };
 
-        Self::new(strategy_cfg).await
+        let balances = vec![
+            TrackedBalance {
+                domain: Domain::Gaia,
+                address: cfg.gaia.ica_address.to_string(),
+                denom: cfg.gaia.deposit_denom.to_string(),
+            },
+            // this should always be zero, but just in case pfm from lombard to the hub
+            // fails, there may be some funds pending to be recovered into the program.
+            TrackedBalance {
+                domain: Domain::Lombard,
+                address: cfg.lombard.ica.to_string(),
+                denom: cfg.lombard.deposit_denom.to_string(),
+            },
+            neutron_deposit_balance(&accounts.deposit),
+            neutron_deposit_balance(&accounts.settlement),
+            neutron_deposit_balance(&accounts.mars_deposit),
+            neutron_deposit_balance(&accounts.supervault_deposit),
+        ];
+
+        let rebalance = neutron
+            .libraries
+            .dynamic_ratio_query_provider
+            .clone()
+            .map(|provider| Rebalancer {
+                dynamic_ratio_query_provider: provider,
+                mars_receiver: accounts.mars_deposit.to_string(),
+                supervault_receiver: accounts.supervault_deposit.to_string(),
+                credit_manager: neutron.mars_credit_manager.to_string(),
+                supervault: neutron.supervault.to_string(),
+                bounds: neutron.rebalance.clone(),
+            });
+
+        let mut positions = vec![Position::EthereumDeposit];
+        positions.extend(balances.into_iter().map(Position::Balance));
+        positions.extend([
+            Position::MarsLend {
+                credit_manager: neutron.mars_credit_manager,
+                owner: accounts.mars_deposit,
+            },
+            Position::Supervault {
+                vault: neutron.supervault,
+                depositor: accounts.supervault_deposit,
+                lp_holder: accounts.settlement.to_string(),
+            },
+        ]);
+
+        StrategyLayout {
+            ethereum: EthereumLayout {
+                rpc_url: cfg.ethereum.rpc_url,
+                authorizations: cfg.ethereum.authorizations,
+                one_way_vault: cfg.ethereum.libraries.one_way_vault,
+                deposit_account: cfg.ethereum.accounts.deposit,
+                deposit_token: cfg.ethereum.denoms.deposit_token,
+                transfer_threshold_amt: cfg.ethereum.ibc_transfer_threshold_amt,
+            },
+            neutron: NeutronLayout {
+                connection: ChainConnection {
+                    grpc_url: neutron.grpc_url,
+                    grpc_port: neutron.grpc_port,
+                    chain_id: neutron.chain_id,
+                },
+                authorizations: neutron.authorizations,
+                processor: neutron.processor,
+                deposit_account: accounts.deposit,
+                settlement_account: accounts.settlement,
+                deposit_denom: denoms.deposit_token.to_string(),
+                clearing_queue: neutron.libraries.clearing_queue,
+                clearing_queue_app_id: neutron.coprocessor_app_ids.clearing_queue,
+            },
+            route: DepositRoute::Eureka(EurekaRoute {
+                gaia: ChainConnection {
+                    grpc_url: cfg.gaia.grpc_url,
+                    grpc_port: cfg.gaia.grpc_port,
+                    chain_id: cfg.gaia.chain_id,
+                },
+                gaia_chain_denom: cfg.gaia.chain_denom,
+                gaia_ica: cfg.gaia.ica_address,
+                gaia_deposit_denom: cfg.gaia.deposit_denom,
+                neutron_ica: Some(accounts.gaia_ica),
+                coprocessor_app_id: cfg.ethereum.coprocessor_app_ids.ibc_eureka,
+                arrival_tolerance_bps: EurekaRoute::DEFAULT_ARRIVAL_TOLERANCE_BPS,
+                transit_expiry_secs: EurekaRoute::DEFAULT_TRANSIT_EXPIRY_SECS,
+                lombard: Some(LombardHop {
+                    connection: ChainConnection {
+                        grpc_url: cfg.lombard.grpc_url,
+                        grpc_port: cfg.lombard.grpc_port,
+                        chain_id: cfg.lombard.chain_id,
+                    },
+                    ica: cfg.lombard.ica,
+                    deposit_denom: cfg.lombard.deposit_denom,
+                    eureka_denom: cfg.lombard.eureka_denom,
+                    native_denom: cfg.lombard.native_denom,
+                    entry_contract: cfg.lombard.entry_contract,
+                    callback_contract: cfg.lombard.callback_contract,
+                }),
+            }),
+            entry: PositionEntry {
+                label: LEND_AND_PROVIDE_LIQUIDITY_LABEL.to_string(),
+                actions: vec![
+                    EntryAction::Split,
+                    EntryAction::Lend,
+                    EntryAction::ProvideLiquidity,
+                ],
+                min_balance: MIN_SPLIT_BALANCE,
+            },
+            registration: RegistrationLayout::default(),
+            settlement: SettlementLayout {
+                denoms: vec![
+                    SettlementDenom {
+                        denom: denoms.deposit_token,
+                        source: SettlementSource::MarsWithdraw,
+                    },
+                    SettlementDenom {
+                        denom: denoms.supervault_lp,
+                        source: SettlementSource::Held,
+                    },
+                ],
+                max_obligations_per_batch: SettlementLayout::DEFAULT_MAX_OBLIGATIONS_PER_BATCH,
+            },
+            update: UpdateLayout {
+                rate_scaling_factor: cfg.ethereum.rate_scaling_factor,
+                max_rate_increment_bps: cfg.ethereum.max_rate_increment_bps,
+                max_rate_decrement_bps: cfg.ethereum.max_rate_decrement_bps,
+                rate_guard: cfg.ethereum.rate_guard,
+                exclude_foreign_mars_debts: cfg.ethereum.exclude_foreign_mars_debts,
+                rebalance,
+                positions,
+            },
+            sentry: cfg.ethereum.sentry,
+            retry: cfg.ethereum.retry,
+            cycle: cfg.ethereum.cycle,
+        }
     }
 }
@@ -1,3 +1,4 @@
+use packages::strategist::rebalance::RebalanceBounds;
 use serde::{Deserialize, Serialize};
 use valence_strategist_utils::worker::ValenceWorkerTomlSerde;
 
@@ -28,6 +29,10 @@ pub struct NeutronStrategyConfig {
     pub libraries: NeutronLibraries,
     /// All IDs of the coprocessor apps
     pub coprocessor_app_ids: NeutronCoprocessorAppIds,
+    /// bounds of the split updates proposed once the dynamic ratio query
+    /// provider is set
+    #[serde(default)]
+    pub rebalance: RebalanceBounds,
 }
 
 impl ValenceWorkerTomlSerde for NeutronStrategyConfig {}
@@ -61,6 +66,10 @@ pub struct NeutronAccounts {
 pub struct NeutronLibraries {
     /// Deposit splitter where funds will be moved from deposit account to both Mars deposit or Supervault deposit according to the split ratio
     pub deposit_splitter: String,
+    /// Dynamic ratio query provider that the deposit splitter reads the split from.
+    /// Split updates are only proposed if it is set.
+    #[serde(default)]
+    pub dynamic_ratio_query_provider: Option<String>,
     /// Mars lending library
     pub mars_lending: String,
     /// Supervault lper
@@ -5,8 +5,10 @@ use maxbtc_mint_deploy::{INPUTS_DIR, OUTPUTS_DIR};
 use maxbtc_mint_types::neutron_config::NeutronStrategyConfig;
 use packages::{
     labels::{
-        ICA_TRANSFER_LABEL, MAXBTC_ISSUE_LABEL, REGISTER_OBLIGATION_LABEL, SETTLE_OBLIGATION_LABEL,
+        ICA_TRANSFER_LABEL, MAXBTC_ISSUE_LABEL, REGISTER_OBLIGATION_LABEL,
+        SETTLE_OBLIGATION_BATCH_LABEL, SETTLE_OBLIGATION_LABEL,
     },
+    strategist::layout::SettlementLayout,
     types::inputs::ClearingQueueCoprocessorApp,
     utils::crypto_provider::setup_crypto_provider,
     verification::VERIFICATION_ROUTE,
@@ -213,7 +215,7 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let subroutine_settle_obligation = AtomicSubroutineBuilder::new()
-        .with_function(settle_obligation_function)
+        .with_function(settle_obligation_function.clone())
         .build();
     let authorization_settle_obligation = AuthorizationBuilder::new()
         .with_label(SETTLE_OBLIGATION_LABEL)
@@ -222,6 +224,22 @@ async fn main() -> anyhow::Result<()> {
         .build();
     authorizations.push(authorization_settle_obligation);
 
+    // Authorization for settling a full batch of obligations in one execution.
+    // Atomic subroutines take exactly one message per function, so the strategist
+    // only sends full batches through it and settles the rest one by one
+    let settle_batch_size = SettlementLayout::DEFAULT_MAX_OBLIGATIONS_PER_BATCH;
+    let subroutine_settle_obligation_batch = (0..settle_batch_size)
+        .fold(AtomicSubroutineBuilder::new(), |builder, _| {
+            builder.with_function(settle_obligation_function.clone())
+        })
+        .build();
+    let authorization_settle_obligation_batch = AuthorizationBuilder::new()
+        .with_label(SETTLE_OBLIGATION_BATCH_LABEL)
+        .with_mode(authorization_permissioned_mode.clone())
+        .with_subroutine(subroutine_settle_obligation_batch)
+        .build();
+    authorizations.push(authorization_settle_obligation_batch);
+
     // Add all authorizations to the authorization contract
     let create_authorizations = valence_authorization_utils::msg::ExecuteMsg::PermissionedAction(
         valence_authorization_utils::msg::PermissionedMsg::CreateAuthorizations { authorizations },
@@ -5,10 +5,13 @@ use alloy::{
     sol_types::SolValue,
 };
 use cosmwasm_std::Uint128;
-use packages::types::sol_types::{
-    Authorization, BaseAccount, CCTPTransfer, CCTPTransferConfig, ERC1967Proxy,
-    OneWayVault::{self, FeeDistributionConfig, OneWayVaultConfig},
-    processor_contract::LiteProcessor,
+use packages::{
+    strategist::{layout::SentryLayout, rate_guard::RateGuard},
+    types::sol_types::{
+        Authorization, BaseAccount, CCTPTransfer, CCTPTransferConfig, ERC1967Proxy,
+        OneWayVault::{self, FeeDistributionConfig, OneWayVaultConfig},
+        processor_contract::LiteProcessor,
+    },
 };
 use serde::Deserialize;
 use usdc_deploy::{INPUTS_DIR, OUTPUTS_DIR};
@@ -244,6 +247,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
         libraries,
         max_rate_decrement_bps: u64::from(parameters.vault.max_rate_decrement_bps),
         max_rate_increment_bps: u64::from(parameters.vault.max_rate_increment_bps),
+        rate_guard: RateGuard::default(),
+        sentry: SentryLayout::default(),
     };
 
     println!("Ethereum Strategy Config created successfully");
@@ -5,12 +5,15 @@ use alloy::{
     sol_types::SolValue,
 };
 use cosmwasm_std::Uint128;
-use packages::types::{
-    inputs::{EurekaTransfer, EurekaTransferCoprocessorApp},
-    sol_types::{
-        Authorization, BaseAccount, ERC1967Proxy, IBCEurekaTransfer, IBCEurekaTransferConfig,
-        OneWayVault::{self, FeeDistributionConfig, OneWayVaultConfig},
-        processor_contract::LiteProcessor,
+use packages::{
+    strategist::{layout::SentryLayout, rate_guard::RateGuard},
+    types::{
+        inputs::{EurekaTransfer, EurekaTransferCoprocessorApp},
+        sol_types::{
+            Authorization, BaseAccount, ERC1967Proxy, IBCEurekaTransfer, IBCEurekaTransferConfig,
+            OneWayVault::{self, FeeDistributionConfig, OneWayVaultConfig},
+            processor_contract::LiteProcessor,
+        },
     },
 };
 use serde::Deserialize;
@@ -251,6 +254,8 @@ async fn main() -> anyhow::Result<()> {
         coprocessor_app_ids,
         max_rate_decrement_bps: u64::from(parameters.vault.max_rate_decrement_bps),
         max_rate_increment_bps: u64::from(parameters.vault.max_rate_increment_bps),
+        rate_guard: RateGuard::default(),
+        sentry: SentryLayout::default(),
     };
 
     println!("Ethereum Strategy Config created successfully");
@@ -1,10 +1,12 @@
-use std::{env, path::Path};
-
-use packages::ibc_eureka_chain_ids::{EUREKA_COSMOS_HUB_CHAIN_ID, EUREKA_ETHEREUM_CHAIN_ID};
-use valence_domain_clients::clients::{
-    coprocessor::CoprocessorClient, ethereum::EthereumClient, gaia::CosmosHubClient,
-    ibc_eureka_route_client::IBCEurekaRouteClient, neutron::NeutronClient,
-    valence_indexer::OneWayVaultIndexerClient,
+use std::path::Path;
+
+use packages::{
+    labels::LEND_AND_PROVIDE_LIQUIDITY_PHASE1_LABEL,
+    strategist::layout::{
+        ChainConnection, DepositRoute, Domain, EntryAction, EthereumLayout, EurekaRoute,
+        NeutronLayout, Position, PositionEntry, RegistrationLayout, SettlementDenom,
+        SettlementLayout, SettlementSource, StrategyLayout, TrackedBalance, UpdateLayout,
+    },
 };
 use wbtc_types::{
     ethereum_config::EthereumStrategyConfig, gaia_config::GaiaStrategyConfig,
@@ -14,6 +16,12 @@ use wbtc_types::{
 use serde::{Deserialize, Serialize};
 use valence_strategist_utils::worker::ValenceWorkerTomlSerde;
 
+pub use packages::strategist::Strategy;
+
+/// there are 6 supervaults and 1 mars lending, so we need at least 7 tokens
+/// to perform a split
+const MIN_SPLIT_BALANCE: u128 = 7;
+
 /// top-level config that wraps around each domain configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyConfig {
@@ -22,107 +30,15 @@ pub struct StrategyConfig {
     pub gaia: GaiaStrategyConfig,
 }
 
-// main strategy struct that wraps around the StrategyConfig
-// and stores the initialized clients
-pub struct Strategy {
-    /// strategy name
-    pub label: String,
-
-    /// strategy timeout (in seconds)
-    pub timeout: u64,
-
-    /// top level strategy configuration
-    pub cfg: StrategyConfig,
-
-    /// active ethereum client
-    pub(crate) eth_client: EthereumClient,
-    /// active cosmos hub client
-    pub(crate) gaia_client: CosmosHubClient,
-    /// active neutron client
-    pub(crate) neutron_client: NeutronClient,
-    /// active one way vault indexer client
-    pub(crate) indexer_client: OneWayVaultIndexerClient,
-    /// skip route client for IBC eureka
-    pub(crate) ibc_eureka_client: IBCEurekaRouteClient,
-    /// active coprocessor client
-    pub(crate) coprocessor_client: CoprocessorClient,
-}
-
-#[allow(dead_code)]
-impl Strategy {
-    /// strategy initializer that takes in a `StrategyConfig`, and uses it
-    /// to initialize the respective domain clients. prerequisite to starting
-    /// the strategist.
-    pub async fn new(cfg: StrategyConfig) -> anyhow::Result<Self> {
-        dotenv::dotenv().ok();
-        let mnemonic = env::var("MNEMONIC").expect("mnemonic must be provided");
-        let label = env::var("LABEL").expect("label must be provided");
-        let indexer_api_key =
-            env::var("INDEXER_API_KEY").expect("indexer api key must be provided");
-        let indexer_api_url =
-            env::var("INDEXER_API_URL").expect("indexer url key must be provided");
-        let eureka_api_url =
-            env::var("EUREKA_API_URL").expect("IBC Eureka route api url must be provided");
-        let strategy_timeout: u64 = env::var("STRATEGY_TIMEOUT")
-            .expect("Strategy timeout must be provided")
-            .parse()?;
-
-        let gaia_client = CosmosHubClient::new(
-            &cfg.gaia.grpc_url,
-            &cfg.gaia.grpc_port,
-            &mnemonic,
-            &cfg.gaia.chain_id,
-            &cfg.gaia.chain_denom,
-        )
-        .await?;
-
-        let neutron_client = NeutronClient::new(
-            &cfg.neutron.grpc_url,
-            &cfg.neutron.grpc_port,
-            &mnemonic,
-            &cfg.neutron.chain_id,
-        )
-        .await?;
-
-        let eth_client = EthereumClient::new(&cfg.ethereum.rpc_url, &mnemonic, None)?;
-
-        let indexer_client = OneWayVaultIndexerClient::new(
-            &indexer_api_url,
-            &indexer_api_key,
-            &cfg.ethereum.libraries.one_way_vault.to_string(),
-        );
-
-        let coprocessor_client = CoprocessorClient::default();
-
-        let ibc_eureka_client = IBCEurekaRouteClient::new(
-            &eureka_api_url,
-            EUREKA_ETHEREUM_CHAIN_ID,
-            &cfg.ethereum.denoms.deposit_token.to_string(),
-            EUREKA_COSMOS_HUB_CHAIN_ID,
-            &cfg.gaia.deposit_denom,
-        );
-
-        Ok(Self {
-            cfg,
-            timeout: strategy_timeout,
-            eth_client,
-            gaia_client,
-            neutron_client,
-            label,
-            indexer_client,
-            coprocessor_client,
-            ibc_eureka_client,
-        })
-    }
-
+impl StrategyConfig {
     /// constructor helper that takes in three paths:
     /// - neutron config path
     /// - ethereum config path
     /// - cosmos hub config path
     ///
     /// reads the configs from those paths, sets up each domain config,
-    /// wraps them in a `StrategyConfig`, and uses that to call the initializer above.
-    pub async fn from_files<P: AsRef<Path>>(
+    /// and wraps them in a `StrategyConfig`.
+    pub fn from_files<P: AsRef<Path>>(
         neutron_path: P,
         gaia_path: P,
         eth_path: P,
@@ -134,12 +50,163 @@ impl Strategy {
         let gaia_cfg = GaiaStrategyConfig::from_file(gaia_path)
             .map_err(|e| anyhow::anyhow!("invalid gaia config: {:?}", e))?;
 
-        let strategy_cfg = StrategyConfig {
+        Ok(StrategyConfig {
             ethereum: eth_cfg,
             neutron: neutron_cfg,
             gaia: gaia_cfg,
+        })
+    }
+}
+
+impl From<StrategyConfig> for StrategyLayout {
+    fn from(cfg: StrategyConfig) -> Self {
+        let neutron = cfg.neutron;
+        let accounts = neutron.accounts;
+        let denoms = neutron.denoms;
+
+        // (supervault, supervault deposit account) pairs, one per
+        // supervault that the deposits get split into
+        let supervaults = [
+            (
+                neutron.bedrockbtc_supervault,
+                accounts.bedrockbtc_supervault_deposit,
+            ),
+            (neutron.ebtc_supervault, accounts.ebtc_supervault_deposit),
+            (neutron.fbtc_supervault, accounts.fbtc_supervault_deposit),
+            (neutron.lbtc_supervault, accounts.lbtc_supervault_deposit),
+            (
+                neutron.pumpbtc_supervault,
+                accounts.pumpbtc_supervault_deposit,
+            ),
+            (
+                neutron.solvbtc_supervault,
+                accounts.solvbtc_supervault_deposit,
+            ),
+        ];
+
+        let neutron_deposit_balance = |address: &str| TrackedBalance {
+            domain: Domain::Neutron,
+            address: address.to_string(),
+            denom: denoms.deposit_token.to_string(),
         };
 
-        Self::new(strategy_cfg).await
+        let mut balances = vec![
+            TrackedBalance {
+                domain: Domain::Gaia,
+                address: cfg.gaia.ica_address.to_string(),
+                denom: cfg.gaia.deposit_denom.to_string(),
+            },
+            neutron_deposit_balance(&accounts.deposit),
+            neutron_deposit_balance(&accounts.settlement),
+            neutron_deposit_balance(&accounts.mars_deposit),
+        ];
+        balances.extend(
+            supervaults
+                .iter()
+                .map(|(_, deposit)| neutron_deposit_balance(deposit)),
+        );
+
+        let mut positions = vec![Position::EthereumDeposit];
+        positions.extend(balances.into_iter().map(Position::Balance));
+        positions.push(Position::MarsLend {
+            credit_manager: neutron.mars_credit_manager,
+            owner: accounts.mars_deposit,
+        });
+        positions.extend(
+            supervaults
+                .into_iter()
+                .map(|(vault, depositor)| Position::Supervault {
+                    vault,
+                    depositor,
+                    lp_holder: accounts.settlement.to_string(),
+                }),
+        );
+
+        // splitter routes the funds to mars and all of the supervault deposit
+        // accounts, followed by the mars lend and a LP for every supervault
+        let mut entry_actions = vec![EntryAction::Split, EntryAction::Lend];
+        entry_actions.extend([EntryAction::ProvideLiquidity; 6]);
+
+        let mut settlement_denoms = vec![SettlementDenom {
+            denom: denoms.deposit_token.to_string(),
+            source: SettlementSource::MarsWithdraw,
+        }];
+        settlement_denoms.extend(
+            [
+                denoms.lbtc_supervault_lp,
+                denoms.bedrockbtc_supervault_lp,
+                denoms.ebtc_supervault_lp,
+                denoms.fbtc_supervault_lp,
+                denoms.pumpbtc_supervault_lp,
+                denoms.solvbtc_supervault_lp,
+            ]
+            .into_iter()
+            .map(|denom| SettlementDenom {
+                denom,
+                source: SettlementSource::Held,
+            }),
+        );
+
+        StrategyLayout {
+            ethereum: EthereumLayout {
+                rpc_url: cfg.ethereum.rpc_url,
+                authorizations: cfg.ethereum.authorizations,
+                one_way_vault: cfg.ethereum.libraries.one_way_vault,
+                deposit_account: cfg.ethereum.accounts.deposit,
+                deposit_token: cfg.ethereum.denoms.deposit_token,
+                transfer_threshold_amt: cfg.ethereum.ibc_transfer_threshold_amt,
+            },
+            neutron: NeutronLayout {
+                connection: ChainConnection {
+                    grpc_url: neutron.grpc_url,
+                    grpc_port: neutron.grpc_port,
+                    chain_id: neutron.chain_id,
+                },
+                authorizations: neutron.authorizations,
+                processor: neutron.processor,
+                deposit_account: accounts.deposit,
+                settlement_account: accounts.settlement,
+                deposit_denom: denoms.deposit_token,
+                clearing_queue: neutron.libraries.clearing_queue,
+                clearing_queue_app_id: neutron.coprocessor_app_ids.clearing_queue,
+            },
+            route: DepositRoute::Eureka(EurekaRoute {
+                gaia: ChainConnection {
+                    grpc_url: cfg.gaia.grpc_url,
+                    grpc_port: cfg.gaia.grpc_port,
+                    chain_id: cfg.gaia.chain_id,
+                },
+                gaia_chain_denom: cfg.gaia.chain_denom,
+                gaia_ica: cfg.gaia.ica_address,
+                gaia_deposit_denom: cfg.gaia.deposit_denom,
+                neutron_ica: None,
+                coprocessor_app_id: cfg.ethereum.coprocessor_app_ids.ibc_eureka,
+                arrival_tolerance_bps: EurekaRoute::DEFAULT_ARRIVAL_TOLERANCE_BPS,
+                transit_expiry_secs: EurekaRoute::DEFAULT_TRANSIT_EXPIRY_SECS,
+                lombard: None,
+            }),
+            entry: PositionEntry {
+                label: LEND_AND_PROVIDE_LIQUIDITY_PHASE1_LABEL.to_string(),
+                actions: entry_actions,
+                min_balance: MIN_SPLIT_BALANCE,
+            },
+            registration: RegistrationLayout::default(),
+            settlement: SettlementLayout {
+                denoms: settlement_denoms,
+                max_obligations_per_batch: SettlementLayout::DEFAULT_MAX_OBLIGATIONS_PER_BATCH,
+            },
+            update: UpdateLayout {
+                rate_scaling_factor: cfg.ethereum.rate_scaling_factor,
+                max_rate_increment_bps: cfg.ethereum.max_rate_increment_bps,
+                max_rate_decrement_bps: cfg.ethereum.max_rate_decrement_bps,
+                rate_guard: cfg.ethereum.rate_guard,
+                exclude_foreign_mars_debts: cfg.ethereum.exclude_foreign_mars_debts,
+                rebalance: None,
+                positions,
+            },
+            sentry: cfg.ethereum.sentry,
+            retry: cfg.ethereum.retry,
+            cycle: cfg.ethereum.cycle,
+        }
     }
 }
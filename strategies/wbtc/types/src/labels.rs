@@ -3,6 +3,7 @@ pub const LEND_AND_PROVIDE_LIQUIDITY_PHASE1_LABEL: &str = "lend_and_provide_liqu
 pub const LEND_AND_PROVIDE_LIQUIDITY_PHASE2_LABEL: &str = "lend_and_provide_liquidity_phase2";
 pub const MARS_WITHDRAW_LABEL: &str = "mars_withdraw";
 pub const SETTLE_OBLIGATION_LABEL: &str = "settle_obligation";
+pub const SETTLE_OBLIGATION_BATCH_LABEL: &str = "settle_obligation_batch";
 pub const REGISTER_OBLIGATION_LABEL: &str = "register_obligation";
 pub const PHASE_SHIFT_STEP1_LABEL: &str = "phase_shift_step1";
 pub const PHASE_SHIFT_STEP2_LABEL: &str = "phase_shift_step2";